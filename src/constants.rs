@@ -1 +1,60 @@
-pub const LENDING_MARKET_SEED: &'static str = "lending_market";
+pub const LENDING_MARKET_SEED: &str = "lending_market";
+pub const RESERVE_SEED: &str = "reserve";
+pub const RESERVE_LIQUIDITY_SUPPLY_SEED: &str = "reserve_liquidity_supply";
+pub const RESERVE_COLLATERAL_MINT_SEED: &str = "reserve_collateral_mint";
+pub const RESERVE_COLLATERAL_SUPPLY_SEED: &str = "reserve_collateral_supply";
+pub const OBLIGATION_SEED: &str = "obligation";
+pub const MANUAL_PRICE_SEED: &str = "manual_price";
+pub const REFERRER_SEED: &str = "referrer";
+pub const QUEUED_CONFIG_CHANGE_SEED: &str = "queued_config_change";
+
+/// Layout version of lending market accounts written by this program.
+/// Older markets are brought up to it with `MigrateLendingMarket`.
+pub const LENDING_MARKET_VERSION: u64 = 1;
+
+/// Scale of every `_wads` fixed-point field (18 decimals).
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+/// Slots in a year at the nominal 400ms slot time, used to turn APRs into
+/// per-slot rates.
+pub const SLOTS_PER_YEAR: u64 = 78_840_000;
+
+/// Number of distinct collateral reserves an obligation can hold.
+pub const MAX_OBLIGATION_DEPOSITS: usize = 8;
+/// Number of distinct borrow reserves an obligation can hold.
+pub const MAX_OBLIGATION_BORROWS: usize = 5;
+
+/// Share of one borrow that a single liquidation may repay, until the market
+/// owner changes it.
+pub const DEFAULT_LIQUIDATION_CLOSE_FACTOR_PCT: u8 = 20;
+/// Obligations whose debt is worth less than this (quote wads) can be
+/// liquidated in full, so dust positions do not linger.
+pub const DEFAULT_MIN_FULL_LIQUIDATION_VALUE_THRESHOLD_WADS: u128 = 2 * WAD;
+/// Upper bound on a reserve's liquidation bonus.
+pub const MAX_LIQUIDATION_BONUS_BPS: u64 = 5_000;
+/// How fast a new reserve's liquidation threshold falls while it is being
+/// autodeleveraged, in basis points per day.
+pub const DEFAULT_AUTODELEVERAGE_RAMP_BPS_PER_DAY: u64 = 100;
+pub const SECONDS_PER_DAY: u64 = 86_400;
+/// Fee a new reserve charges on flash loans, in basis points of the amount.
+pub const DEFAULT_FLASH_LOAN_FEE_BPS: u64 = 30;
+/// `referrer_fee_pct` of reserves that pay referrers the market's
+/// `referral_fee_bps`. New reserves start with it.
+pub const REFERRER_FEE_PCT_INHERIT: u8 = u8::MAX;
+
+/// Oldest oracle price a new reserve accepts.
+pub const DEFAULT_MAX_PRICE_AGE_SECONDS: u64 = 60;
+/// Widest oracle confidence interval a new reserve accepts, in basis points
+/// of the price.
+pub const DEFAULT_MAX_PRICE_CONFIDENCE_BPS: u64 = 200;
+
+/// Number of price sources a reserve can combine.
+pub const MAX_ORACLE_SOURCES: usize = 3;
+
+/// Number of elevation groups a lending market can define. Group ids run
+/// from 1 to this value; 0 means no group.
+pub const MAX_ELEVATION_GROUPS: usize = 8;
+
+/// Longest governance delay a market can set, so a mistaken value cannot
+/// lock its configuration indefinitely.
+pub const MAX_GOVERNANCE_DELAY_SECONDS: u64 = 30 * SECONDS_PER_DAY;
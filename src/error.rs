@@ -0,0 +1,32 @@
+use pinocchio::program_error::ProgramError;
+
+/// Failures specific to this program, surfaced as `ProgramError::Custom`
+/// with the variant's value so clients can tell them apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlendError {
+    /// The deposit would take the reserve's supply past its deposit limit.
+    DepositLimitExceeded = 0,
+    /// The borrow would take the reserve's debt past its borrow limit.
+    BorrowLimitExceeded = 1,
+    /// The reserve's withdrawal cap for the current window is used up.
+    WithdrawalCapReached = 2,
+    /// The reserve's borrow cap for the current window is used up.
+    BorrowCapReached = 3,
+    /// The action is paused on the reserve.
+    ReserveActionPaused = 4,
+    /// The reserve is frozen or obsolete and takes no new positions.
+    ReserveNotActive = 5,
+    /// The market has a governance delay, so the change must be queued.
+    TimelockRequired = 6,
+    /// The queued change's governance delay has not elapsed yet.
+    TimelockNotElapsed = 7,
+    /// The borrow would take the obligation past the market's global
+    /// borrow value limit.
+    GlobalBorrowLimitExceeded = 8,
+}
+
+impl From<PlendError> for ProgramError {
+    fn from(error: PlendError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{helper::utils::load_account, state::LendingMarketState};
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
 
 #[inline(always)]
@@ -7,3 +8,21 @@ pub fn check_signer(account: &AccountInfo) -> Result<(), ProgramError> {
     }
     Ok(())
 }
+
+#[inline(always)]
+pub fn check_program_owner(account: &AccountInfo) -> Result<(), ProgramError> {
+    if !account.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Loads the lending market and rejects user actions while it is in emergency mode.
+pub fn check_market_operational(lending_market: &AccountInfo) -> Result<(), ProgramError> {
+    let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+    if lending_market_state.emergency_mode != 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
@@ -14,6 +14,9 @@ pub trait HasOwner {
 pub trait StateDefinition {
     const LEN: usize;
     const SEED: &'static str;
+    /// First 8 bytes of every account of the type, so one program account
+    /// can't be passed off as another of the same length.
+    const DISCRIMINATOR: [u8; 8];
 }
 
 #[inline(always)]
@@ -39,3 +42,28 @@ where
 
     Ok(())
 }
+
+/// Creates a PDA of `space` bytes owned by `owner`, e.g. a token account or
+/// mint that is later initialized through the token program.
+#[inline(always)]
+pub fn create_pda_account_owned_by(
+    payer: &AccountInfo,
+    account: &AccountInfo,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[Seed],
+    rent: &Rent,
+) -> Result<(), ProgramError> {
+    let signers = [Signer::from(signer_seeds)];
+
+    CreateAccount {
+        from: payer,
+        to: account,
+        space: space as u64,
+        owner,
+        lamports: rent.minimum_balance(space),
+    }
+    .invoke_signed(&signers)?;
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+//! WAD fixed-point arithmetic shared by every processor.
+//!
+//! All intermediate products are computed in 256 bits, so multiplying two
+//! in-range values never overflows before the final rescale. Every operation
+//! is checked and callers choose the rounding direction explicitly, which
+//! keeps results identical on BPF and host.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::WAD;
+
+#[allow(clippy::manual_div_ceil)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+pub use u256::U256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Unsigned fixed-point number with 18 decimals.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Decimal(U256);
+
+impl Decimal {
+    pub fn zero() -> Self {
+        Self(U256::zero())
+    }
+
+    pub fn one() -> Self {
+        Self(U256::from(WAD))
+    }
+
+    /// Reinterprets a stored `_wads` value.
+    pub fn from_wads(wads: u128) -> Self {
+        Self(U256::from(wads))
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        Self(U256::from(value) * U256::from(WAD))
+    }
+
+    pub fn from_pct(pct: u8) -> Self {
+        Self(U256::from(pct) * U256::from(WAD / 100))
+    }
+
+    pub fn from_bps(bps: u64) -> Self {
+        Self(U256::from(bps) * U256::from(WAD / 10_000))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Raw wads, for storing back into account state.
+    pub fn to_wads(&self) -> Result<u128, ProgramError> {
+        u128::try_from(self.0).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    /// Whole units, rounded as requested.
+    pub fn to_u64(&self, rounding: Rounding) -> Result<u64, ProgramError> {
+        let units = div_rounded(self.0, U256::from(WAD), rounding)?;
+        u64::try_from(units).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    pub fn try_add(self, rhs: Self) -> Result<Self, ProgramError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Self)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    pub fn try_sub(self, rhs: Self) -> Result<Self, ProgramError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Self)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    pub fn try_mul(self, rhs: Self, rounding: Rounding) -> Result<Self, ProgramError> {
+        let product = self
+            .0
+            .checked_mul(rhs.0)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(Self(div_rounded(product, U256::from(WAD), rounding)?))
+    }
+
+    /// Multiplies by a plain integer; exact, so no rounding is involved.
+    pub fn try_mul_u64(self, rhs: u64) -> Result<Self, ProgramError> {
+        self.0
+            .checked_mul(U256::from(rhs))
+            .map(Self)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    pub fn try_div(self, rhs: Self, rounding: Rounding) -> Result<Self, ProgramError> {
+        let scaled = self
+            .0
+            .checked_mul(U256::from(WAD))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(Self(div_rounded(scaled, rhs.0, rounding)?))
+    }
+
+    pub fn try_div_u64(self, rhs: u64, rounding: Rounding) -> Result<Self, ProgramError> {
+        Ok(Self(div_rounded(self.0, U256::from(rhs), rounding)?))
+    }
+
+    /// `self^exp` by binary exponentiation, rounding each step down.
+    pub fn try_pow(self, mut exp: u64) -> Result<Self, ProgramError> {
+        let mut base = self;
+        let mut result = Self::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.try_mul(base, Rounding::Down)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.try_mul(base, Rounding::Down)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[inline(always)]
+fn div_rounded(
+    numerator: U256,
+    denominator: U256,
+    rounding: Rounding,
+) -> Result<U256, ProgramError> {
+    if denominator.is_zero() {
+        return Err(ProgramError::ArithmeticOverflow);
+    }
+    let quotient = numerator / denominator;
+    match rounding {
+        Rounding::Down => Ok(quotient),
+        Rounding::Up if (numerator % denominator).is_zero() => Ok(quotient),
+        Rounding::Up => quotient
+            .checked_add(U256::one())
+            .ok_or(ProgramError::ArithmeticOverflow),
+    }
+}
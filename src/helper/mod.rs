@@ -1,9 +1,13 @@
 pub mod account_checks;
 pub mod account_close;
 pub mod account_init;
+pub mod math;
+pub mod token;
 pub mod utils;
 
 pub use account_checks::*;
 pub use account_close::*;
 pub use account_init::*;
+pub use math::*;
+pub use token::*;
 pub use utils::*;
@@ -0,0 +1,256 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction, Signer},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Size of an SPL `Mint` account without extensions.
+pub const MINT_LEN: usize = 82;
+/// Size of an SPL token `Account` without extensions.
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+const MINT_DECIMALS_OFFSET: usize = 44;
+const TOKEN_ACCOUNT_MINT_OFFSET: usize = 0;
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+
+#[inline(always)]
+pub fn check_token_program(token_program: &AccountInfo) -> Result<(), ProgramError> {
+    if token_program.key() != &TOKEN_PROGRAM_ID && token_program.key() != &TOKEN_2022_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+pub fn mint_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    check_token_program_owner(mint)?;
+    let data = mint.try_borrow_data()?;
+    if data.len() < MINT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(data[MINT_DECIMALS_OFFSET])
+}
+
+/// Checks that `mint` has no Token-2022 extensions. Vaults are sized for
+/// plain token accounts and moved with `Transfer`, which mints with
+/// extensions such as transfer fees do not support.
+pub fn check_mint_without_extensions(mint: &AccountInfo) -> Result<(), ProgramError> {
+    check_token_program_owner(mint)?;
+    if mint.data_len() != MINT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+/// Checks that `token_account` is a token account for `mint` owned by `owner`.
+pub fn check_token_account(
+    token_account: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<(), ProgramError> {
+    check_token_program_owner(token_account)?;
+    let data = token_account.try_borrow_data()?;
+    if data.len() < TOKEN_ACCOUNT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if &data[TOKEN_ACCOUNT_MINT_OFFSET..TOKEN_ACCOUNT_MINT_OFFSET + 32] != mint.as_ref()
+        || &data[TOKEN_ACCOUNT_OWNER_OFFSET..TOKEN_ACCOUNT_OWNER_OFFSET + 32] != owner.as_ref()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn check_token_program_owner(account: &AccountInfo) -> Result<(), ProgramError> {
+    if !account.is_owned_by(&TOKEN_PROGRAM_ID) && !account.is_owned_by(&TOKEN_2022_PROGRAM_ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+pub struct InitializeMint2<'a> {
+    pub token_program: &'a Pubkey,
+    pub mint: &'a AccountInfo,
+    pub decimals: u8,
+    pub mint_authority: &'a Pubkey,
+}
+
+impl InitializeMint2<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [AccountMeta::writable(self.mint.key())];
+
+        // - [0]    : instruction discriminator
+        // - [1]    : decimals
+        // - [2..34]: mint authority
+        // - [34]   : freeze authority option (none)
+        let mut instruction_data = [0u8; 35];
+        instruction_data[0] = 20;
+        instruction_data[1] = self.decimals;
+        instruction_data[2..34].copy_from_slice(self.mint_authority);
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke(&instruction, &[self.mint])
+    }
+}
+
+pub struct InitializeAccount3<'a> {
+    pub token_program: &'a Pubkey,
+    pub account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub owner: &'a Pubkey,
+}
+
+impl InitializeAccount3<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly(self.mint.key()),
+        ];
+
+        // - [0]    : instruction discriminator
+        // - [1..33]: owner
+        let mut instruction_data = [0u8; 33];
+        instruction_data[0] = 18;
+        instruction_data[1..33].copy_from_slice(self.owner);
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke(&instruction, &[self.account, self.mint])
+    }
+}
+
+pub struct Transfer<'a> {
+    pub token_program: &'a Pubkey,
+    pub from: &'a AccountInfo,
+    pub to: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+}
+
+impl Transfer<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    #[inline(always)]
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.from.key()),
+            AccountMeta::writable(self.to.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // - [0]   : instruction discriminator
+        // - [1..9]: amount
+        let mut instruction_data = [0u8; 9];
+        instruction_data[0] = 3;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(&instruction, &[self.from, self.to, self.authority], signers)
+    }
+}
+
+pub struct MintTo<'a> {
+    pub token_program: &'a Pubkey,
+    pub mint: &'a AccountInfo,
+    pub account: &'a AccountInfo,
+    pub mint_authority: &'a AccountInfo,
+    pub amount: u64,
+}
+
+impl MintTo<'_> {
+    #[inline(always)]
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly_signer(self.mint_authority.key()),
+        ];
+
+        // - [0]   : instruction discriminator
+        // - [1..9]: amount
+        let mut instruction_data = [0u8; 9];
+        instruction_data[0] = 7;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.mint, self.account, self.mint_authority],
+            signers,
+        )
+    }
+}
+
+pub struct Burn<'a> {
+    pub token_program: &'a Pubkey,
+    pub account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+}
+
+impl Burn<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    #[inline(always)]
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // - [0]   : instruction discriminator
+        // - [1..9]: amount
+        let mut instruction_data = [0u8; 9];
+        instruction_data[0] = 8;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &instruction_data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.account, self.mint, self.authority],
+            signers,
+        )
+    }
+}
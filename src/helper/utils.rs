@@ -1,4 +1,8 @@
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+use bytemuck::Pod;
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+};
 
 pub trait DataLen {
     const LEN: usize;
@@ -8,6 +12,9 @@ pub trait Initialized {
     fn is_initialized(&self) -> bool;
 }
 
+/// # Safety
+///
+/// `bytes` must be aligned for `T` and hold a valid bit pattern of `T`.
 #[inline(always)]
 pub unsafe fn load_acc<T: DataLen + Initialized>(bytes: &[u8]) -> Result<&T, ProgramError> {
     load_acc_unchecked::<T>(bytes).and_then(|acc| {
@@ -19,6 +26,9 @@ pub unsafe fn load_acc<T: DataLen + Initialized>(bytes: &[u8]) -> Result<&T, Pro
     })
 }
 
+/// # Safety
+///
+/// `bytes` must be aligned for `T` and hold a valid bit pattern of `T`.
 #[inline(always)]
 pub unsafe fn load_acc_unchecked<T: DataLen>(bytes: &[u8]) -> Result<&T, ProgramError> {
     if bytes.len() != T::LEN {
@@ -27,6 +37,9 @@ pub unsafe fn load_acc_unchecked<T: DataLen>(bytes: &[u8]) -> Result<&T, Program
     Ok(&*(bytes.as_ptr() as *const T))
 }
 
+/// # Safety
+///
+/// `bytes` must be aligned for `T` and hold a valid bit pattern of `T`.
 #[inline(always)]
 pub unsafe fn load_acc_mut<T: DataLen + Initialized>(
     bytes: &mut [u8],
@@ -40,6 +53,9 @@ pub unsafe fn load_acc_mut<T: DataLen + Initialized>(
     })
 }
 
+/// # Safety
+///
+/// `bytes` must be aligned for `T` and hold a valid bit pattern of `T`.
 #[inline(always)]
 pub unsafe fn load_acc_mut_unchecked<T: DataLen>(bytes: &mut [u8]) -> Result<&mut T, ProgramError> {
     if bytes.len() != T::LEN {
@@ -48,22 +64,74 @@ pub unsafe fn load_acc_mut_unchecked<T: DataLen>(bytes: &mut [u8]) -> Result<&mu
     Ok(&mut *(bytes.as_mut_ptr() as *mut T))
 }
 
+/// # Safety
+///
+/// `bytes` must be aligned for `T` and hold a valid bit pattern of `T`.
 #[inline(always)]
 pub unsafe fn load_ix_data<T: DataLen>(bytes: &[u8]) -> Result<&T, ProgramError> {
     if bytes.len() != T::LEN {
-        return Err(ProgramError::InvalidInstructionData.into());
+        return Err(ProgramError::InvalidInstructionData);
     }
     Ok(&*(bytes.as_ptr() as *const T))
 }
 
+/// # Safety
+///
+/// `T` must not contain padding bytes.
 pub unsafe fn to_bytes<T: DataLen>(data: &T) -> &[u8] {
     core::slice::from_raw_parts(data as *const T as *const u8, T::LEN)
 }
 
+/// # Safety
+///
+/// `T` must not contain padding bytes, and any bytes written must form a valid `T`.
 pub unsafe fn to_mut_bytes<T: DataLen>(data: &mut T) -> &mut [u8] {
     core::slice::from_raw_parts_mut(data as *mut T as *mut u8, T::LEN)
 }
 
+/// Borrows a program-owned account as `T`, checking its owner, length and
+/// discriminator. The borrow is tracked like `try_borrow_data`.
+pub fn load_account<T: Pod + DataLen + Initialized>(
+    acc: &AccountInfo,
+) -> Result<Ref<'_, T>, ProgramError> {
+    if !acc.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ref::try_map(acc.try_borrow_data()?, |bytes| {
+        check_account_data::<T>(bytes)?;
+        Ok(bytemuck::from_bytes(bytes))
+    })
+    .map_err(|(_, err)| err)
+}
+
+/// Mutable [`load_account`].
+pub fn load_account_mut<T: Pod + DataLen + Initialized>(
+    acc: &AccountInfo,
+) -> Result<RefMut<'_, T>, ProgramError> {
+    if !acc.is_owned_by(&crate::ID) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    RefMut::try_map(acc.try_borrow_mut_data()?, |bytes| {
+        check_account_data::<T>(bytes)?;
+        Ok(bytemuck::from_bytes_mut(bytes))
+    })
+    .map_err(|(_, err)| err)
+}
+
+fn check_account_data<T: Pod + DataLen + Initialized>(bytes: &[u8]) -> Result<(), ProgramError> {
+    if bytes.len() != T::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !bytemuck::from_bytes::<T>(bytes).is_initialized() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(())
+}
+
+/// # Safety
+///
+/// The account data must hold a valid `T`, and the caller must not alias the
+/// returned reference with another borrow of the same account data.
 pub unsafe fn try_from_account_info<T: DataLen>(acc: &AccountInfo) -> Result<&T, ProgramError> {
     if acc.owner() != &crate::ID {
         return Err(ProgramError::IllegalOwner);
@@ -76,6 +144,11 @@ pub unsafe fn try_from_account_info<T: DataLen>(acc: &AccountInfo) -> Result<&T,
     Ok(&*(bytes.as_ptr() as *const T))
 }
 
+/// # Safety
+///
+/// The account data must hold a valid `T`, and the caller must not alias the
+/// returned reference with another borrow of the same account data.
+#[allow(clippy::mut_from_ref)]
 pub unsafe fn try_from_account_info_mut<T: DataLen>(
     acc: &AccountInfo,
 ) -> Result<&mut T, ProgramError> {
@@ -0,0 +1,84 @@
+use crate::error::PlendError;
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_close::close_account,
+    utils::{load_account, load_account_mut},
+};
+use crate::instructions::governance::{is_manual_price, read_manual_price};
+use crate::instructions::market::{apply_lending_market_update, UpdateLendingMarketMode};
+use crate::instructions::oracles::apply_manual_price_update;
+use crate::instructions::reserves::{
+    apply_reserve_config_update, check_accrued_before_update, finish_autodeleverage_before_update,
+    UpdateReserveConfigMode,
+};
+use crate::state::{LendingMarketState, ManualPriceState, QueuedConfigChange, ReserveState};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// Applies a queued change whose delay has passed and closes its account,
+/// returning the rent to the owner.
+pub fn process_execute_config_change(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let [lending_market_owner, lending_market, target, queued_change, _remaining @ ..] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(target)?;
+    check_program_owner(queued_change)?;
+
+    let change = *load_account::<QueuedConfigChange>(queued_change)?;
+    if &change.lending_market != lending_market.key() || &change.target != target.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::get()?;
+    if !change.is_executable(clock.unix_timestamp) {
+        return Err(PlendError::TimelockNotElapsed.into());
+    }
+
+    {
+        let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
+
+        if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        if change.is_market_change() {
+            let mode = UpdateLendingMarketMode::try_from(change.mode)?;
+            apply_lending_market_update(
+                &mut lending_market_state,
+                mode,
+                change.value[..72].try_into().unwrap(),
+            )?;
+        }
+    }
+
+    if !change.is_market_change() {
+        if is_manual_price(target)? {
+            let mut manual_price_state = load_account_mut::<ManualPriceState>(target)?;
+            apply_manual_price_update(
+                &mut manual_price_state,
+                read_manual_price(&change.value),
+                &clock,
+            )?;
+        } else {
+            let mode = UpdateReserveConfigMode::try_from(change.mode)?;
+            let mut reserve_state = load_account_mut::<ReserveState>(target)?;
+            check_accrued_before_update(&reserve_state, mode)?;
+            finish_autodeleverage_before_update(&mut reserve_state, mode, &change.value)?;
+            apply_reserve_config_update(&mut reserve_state, mode, &change.value)?;
+        }
+    }
+
+    close_account(queued_change, lending_market_owner)
+}
@@ -0,0 +1,7 @@
+pub mod execute_config_change;
+pub mod queue_config_change;
+pub mod veto_config_change;
+
+pub use execute_config_change::*;
+pub use queue_config_change::*;
+pub use veto_config_change::*;
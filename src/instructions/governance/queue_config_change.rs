@@ -0,0 +1,164 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_init::{create_pda_account, StateDefinition},
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::instructions::market::{apply_lending_market_update, UpdateLendingMarketMode};
+use crate::instructions::oracles::apply_manual_price_update;
+use crate::instructions::reserves::{apply_reserve_config_update, UpdateReserveConfigMode};
+use crate::state::{LendingMarketState, ManualPriceState, QueuedConfigChange, ReserveState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+/// `mode` is an `UpdateReserveConfigMode` when the target is a reserve and a
+/// `UpdateLendingMarketMode` when it is the lending market; `value` is laid out as
+/// for that mode. For a manual price `mode` is 0 and `value` starts with the
+/// u128 price.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct QueueConfigChangeIxData {
+    pub mode: u64,
+    pub value: [u8; 128],
+}
+
+impl DataLen for QueueConfigChangeIxData {
+    const LEN: usize = core::mem::size_of::<QueueConfigChangeIxData>();
+}
+
+/// Queues a change to `target`, a reserve or manual price of the market or
+/// the market itself, that can be executed once the market's governance delay has
+/// passed. The value is validated now and again on execution.
+pub fn process_queue_config_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [lending_market_owner, lending_market, target, queued_change, rent_sysvar, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(target)?;
+
+    let ix_data =
+        bytemuck::from_bytes::<QueueConfigChangeIxData>(&data[..QueueConfigChangeIxData::LEN]);
+
+    let lending_market_state = *load_account::<LendingMarketState>(lending_market)?;
+    if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let clock = Clock::get()?;
+
+    // Apply the change to a copy so invalid values are rejected up front.
+    if target.key() == lending_market.key() {
+        let mode = UpdateLendingMarketMode::try_from(ix_data.mode)?;
+        let mut lending_market_state = lending_market_state;
+        apply_lending_market_update(
+            &mut lending_market_state,
+            mode,
+            ix_data.value[..72].try_into().unwrap(),
+        )?;
+    } else if is_manual_price(target)? {
+        if ix_data.mode != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut manual_price_state = *load_account::<ManualPriceState>(target)?;
+        if &manual_price_state.lending_market != lending_market.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        apply_manual_price_update(
+            &mut manual_price_state,
+            read_manual_price(&ix_data.value),
+            &clock,
+        )?;
+    } else {
+        let mode = UpdateReserveConfigMode::try_from(ix_data.mode)?;
+        let mut reserve_state = *load_account::<ReserveState>(target)?;
+        if &reserve_state.lending_market != lending_market.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        apply_reserve_config_update(&mut reserve_state, mode, &ix_data.value)?;
+    }
+
+    if !queued_change.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let id = lending_market_state.queued_config_change_count;
+    let id_bytes = id.to_le_bytes();
+    let (expected_queued_change_key, bump) = pubkey::find_program_address(
+        &[
+            QueuedConfigChange::SEED.as_bytes(),
+            lending_market.key().as_ref(),
+            &id_bytes,
+        ],
+        program_id,
+    );
+    if expected_queued_change_key != *queued_change.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let executable_at = clock
+        .unix_timestamp
+        .checked_add(lending_market_state.governance_delay_seconds as i64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let rent = Rent::from_account_info(rent_sysvar)?;
+    let bump_bytes = [bump];
+    let queued_change_seeds = [
+        Seed::from(QueuedConfigChange::SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(&id_bytes[..]),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    create_pda_account::<QueuedConfigChange>(
+        lending_market_owner,
+        queued_change,
+        &queued_change_seeds,
+        &rent,
+    )?;
+
+    {
+        let data = &mut queued_change.try_borrow_mut_data()?;
+        *bytemuck::from_bytes_mut::<QueuedConfigChange>(data) = QueuedConfigChange {
+            discriminator: QueuedConfigChange::DISCRIMINATOR,
+            version: 0,
+            lending_market: *lending_market.key(),
+            target: *target.key(),
+            mode: ix_data.mode,
+            value: ix_data.value,
+            id,
+            queued_at: clock.unix_timestamp,
+            executable_at,
+            bump,
+        };
+    }
+
+    load_account_mut::<LendingMarketState>(lending_market)?.queued_config_change_count = id + 1;
+
+    Ok(())
+}
+
+/// Whether a queued change's target is a manual price rather than a reserve.
+pub(crate) fn is_manual_price(target: &AccountInfo) -> Result<bool, ProgramError> {
+    Ok(target
+        .try_borrow_data()?
+        .starts_with(&ManualPriceState::DISCRIMINATOR))
+}
+
+#[inline(always)]
+pub(crate) fn read_manual_price(value: &[u8; 128]) -> u128 {
+    u128::from_le_bytes(value[..16].try_into().unwrap())
+}
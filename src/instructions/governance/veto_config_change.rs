@@ -0,0 +1,46 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_close::close_account,
+    utils::load_account,
+};
+use crate::state::{LendingMarketState, QueuedConfigChange};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// Discards a queued change before it is executed. The risk council vetoes
+/// with it, and the owner can use it to withdraw its own change; either way
+/// the rent goes back to the owner.
+pub fn process_veto_config_change(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [authority, lending_market, queued_change, lending_market_owner, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(authority)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(queued_change)?;
+
+    {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+        if authority.key() != &lending_market_state.lending_market_owner
+            && authority.key() != &lending_market_state.risk_council
+        {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    {
+        let change = load_account::<QueuedConfigChange>(queued_change)?;
+        if &change.lending_market != lending_market.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    close_account(queued_change, lending_market_owner)
+}
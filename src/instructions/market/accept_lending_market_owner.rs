@@ -0,0 +1,32 @@
+use crate::helper::{account_checks::check_signer, utils::load_account_mut};
+use crate::state::LendingMarketState;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// Completes an ownership transfer: the pending owner signs to become the
+/// lending market owner.
+pub fn process_accept_lending_market_owner(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let [new_owner, lending_market, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(new_owner)?;
+
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
+
+    if lending_market_state.pending_owner == Pubkey::default() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if new_owner.key() != &lending_market_state.pending_owner {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    lending_market_state.lending_market_owner = lending_market_state.pending_owner;
+    lending_market_state.pending_owner = Pubkey::default();
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::helper::{account_checks::check_signer, utils::load_account_mut};
+use crate::state::LendingMarketState;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// Completes a risk council change proposed through `UpdateRiskCouncil`:
+/// the pending council signs to take over.
+pub fn process_accept_risk_council(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let [new_risk_council, lending_market, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(new_risk_council)?;
+
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
+
+    if lending_market_state.pending_risk_council == Pubkey::default() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if new_risk_council.key() != &lending_market_state.pending_risk_council {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    lending_market_state.risk_council = lending_market_state.pending_risk_council;
+    lending_market_state.pending_risk_council = Pubkey::default();
+
+    Ok(())
+}
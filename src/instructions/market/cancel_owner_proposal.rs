@@ -0,0 +1,29 @@
+use crate::helper::{account_checks::check_signer, utils::load_account_mut};
+use crate::state::LendingMarketState;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// Withdraws any pending owner and risk council proposals. Only the current
+/// owner may cancel them.
+pub fn process_cancel_owner_proposal(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let [owner, lending_market, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
+
+    if owner.key() != &lending_market_state.lending_market_owner {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    lending_market_state.pending_owner = Pubkey::default();
+    lending_market_state.pending_risk_council = Pubkey::default();
+
+    Ok(())
+}
@@ -77,13 +77,13 @@ pub fn process_init_lending_market(accounts: &[AccountInfo], data: &[u8]) -> Pro
 
     let data = &mut lending_market.try_borrow_mut_data()?;
 
-    let lending_market_state = &mut bytemuck::from_bytes_mut::<LendingMarketState>(data);
+    let lending_market_state = bytemuck::from_bytes_mut::<LendingMarketState>(data);
 
-    lending_market_state.emergency_mode = 0;
-    lending_market_state.lending_market_owner = ix_data.lending_market_owner;
-    lending_market_state.quote_currency = ix_data.quote_currency;
-    lending_market_state.version = 0;
-    lending_market_state.risk_council = ix_data.risk_council;
+    *lending_market_state = LendingMarketState::new(
+        ix_data.lending_market_owner,
+        ix_data.quote_currency,
+        ix_data.risk_council,
+    );
 
     Ok(())
 }
@@ -0,0 +1,47 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_init::StateDefinition,
+};
+use crate::state::LendingMarketState;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::rent::Rent,
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+/// Brings a version 0 lending market up to the current layout: grows the
+/// account to the current size, with `payer` topping up its rent, and fills
+/// in the discriminator and the fields the old layout lacked. Anyone may
+/// migrate a market.
+pub fn process_migrate_lending_market(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let [payer, lending_market, rent_sysvar, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(payer)?;
+    check_program_owner(lending_market)?;
+
+    let migrated = LendingMarketState::from_v0(&lending_market.try_borrow_data()?)?;
+
+    let rent = Rent::from_account_info(rent_sysvar)?;
+    let minimum_balance = rent.minimum_balance(<LendingMarketState as StateDefinition>::LEN);
+    let lamports = lending_market.lamports();
+    if lamports < minimum_balance {
+        Transfer {
+            from: payer,
+            to: lending_market,
+            lamports: minimum_balance - lamports,
+        }
+        .invoke()?;
+    }
+
+    lending_market.resize(<LendingMarketState as StateDefinition>::LEN)?;
+
+    let data = &mut lending_market.try_borrow_mut_data()?;
+    *bytemuck::from_bytes_mut::<LendingMarketState>(data) = migrated;
+
+    Ok(())
+}
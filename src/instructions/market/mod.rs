@@ -1,12 +1,24 @@
+pub mod accept_lending_market_owner;
+pub mod accept_risk_council;
+pub mod cancel_owner_proposal;
 pub mod init_lending_market;
+pub mod migrate_lending_market;
+pub mod propose_lending_market_owner;
 pub mod set_emergency_mode;
-pub mod update_lending_market_owner;
+pub mod update_elevation_group;
+pub mod update_lending_market;
 pub mod update_risk_council;
 
+pub use accept_lending_market_owner::*;
+pub use accept_risk_council::*;
+pub use cancel_owner_proposal::*;
 pub use init_lending_market::*;
+pub use migrate_lending_market::*;
 #[allow(unused_imports)]
-pub use set_emergency_mode::*;
+pub use propose_lending_market_owner::*;
 #[allow(unused_imports)]
-pub use update_lending_market_owner::*;
+pub use set_emergency_mode::*;
+pub use update_elevation_group::*;
+pub use update_lending_market::*;
 #[allow(unused_imports)]
 pub use update_risk_council::*;
@@ -0,0 +1,52 @@
+use crate::helper::{
+    account_checks::check_signer,
+    utils::{load_account_mut, DataLen},
+};
+use crate::state::LendingMarketState;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ProposeLendingMarketOwnerIxData {
+    pub new_owner: Pubkey,
+}
+
+impl DataLen for ProposeLendingMarketOwnerIxData {
+    const LEN: usize = core::mem::size_of::<ProposeLendingMarketOwnerIxData>();
+}
+
+/// Records `new_owner` as the market's pending owner. Ownership only moves
+/// once `new_owner` signs `AcceptLendingMarketOwner`; until then the
+/// current owner keeps control and may replace or cancel the proposal.
+pub fn process_propose_lending_market_owner(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [current_owner, lending_market, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(current_owner)?;
+
+    let ix_data = bytemuck::from_bytes::<ProposeLendingMarketOwnerIxData>(
+        &data[..ProposeLendingMarketOwnerIxData::LEN],
+    );
+    if ix_data.new_owner == Pubkey::default() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
+
+    if current_owner.key() != &lending_market_state.lending_market_owner {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    lending_market_state.pending_owner = ix_data.new_owner;
+
+    Ok(())
+}
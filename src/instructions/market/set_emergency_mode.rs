@@ -1,6 +1,6 @@
 use crate::helper::{
     account_checks::check_signer,
-    utils::{ DataLen},
+    utils::{load_account_mut, DataLen},
 };
 use crate::state::LendingMarketState;
 use bytemuck::{Pod, Zeroable};
@@ -31,8 +31,7 @@ pub fn process_set_emergency_mode(
     let ix_data =
         bytemuck::from_bytes::<SetEmergencyModeIxData>(&data[..SetEmergencyModeIxData::LEN]);
 
-    let data = &mut lending_market.try_borrow_mut_data()?;
-    let lending_market_state = &mut bytemuck::from_bytes_mut::<LendingMarketState>(data);
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
 
     if authority.key() != &lending_market_state.lending_market_owner
         && authority.key() != &lending_market_state.risk_council
@@ -0,0 +1,77 @@
+use crate::error::PlendError;
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    utils::{load_account_mut, DataLen},
+};
+use crate::state::{ElevationGroup, LendingMarketState};
+use crate::{MAX_ELEVATION_GROUPS, MAX_LIQUIDATION_BONUS_BPS};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct UpdateElevationGroupIxData {
+    pub elevation_group: ElevationGroup,
+}
+
+impl DataLen for UpdateElevationGroupIxData {
+    const LEN: usize = core::mem::size_of::<UpdateElevationGroupIxData>();
+}
+
+/// Writes one entry of the market's elevation group table. A zeroed
+/// `debt_reserve` disables the group; obligations already in it fall back to
+/// their reserves' parameters on their next refresh. Markets with a
+/// governance delay queue the update through `QueueConfigChange` instead.
+pub fn process_update_elevation_group(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [lending_market_owner, lending_market, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+    check_program_owner(lending_market)?;
+
+    let ix_data = bytemuck::from_bytes::<UpdateElevationGroupIxData>(
+        &data[..UpdateElevationGroupIxData::LEN],
+    );
+    let elevation_group = ix_data.elevation_group;
+    validate_elevation_group(&elevation_group)?;
+
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
+
+    if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if lending_market_state.is_timelocked() {
+        return Err(PlendError::TimelockRequired.into());
+    }
+
+    lending_market_state.elevation_groups[elevation_group.id as usize - 1] = elevation_group;
+
+    Ok(())
+}
+
+pub(crate) fn validate_elevation_group(elevation_group: &ElevationGroup) -> ProgramResult {
+    let id = elevation_group.id as usize;
+    if id == 0 || id > MAX_ELEVATION_GROUPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !elevation_group.is_enabled() {
+        return Ok(());
+    }
+
+    let liquidation_bonus_bps = elevation_group.liquidation_bonus_bps;
+    if elevation_group.loan_to_value_pct > elevation_group.liquidation_threshold_pct
+        || elevation_group.liquidation_threshold_pct > 100
+        || liquidation_bonus_bps > MAX_LIQUIDATION_BONUS_BPS
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::error::PlendError;
+use crate::helper::{
+    account_checks::check_signer,
+    utils::{load_account_mut, DataLen},
+};
+use crate::instructions::market::validate_elevation_group;
+use crate::state::{ElevationGroup, LendingMarketState};
+use crate::MAX_GOVERNANCE_DELAY_SECONDS;
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpdateLendingMarketMode {
+    QuoteCurrency = 0,
+    ReferralFeeBps = 1,
+    /// u128 quote wads.
+    MinFullLiquidationValueThreshold = 2,
+    /// u128 quote wads.
+    GlobalAllowedBorrowValue = 3,
+    LiquidationCloseFactorPct = 4,
+    PriceRefreshTriggerToMaxAgePct = 5,
+    Name = 6,
+    /// Proposes the new risk council, which still has to accept.
+    RiskCouncil = 7,
+    GovernanceDelaySeconds = 8,
+    /// An `ElevationGroup`, written like `UpdateElevationGroup` does.
+    ElevationGroup = 9,
+}
+
+impl TryFrom<u64> for UpdateLendingMarketMode {
+    type Error = ProgramError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(UpdateLendingMarketMode::QuoteCurrency),
+            1 => Ok(UpdateLendingMarketMode::ReferralFeeBps),
+            2 => Ok(UpdateLendingMarketMode::MinFullLiquidationValueThreshold),
+            3 => Ok(UpdateLendingMarketMode::GlobalAllowedBorrowValue),
+            4 => Ok(UpdateLendingMarketMode::LiquidationCloseFactorPct),
+            5 => Ok(UpdateLendingMarketMode::PriceRefreshTriggerToMaxAgePct),
+            6 => Ok(UpdateLendingMarketMode::Name),
+            7 => Ok(UpdateLendingMarketMode::RiskCouncil),
+            8 => Ok(UpdateLendingMarketMode::GovernanceDelaySeconds),
+            9 => Ok(UpdateLendingMarketMode::ElevationGroup),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// `value` is little-endian and sized for the widest setting; narrower
+/// settings only read its leading bytes.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct UpdateLendingMarketIxData {
+    pub mode: u64,
+    pub value: [u8; 72],
+}
+
+impl DataLen for UpdateLendingMarketIxData {
+    const LEN: usize = core::mem::size_of::<UpdateLendingMarketIxData>();
+}
+
+/// Updates one market setting. Markets with a governance delay queue the
+/// update through `QueueConfigChange` instead.
+pub fn process_update_lending_market(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [lending_market_owner, lending_market, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+
+    let ix_data =
+        bytemuck::from_bytes::<UpdateLendingMarketIxData>(&data[..UpdateLendingMarketIxData::LEN]);
+    let mode = UpdateLendingMarketMode::try_from(ix_data.mode)?;
+
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
+
+    if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if lending_market_state.is_timelocked() {
+        return Err(PlendError::TimelockRequired.into());
+    }
+
+    apply_lending_market_update(&mut lending_market_state, mode, &ix_data.value)
+}
+
+/// Validates and writes a single market setting.
+pub fn apply_lending_market_update(
+    lending_market_state: &mut LendingMarketState,
+    mode: UpdateLendingMarketMode,
+    value: &[u8; 72],
+) -> ProgramResult {
+    match mode {
+        UpdateLendingMarketMode::QuoteCurrency => {
+            lending_market_state.quote_currency = value[..32].try_into().unwrap();
+        }
+        UpdateLendingMarketMode::ReferralFeeBps => {
+            let referral_fee_bps = read_u64(value);
+            if referral_fee_bps > 10_000 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            lending_market_state.referral_fee_bps = referral_fee_bps;
+        }
+        UpdateLendingMarketMode::MinFullLiquidationValueThreshold => {
+            lending_market_state.min_full_liquidation_value_threshold_wads = read_u128(value);
+        }
+        UpdateLendingMarketMode::GlobalAllowedBorrowValue => {
+            lending_market_state.global_allowed_borrow_value_wads = read_u128(value);
+        }
+        UpdateLendingMarketMode::LiquidationCloseFactorPct => {
+            let close_factor_pct = value[0];
+            if close_factor_pct == 0 || close_factor_pct > 100 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            lending_market_state.liquidation_max_debt_close_factor_pct = close_factor_pct;
+        }
+        UpdateLendingMarketMode::PriceRefreshTriggerToMaxAgePct => {
+            let trigger_pct = value[0];
+            if trigger_pct > 100 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            lending_market_state.price_refresh_trigger_to_max_age_pct = trigger_pct;
+        }
+        UpdateLendingMarketMode::Name => {
+            lending_market_state.name = value[..32].try_into().unwrap();
+        }
+        UpdateLendingMarketMode::RiskCouncil => {
+            let risk_council: Pubkey = value[..32].try_into().unwrap();
+            if risk_council == Pubkey::default() {
+                return Err(ProgramError::InvalidArgument);
+            }
+            lending_market_state.pending_risk_council = risk_council;
+        }
+        UpdateLendingMarketMode::GovernanceDelaySeconds => {
+            let governance_delay_seconds = read_u64(value);
+            if governance_delay_seconds > MAX_GOVERNANCE_DELAY_SECONDS {
+                return Err(ProgramError::InvalidArgument);
+            }
+            lending_market_state.governance_delay_seconds = governance_delay_seconds;
+        }
+        UpdateLendingMarketMode::ElevationGroup => {
+            let elevation_group = *bytemuck::from_bytes::<ElevationGroup>(
+                &value[..core::mem::size_of::<ElevationGroup>()],
+            );
+            validate_elevation_group(&elevation_group)?;
+            lending_market_state.elevation_groups[elevation_group.id as usize - 1] =
+                elevation_group;
+        }
+    }
+
+    Ok(())
+}
+
+#[inline(always)]
+fn read_u64(value: &[u8; 72]) -> u64 {
+    u64::from_le_bytes(value[..8].try_into().unwrap())
+}
+
+#[inline(always)]
+fn read_u128(value: &[u8; 72]) -> u128 {
+    u128::from_le_bytes(value[..16].try_into().unwrap())
+}
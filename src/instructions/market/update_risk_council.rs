@@ -1,6 +1,7 @@
+use crate::error::PlendError;
 use crate::helper::{
     account_checks::check_signer,
-    utils::{ DataLen},
+    utils::{load_account_mut, DataLen},
 };
 use crate::state::LendingMarketState;
 use bytemuck::{Pod, Zeroable};
@@ -17,6 +18,9 @@ impl DataLen for UpdateRiskCouncilIxData {
     const LEN: usize = core::mem::size_of::<UpdateRiskCouncilIxData>();
 }
 
+/// Proposes `new_risk_council`, which takes over once it signs
+/// `AcceptRiskCouncil`. Markets with a governance delay queue the proposal
+/// through `QueueConfigChange` instead.
 pub fn process_update_risk_council(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -30,15 +34,20 @@ pub fn process_update_risk_council(
 
     let ix_data =
         bytemuck::from_bytes::<UpdateRiskCouncilIxData>(&data[..UpdateRiskCouncilIxData::LEN]);
+    if ix_data.new_risk_council == Pubkey::default() {
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    let data = &mut lending_market.try_borrow_mut_data()?;
-    let lending_market_state = &mut bytemuck::from_bytes_mut::<LendingMarketState>(data);
+    let mut lending_market_state = load_account_mut::<LendingMarketState>(lending_market)?;
 
     if owner.key() != &lending_market_state.lending_market_owner {
         return Err(ProgramError::IllegalOwner);
     }
+    if lending_market_state.is_timelocked() {
+        return Err(PlendError::TimelockRequired.into());
+    }
 
-    lending_market_state.risk_council = ix_data.new_risk_council;
+    lending_market_state.pending_risk_council = ix_data.new_risk_council;
 
     Ok(())
 }
@@ -1,13 +1,61 @@
+pub mod governance;
 pub mod market;
+pub mod obligations;
+pub mod oracles;
+pub mod referrers;
+pub mod reserves;
 
+pub use governance::*;
 pub use market::*;
+pub use obligations::*;
+pub use oracles::*;
+pub use referrers::*;
+pub use reserves::*;
+
 use pinocchio::program_error::ProgramError;
 
+/// Discriminant 1 belonged to the one-step `UpdateLendingMarketOwner` and is
+/// left unused so transactions built for it fail instead of proposing an
+/// owner.
 pub enum PlendInstructions {
     InitLendingMarket = 0,
-    UpdateLendingMarketOwner = 1,
     SetEmergencyMode = 2,
     UpdateRiskCouncil = 3,
+    InitReserve = 4,
+    DepositReserveLiquidity = 5,
+    RedeemReserveCollateral = 6,
+    InitObligation = 7,
+    CloseObligation = 8,
+    UpdateReserveConfig = 9,
+    BorrowObligationLiquidity = 10,
+    RepayObligationLiquidity = 11,
+    DepositObligationCollateral = 12,
+    WithdrawObligationCollateral = 13,
+    DepositReserveLiquidityAndObligationCollateral = 14,
+    LiquidateObligation = 15,
+    RefreshReserve = 16,
+    RefreshObligation = 17,
+    InitManualPrice = 18,
+    UpdateManualPrice = 19,
+    UpdateElevationGroup = 20,
+    RequestElevationGroup = 21,
+    SetReserveAutodeleverage = 22,
+    LiquidateObligationAutodeleverage = 23,
+    FlashBorrowReserveLiquidity = 24,
+    FlashRepayReserveLiquidity = 25,
+    RedeemFees = 26,
+    InitReferrerState = 27,
+    WithdrawReferrerFees = 28,
+    SetReserveStatus = 29,
+    AcceptLendingMarketOwner = 30,
+    CancelOwnerProposal = 31,
+    AcceptRiskCouncil = 32,
+    QueueConfigChange = 33,
+    ExecuteConfigChange = 34,
+    VetoConfigChange = 35,
+    UpdateLendingMarket = 36,
+    MigrateLendingMarket = 37,
+    ProposeLendingMarketOwner = 38,
 }
 
 impl TryFrom<u8> for PlendInstructions {
@@ -16,9 +64,43 @@ impl TryFrom<u8> for PlendInstructions {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(PlendInstructions::InitLendingMarket),
-            1 => Ok(PlendInstructions::UpdateLendingMarketOwner),
             2 => Ok(PlendInstructions::SetEmergencyMode),
             3 => Ok(PlendInstructions::UpdateRiskCouncil),
+            4 => Ok(PlendInstructions::InitReserve),
+            5 => Ok(PlendInstructions::DepositReserveLiquidity),
+            6 => Ok(PlendInstructions::RedeemReserveCollateral),
+            7 => Ok(PlendInstructions::InitObligation),
+            8 => Ok(PlendInstructions::CloseObligation),
+            9 => Ok(PlendInstructions::UpdateReserveConfig),
+            10 => Ok(PlendInstructions::BorrowObligationLiquidity),
+            11 => Ok(PlendInstructions::RepayObligationLiquidity),
+            12 => Ok(PlendInstructions::DepositObligationCollateral),
+            13 => Ok(PlendInstructions::WithdrawObligationCollateral),
+            14 => Ok(PlendInstructions::DepositReserveLiquidityAndObligationCollateral),
+            15 => Ok(PlendInstructions::LiquidateObligation),
+            16 => Ok(PlendInstructions::RefreshReserve),
+            17 => Ok(PlendInstructions::RefreshObligation),
+            18 => Ok(PlendInstructions::InitManualPrice),
+            19 => Ok(PlendInstructions::UpdateManualPrice),
+            20 => Ok(PlendInstructions::UpdateElevationGroup),
+            21 => Ok(PlendInstructions::RequestElevationGroup),
+            22 => Ok(PlendInstructions::SetReserveAutodeleverage),
+            23 => Ok(PlendInstructions::LiquidateObligationAutodeleverage),
+            24 => Ok(PlendInstructions::FlashBorrowReserveLiquidity),
+            25 => Ok(PlendInstructions::FlashRepayReserveLiquidity),
+            26 => Ok(PlendInstructions::RedeemFees),
+            27 => Ok(PlendInstructions::InitReferrerState),
+            28 => Ok(PlendInstructions::WithdrawReferrerFees),
+            29 => Ok(PlendInstructions::SetReserveStatus),
+            30 => Ok(PlendInstructions::AcceptLendingMarketOwner),
+            31 => Ok(PlendInstructions::CancelOwnerProposal),
+            32 => Ok(PlendInstructions::AcceptRiskCouncil),
+            33 => Ok(PlendInstructions::QueueConfigChange),
+            34 => Ok(PlendInstructions::ExecuteConfigChange),
+            35 => Ok(PlendInstructions::VetoConfigChange),
+            36 => Ok(PlendInstructions::UpdateLendingMarket),
+            37 => Ok(PlendInstructions::MigrateLendingMarket),
+            38 => Ok(PlendInstructions::ProposeLendingMarketOwner),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
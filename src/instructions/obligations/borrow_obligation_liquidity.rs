@@ -0,0 +1,225 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::{Decimal, Rounding},
+    token::Transfer,
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::instructions::obligations::borrow_factor;
+use crate::{
+    error::PlendError,
+    state::{LendingMarketState, ObligationState, ReferrerState, ReserveAction, ReserveState},
+    RESERVE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct BorrowObligationLiquidityIxData {
+    pub liquidity_amount: u64,
+}
+
+impl DataLen for BorrowObligationLiquidityIxData {
+    const LEN: usize = core::mem::size_of::<BorrowObligationLiquidityIxData>();
+}
+
+/// The obligation and the borrow reserve must both have been refreshed in the
+/// current slot, with valid prices. A `ReferrerState` for the borrow reserve
+/// may follow the fixed accounts to credit its referrer with part of the
+/// origination fee.
+pub fn process_borrow_obligation_liquidity(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [owner, lending_market, obligation, borrow_reserve, reserve_liquidity_supply, user_destination_liquidity, token_program, remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+    check_program_owner(borrow_reserve)?;
+
+    let referrer_state = remaining.first();
+    if let Some(referrer_state) = referrer_state {
+        let referrer_state = load_account::<ReferrerState>(referrer_state)?;
+        if &referrer_state.reserve != borrow_reserve.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    let ix_data = bytemuck::from_bytes::<BorrowObligationLiquidityIxData>(
+        &data[..BorrowObligationLiquidityIxData::LEN],
+    );
+    let liquidity_amount = ix_data.liquidity_amount;
+    if liquidity_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let clock = Clock::get()?;
+
+    let (
+        borrowed_value,
+        borrow_factor_adjusted_debt_value,
+        allowed_borrow_value,
+        elevation_group_id,
+    ) = {
+        let obligation_state = load_account::<ObligationState>(obligation)?;
+
+        if &obligation_state.lending_market != lending_market.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &obligation_state.owner != owner.key() {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if !obligation_state.is_fresh(clock.slot) || !obligation_state.has_valid_prices() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        (
+            Decimal::from_wads(obligation_state.borrowed_value_wads),
+            Decimal::from_wads(obligation_state.borrow_factor_adjusted_debt_value_wads),
+            Decimal::from_wads(obligation_state.allowed_borrow_value_wads),
+            obligation_state.elevation_group,
+        )
+    };
+
+    // Obligations in an elevation group may only borrow its debt reserve,
+    // and do so without a borrow factor.
+    let (elevation_group, referral_fee_bps, global_allowed_borrow_value) = {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+        (
+            lending_market_state.elevation_group(elevation_group_id),
+            lending_market_state.referral_fee_bps,
+            Decimal::from_wads(lending_market_state.global_allowed_borrow_value_wads),
+        )
+    };
+    if elevation_group_id != 0 {
+        match elevation_group {
+            Some(group) if &group.debt_reserve == borrow_reserve.key() => {}
+            _ => return Err(ProgramError::InvalidArgument),
+        }
+    }
+
+    let (liquidity_mint, bump, cumulative_borrow_rate_wads, borrowed_wads, referrer_fee) = {
+        let mut reserve_state = load_account_mut::<ReserveState>(borrow_reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.liquidity_supply_vault != reserve_liquidity_supply.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        reserve_state.check_action(ReserveAction::Borrow)?;
+        if !reserve_state.is_fresh(clock.slot) || !reserve_state.is_price_valid() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if liquidity_amount > reserve_state.borrowable_liquidity()? {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        let borrow_factor = match elevation_group {
+            Some(_) => Decimal::one(),
+            None => borrow_factor(reserve_state.config.borrow_factor_pct)?,
+        };
+        // The origination fee is owed on top of the amount paid out.
+        let borrow_fee = reserve_state.borrow_fee(liquidity_amount)?;
+        let borrowed_amount = Decimal::from_u64(liquidity_amount).try_add(borrow_fee)?;
+        let borrow_value = reserve_state.market_value(borrowed_amount)?;
+        let new_debt_value = borrow_factor_adjusted_debt_value
+            .try_add(borrow_value.try_mul(borrow_factor, Rounding::Up)?)?;
+        if new_debt_value > allowed_borrow_value {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if borrowed_value.try_add(borrow_value)? > global_allowed_borrow_value {
+            return Err(PlendError::GlobalBorrowLimitExceeded.into());
+        }
+
+        let total_borrowed =
+            Decimal::from_wads(reserve_state.borrowed_amount_wads).try_add(borrowed_amount)?;
+        if total_borrowed > Decimal::from_u64(reserve_state.config.borrow_limit) {
+            return Err(PlendError::BorrowLimitExceeded.into());
+        }
+        if !reserve_state
+            .borrow_cap
+            .try_consume(liquidity_amount, clock.unix_timestamp)
+        {
+            return Err(PlendError::BorrowCapReached.into());
+        }
+
+        let borrowed_wads = borrowed_amount.to_wads()?;
+        reserve_state.available_liquidity -= liquidity_amount;
+        reserve_state.borrowed_amount_wads = reserve_state
+            .borrowed_amount_wads
+            .checked_add(borrowed_wads)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let referrer_fee = match referrer_state {
+            Some(_) => borrow_fee.try_mul(
+                reserve_state.config.referrer_share(referral_fee_bps),
+                Rounding::Down,
+            )?,
+            None => Decimal::zero(),
+        };
+        reserve_state.add_protocol_fee(borrow_fee.try_sub(referrer_fee)?)?;
+        reserve_state.add_referrer_fee(referrer_fee)?;
+
+        (
+            reserve_state.liquidity_mint,
+            reserve_state.bump,
+            reserve_state.cumulative_borrow_rate_wads,
+            borrowed_wads,
+            referrer_fee,
+        )
+    };
+
+    if let Some(referrer_state) = referrer_state {
+        let mut referrer_state = load_account_mut::<ReferrerState>(referrer_state)?;
+        referrer_state.unclaimed_fees_wads = Decimal::from_wads(referrer_state.unclaimed_fees_wads)
+            .try_add(referrer_fee)?
+            .to_wads()?;
+    }
+
+    {
+        let mut obligation_state = load_account_mut::<ObligationState>(obligation)?;
+
+        let borrow = obligation_state
+            .find_or_add_borrow(borrow_reserve.key(), cumulative_borrow_rate_wads)?;
+        borrow.accrue_interest(cumulative_borrow_rate_wads)?;
+        borrow.borrowed_amount_wads = borrow
+            .borrowed_amount_wads
+            .checked_add(borrowed_wads)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        obligation_state.mark_stale();
+    }
+
+    let bump_bytes = [bump];
+    let reserve_seeds = [
+        Seed::from(RESERVE_SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(liquidity_mint.as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    Transfer {
+        token_program: token_program.key(),
+        from: reserve_liquidity_supply,
+        to: user_destination_liquidity,
+        authority: borrow_reserve,
+        amount: liquidity_amount,
+    }
+    .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+    Ok(())
+}
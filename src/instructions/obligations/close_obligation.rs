@@ -0,0 +1,32 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_close::close_account,
+    utils::load_account,
+};
+use crate::state::ObligationState;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+pub fn process_close_obligation(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [owner, obligation, destination, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+    check_program_owner(obligation)?;
+
+    {
+        let obligation_state = load_account::<ObligationState>(obligation)?;
+
+        if owner.key() != &obligation_state.owner {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        if !obligation_state.is_empty() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    close_account(obligation, destination)
+}
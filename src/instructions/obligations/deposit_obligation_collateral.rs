@@ -0,0 +1,127 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    token::Transfer,
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::state::{ObligationState, ReserveAction, ReserveState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct DepositObligationCollateralIxData {
+    pub collateral_amount: u64,
+}
+
+impl DataLen for DepositObligationCollateralIxData {
+    const LEN: usize = core::mem::size_of::<DepositObligationCollateralIxData>();
+}
+
+pub fn process_deposit_obligation_collateral(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [owner, lending_market, obligation, deposit_reserve, reserve_collateral_supply, user_source_collateral, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+    check_program_owner(deposit_reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<DepositObligationCollateralIxData>(
+        &data[..DepositObligationCollateralIxData::LEN],
+    );
+    let collateral_amount = ix_data.collateral_amount;
+    if collateral_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    {
+        let reserve_state = load_account::<ReserveState>(deposit_reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.collateral_supply_vault != reserve_collateral_supply.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        reserve_state.check_action(ReserveAction::Deposit)?;
+    }
+
+    let clock = Clock::get()?;
+    credit_obligation_collateral(
+        owner,
+        lending_market,
+        obligation,
+        deposit_reserve,
+        collateral_amount,
+        clock.slot,
+    )?;
+
+    Transfer {
+        token_program: token_program.key(),
+        from: user_source_collateral,
+        to: reserve_collateral_supply,
+        authority: owner,
+        amount: collateral_amount,
+    }
+    .invoke()?;
+
+    Ok(())
+}
+
+/// Adds `collateral_amount` to the obligation's deposit slot for
+/// `deposit_reserve`, after checking the obligation belongs to `owner` on
+/// `lending_market`, was refreshed in `current_slot` and, if it is in an
+/// elevation group, that the reserve belongs to the group.
+pub fn credit_obligation_collateral(
+    owner: &AccountInfo,
+    lending_market: &AccountInfo,
+    obligation: &AccountInfo,
+    deposit_reserve: &AccountInfo,
+    collateral_amount: u64,
+    current_slot: u64,
+) -> ProgramResult {
+    let mut obligation_state = load_account_mut::<ObligationState>(obligation)?;
+
+    if &obligation_state.lending_market != lending_market.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if &obligation_state.owner != owner.key() {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if !obligation_state.is_fresh(current_slot) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if obligation_state.elevation_group != 0 {
+        let reserve_state = load_account::<ReserveState>(deposit_reserve)?;
+        if !reserve_state
+            .config
+            .is_in_elevation_group(obligation_state.elevation_group)
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    let deposit = obligation_state.find_or_add_deposit(deposit_reserve.key())?;
+    deposit.deposited_amount = deposit
+        .deposited_amount
+        .checked_add(collateral_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    obligation_state.mark_stale();
+
+    Ok(())
+}
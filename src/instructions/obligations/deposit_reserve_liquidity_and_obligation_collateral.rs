@@ -0,0 +1,78 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    utils::{load_account, DataLen},
+};
+use crate::instructions::{obligations::credit_obligation_collateral, reserves::DepositLiquidity};
+use crate::state::ReserveState;
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct DepositReserveLiquidityAndObligationCollateralIxData {
+    pub liquidity_amount: u64,
+}
+
+impl DataLen for DepositReserveLiquidityAndObligationCollateralIxData {
+    const LEN: usize = core::mem::size_of::<DepositReserveLiquidityAndObligationCollateralIxData>();
+}
+
+/// Deposits liquidity into a reserve and locks the minted collateral in the
+/// obligation without it passing through a user token account.
+pub fn process_deposit_reserve_liquidity_and_obligation_collateral(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [owner, lending_market, obligation, reserve, reserve_liquidity_supply, reserve_collateral_mint, reserve_collateral_supply, user_source_liquidity, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+    check_program_owner(reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<DepositReserveLiquidityAndObligationCollateralIxData>(
+        &data[..DepositReserveLiquidityAndObligationCollateralIxData::LEN],
+    );
+
+    {
+        let reserve_state = load_account::<ReserveState>(reserve)?;
+
+        if &reserve_state.collateral_supply_vault != reserve_collateral_supply.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    let collateral_amount = DepositLiquidity {
+        user: owner,
+        lending_market,
+        reserve,
+        reserve_liquidity_supply,
+        reserve_collateral_mint,
+        user_source_liquidity,
+        destination_collateral: reserve_collateral_supply,
+        token_program,
+        liquidity_amount: ix_data.liquidity_amount,
+    }
+    .process()?;
+
+    let clock = Clock::get()?;
+    credit_obligation_collateral(
+        owner,
+        lending_market,
+        obligation,
+        reserve,
+        collateral_amount,
+        clock.slot,
+    )
+}
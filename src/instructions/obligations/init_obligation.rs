@@ -0,0 +1,88 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_init::{create_pda_account, StateDefinition},
+    utils::{load_account, DataLen},
+};
+use crate::state::{LendingMarketState, ObligationState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct InitObligationIxData {
+    /// Caller-chosen id so one wallet can hold several obligations per market.
+    pub id: u64,
+}
+
+impl DataLen for InitObligationIxData {
+    const LEN: usize = core::mem::size_of::<InitObligationIxData>();
+}
+
+pub fn process_init_obligation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [owner, lending_market, obligation, rent_sysvar, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+    check_program_owner(lending_market)?;
+
+    load_account::<LendingMarketState>(lending_market)?;
+
+    if !obligation.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let ix_data = bytemuck::from_bytes::<InitObligationIxData>(&data[..InitObligationIxData::LEN]);
+    let id_bytes = ix_data.id.to_le_bytes();
+
+    let (expected_obligation_key, bump) = pubkey::find_program_address(
+        &[
+            ObligationState::SEED.as_bytes(),
+            lending_market.key().as_ref(),
+            owner.key().as_ref(),
+            &id_bytes,
+        ],
+        program_id,
+    );
+    if expected_obligation_key != *obligation.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_sysvar)?;
+    let bump_bytes = [bump];
+    let obligation_seeds = [
+        Seed::from(ObligationState::SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(owner.key().as_ref()),
+        Seed::from(&id_bytes[..]),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    create_pda_account::<ObligationState>(owner, obligation, &obligation_seeds, &rent)?;
+
+    let clock = Clock::get()?;
+
+    let data = &mut obligation.try_borrow_mut_data()?;
+    let obligation_state = bytemuck::from_bytes_mut::<ObligationState>(data);
+
+    *obligation_state = ObligationState::new(
+        *lending_market.key(),
+        *owner.key(),
+        ix_data.id,
+        clock.slot,
+        bump,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,414 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::{Decimal, Rounding},
+    token::{Burn, Transfer},
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::{
+    state::{
+        LendingMarketState, ObligationCollateral, ObligationLiquidity, ObligationState,
+        ReserveAction, ReserveState,
+    },
+    RESERVE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// `liquidity_amount` is the most the liquidator is willing to repay; the
+/// close factor may cap it further.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct LiquidateObligationIxData {
+    pub liquidity_amount: u64,
+}
+
+impl DataLen for LiquidateObligationIxData {
+    const LEN: usize = core::mem::size_of::<LiquidateObligationIxData>();
+}
+
+/// The obligation and both reserves must have been refreshed in the current
+/// slot, with valid prices.
+pub fn process_liquidate_obligation(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [liquidator, lending_market, obligation, repay_reserve, repay_reserve_liquidity_supply, withdraw_reserve, withdraw_reserve_collateral_supply, withdraw_reserve_collateral_mint, liquidator_source_liquidity, liquidator_destination_collateral, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(liquidator)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+    check_program_owner(repay_reserve)?;
+    check_program_owner(withdraw_reserve)?;
+
+    let ix_data =
+        bytemuck::from_bytes::<LiquidateObligationIxData>(&data[..LiquidateObligationIxData::LEN]);
+    let liquidity_amount = ix_data.liquidity_amount;
+    if liquidity_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let clock = Clock::get()?;
+
+    let (borrowed_value_wads, elevation_group_id) = {
+        let obligation_state = load_account::<ObligationState>(obligation)?;
+
+        if &obligation_state.lending_market != lending_market.key()
+            || !obligation_state.is_fresh(clock.slot)
+            || !obligation_state.has_valid_prices()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !obligation_state.is_unhealthy() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        (
+            obligation_state.borrowed_value_wads,
+            obligation_state.elevation_group,
+        )
+    };
+
+    let (max_repay_pct, bonus_bps) = liquidation_terms(
+        lending_market,
+        withdraw_reserve,
+        borrowed_value_wads,
+        elevation_group_id,
+    )?;
+
+    Liquidate {
+        liquidator,
+        lending_market,
+        obligation,
+        repay_reserve,
+        repay_reserve_liquidity_supply,
+        withdraw_reserve,
+        withdraw_reserve_collateral_supply,
+        withdraw_reserve_collateral_mint,
+        liquidator_source_liquidity,
+        liquidator_destination_collateral,
+        token_program,
+    }
+    .process(liquidity_amount, max_repay_pct, bonus_bps, clock.slot)?;
+
+    Ok(())
+}
+
+/// The share of a borrow one liquidation may repay and the bonus paid on it,
+/// for an obligation owing `borrowed_value_wads` in `elevation_group_id`.
+pub(crate) fn liquidation_terms(
+    lending_market: &AccountInfo,
+    withdraw_reserve: &AccountInfo,
+    borrowed_value_wads: u128,
+    elevation_group_id: u8,
+) -> Result<(u8, u64), ProgramError> {
+    let (close_factor_pct, min_full_liquidation_value_threshold_wads, elevation_group) = {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+        (
+            lending_market_state.liquidation_max_debt_close_factor_pct,
+            lending_market_state.min_full_liquidation_value_threshold_wads,
+            lending_market_state.elevation_group(elevation_group_id),
+        )
+    };
+
+    let max_repay_pct = if borrowed_value_wads < min_full_liquidation_value_threshold_wads {
+        100
+    } else {
+        close_factor_pct
+    };
+
+    let bonus_bps = match elevation_group {
+        Some(group) => group.liquidation_bonus_bps,
+        None => {
+            load_account::<ReserveState>(withdraw_reserve)?
+                .config
+                .liquidation_bonus_bps
+        }
+    };
+
+    Ok((max_repay_pct, bonus_bps))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LiquidationResult {
+    /// Liquidity the liquidator pays in.
+    pub repay_amount: u64,
+    /// Debt removed from the obligation.
+    pub settle_amount_wads: u128,
+    /// Collateral the liquidator receives.
+    pub withdraw_amount: u64,
+}
+
+/// Sizes a liquidation of one borrow against one deposit.
+///
+/// At most `max_repay_pct` of the borrow is repaid, and the liquidator is
+/// paid the repaid value plus `bonus_bps` in collateral. When the deposit
+/// cannot cover that, the whole deposit is seized and the repayment shrinks
+/// to match.
+pub fn calculate_liquidation(
+    repay_reserve: &ReserveState,
+    withdraw_reserve: &ReserveState,
+    borrowed_amount_wads: u128,
+    deposited_amount: u64,
+    liquidity_amount: u64,
+    max_repay_pct: u8,
+    bonus_bps: u64,
+) -> Result<LiquidationResult, ProgramError> {
+    let borrowed_amount = Decimal::from_wads(borrowed_amount_wads);
+    let max_repay = borrowed_amount.try_mul(Decimal::from_pct(max_repay_pct), Rounding::Down)?;
+    let mut repay_amount = Decimal::from_u64(liquidity_amount)
+        .min(max_repay)
+        .to_u64(Rounding::Up)?;
+
+    let repay_value = repay_reserve.market_value(Decimal::from_u64(repay_amount))?;
+    let bonus_value = repay_value.try_mul(
+        Decimal::one().try_add(Decimal::from_bps(bonus_bps))?,
+        Rounding::Down,
+    )?;
+
+    let deposit_liquidity = withdraw_reserve.collateral_to_liquidity(deposited_amount)?;
+    let deposit_value = withdraw_reserve.market_value(Decimal::from_u64(deposit_liquidity))?;
+    if deposit_value.is_zero() || bonus_value.is_zero() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let withdraw_amount = if bonus_value >= deposit_value {
+        repay_amount = Decimal::from_u64(repay_amount)
+            .try_mul(deposit_value, Rounding::Down)?
+            .try_div(bonus_value, Rounding::Down)?
+            .to_u64(Rounding::Down)?;
+        deposited_amount
+    } else {
+        Decimal::from_u64(deposited_amount)
+            .try_mul(bonus_value, Rounding::Down)?
+            .try_div(deposit_value, Rounding::Down)?
+            .to_u64(Rounding::Down)?
+    };
+
+    if repay_amount == 0 || withdraw_amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let settle_amount_wads = Decimal::from_u64(repay_amount)
+        .min(borrowed_amount)
+        .to_wads()?;
+
+    Ok(LiquidationResult {
+        repay_amount,
+        settle_amount_wads,
+        withdraw_amount,
+    })
+}
+
+/// Collateral kept by the protocol out of `withdraw_amount`: its share of
+/// the bonus the liquidator is paid.
+pub fn protocol_liquidation_fee(
+    withdraw_amount: u64,
+    bonus_bps: u64,
+    protocol_liquidation_fee_pct: u8,
+) -> Result<u64, ProgramError> {
+    Decimal::from_u64(withdraw_amount)
+        .try_mul_u64(bonus_bps)?
+        .try_div_u64(10_000 + bonus_bps, Rounding::Down)?
+        .try_mul(
+            Decimal::from_pct(protocol_liquidation_fee_pct),
+            Rounding::Down,
+        )?
+        .to_u64(Rounding::Down)
+}
+
+/// Repays part of an obligation's borrow from `repay_reserve` and hands the
+/// liquidator collateral from `withdraw_reserve`. Callers decide whether the
+/// obligation may be liquidated and on what terms.
+pub struct Liquidate<'a> {
+    pub liquidator: &'a AccountInfo,
+    pub lending_market: &'a AccountInfo,
+    pub obligation: &'a AccountInfo,
+    pub repay_reserve: &'a AccountInfo,
+    pub repay_reserve_liquidity_supply: &'a AccountInfo,
+    pub withdraw_reserve: &'a AccountInfo,
+    pub withdraw_reserve_collateral_supply: &'a AccountInfo,
+    pub withdraw_reserve_collateral_mint: &'a AccountInfo,
+    pub liquidator_source_liquidity: &'a AccountInfo,
+    pub liquidator_destination_collateral: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl Liquidate<'_> {
+    /// Both reserves must have been refreshed in `current_slot`.
+    pub fn process(
+        &self,
+        liquidity_amount: u64,
+        max_repay_pct: u8,
+        bonus_bps: u64,
+        current_slot: u64,
+    ) -> Result<LiquidationResult, ProgramError> {
+        let cumulative_borrow_rate_wads =
+            load_account::<ReserveState>(self.repay_reserve)?.cumulative_borrow_rate_wads;
+
+        let (borrow_index, deposit_index, borrowed_amount_wads, deposited_amount) = {
+            let mut obligation_state = load_account_mut::<ObligationState>(self.obligation)?;
+
+            let borrow_index = obligation_state
+                .find_borrow_index(self.repay_reserve.key())
+                .ok_or(ProgramError::InvalidArgument)?;
+            let deposit_index = obligation_state
+                .find_deposit_index(self.withdraw_reserve.key())
+                .ok_or(ProgramError::InvalidArgument)?;
+            obligation_state.borrows[borrow_index].accrue_interest(cumulative_borrow_rate_wads)?;
+
+            (
+                borrow_index,
+                deposit_index,
+                obligation_state.borrows[borrow_index].borrowed_amount_wads,
+                obligation_state.deposits[deposit_index].deposited_amount,
+            )
+        };
+
+        let (result, protocol_fee, liquidity_mint, bump) = {
+            let repay_reserve_state = load_account::<ReserveState>(self.repay_reserve)?;
+            let withdraw_reserve_state = load_account::<ReserveState>(self.withdraw_reserve)?;
+
+            if &repay_reserve_state.lending_market != self.lending_market.key()
+                || &repay_reserve_state.liquidity_supply_vault
+                    != self.repay_reserve_liquidity_supply.key()
+                || &withdraw_reserve_state.lending_market != self.lending_market.key()
+                || &withdraw_reserve_state.collateral_supply_vault
+                    != self.withdraw_reserve_collateral_supply.key()
+                || &withdraw_reserve_state.collateral_mint
+                    != self.withdraw_reserve_collateral_mint.key()
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if &repay_reserve_state.token_program != self.token_program.key()
+                || &withdraw_reserve_state.token_program != self.token_program.key()
+            {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            repay_reserve_state.check_action(ReserveAction::Liquidate)?;
+            withdraw_reserve_state.check_action(ReserveAction::Liquidate)?;
+            if !repay_reserve_state.is_fresh(current_slot)
+                || !withdraw_reserve_state.is_fresh(current_slot)
+                || !repay_reserve_state.is_price_valid()
+                || !withdraw_reserve_state.is_price_valid()
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let result = calculate_liquidation(
+                &repay_reserve_state,
+                &withdraw_reserve_state,
+                borrowed_amount_wads,
+                deposited_amount,
+                liquidity_amount,
+                max_repay_pct,
+                bonus_bps,
+            )?;
+
+            let protocol_fee = protocol_liquidation_fee(
+                result.withdraw_amount,
+                bonus_bps,
+                withdraw_reserve_state.config.protocol_liquidation_fee_pct,
+            )?;
+
+            (
+                result,
+                protocol_fee,
+                withdraw_reserve_state.liquidity_mint,
+                withdraw_reserve_state.bump,
+            )
+        };
+
+        {
+            let mut obligation_state = load_account_mut::<ObligationState>(self.obligation)?;
+
+            let borrow = &mut obligation_state.borrows[borrow_index];
+            borrow.borrowed_amount_wads = borrowed_amount_wads - result.settle_amount_wads;
+            if borrow.borrowed_amount_wads == 0 {
+                *borrow = ObligationLiquidity::zeroed();
+            }
+
+            let deposit = &mut obligation_state.deposits[deposit_index];
+            deposit.deposited_amount = deposited_amount - result.withdraw_amount;
+            if deposit.deposited_amount == 0 {
+                *deposit = ObligationCollateral::zeroed();
+            }
+            obligation_state.mark_stale();
+        }
+
+        {
+            let mut reserve_state = load_account_mut::<ReserveState>(self.repay_reserve)?;
+
+            reserve_state.available_liquidity = reserve_state
+                .available_liquidity
+                .checked_add(result.repay_amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reserve_state.borrowed_amount_wads = reserve_state
+                .borrowed_amount_wads
+                .saturating_sub(result.settle_amount_wads);
+        }
+
+        // The protocol's cut of the seized collateral is redeemed in place:
+        // its liquidity becomes protocol fees and the collateral is burned.
+        if protocol_fee > 0 {
+            let mut reserve_state = load_account_mut::<ReserveState>(self.withdraw_reserve)?;
+
+            let fee_liquidity = reserve_state.collateral_to_liquidity(protocol_fee)?;
+            reserve_state.add_protocol_fee(Decimal::from_u64(fee_liquidity))?;
+            reserve_state.collateral_mint_total_supply = reserve_state
+                .collateral_mint_total_supply
+                .checked_sub(protocol_fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        Transfer {
+            token_program: self.token_program.key(),
+            from: self.liquidator_source_liquidity,
+            to: self.repay_reserve_liquidity_supply,
+            authority: self.liquidator,
+            amount: result.repay_amount,
+        }
+        .invoke()?;
+
+        let bump_bytes = [bump];
+        let reserve_seeds = [
+            Seed::from(RESERVE_SEED.as_bytes()),
+            Seed::from(self.lending_market.key().as_ref()),
+            Seed::from(liquidity_mint.as_ref()),
+            Seed::from(&bump_bytes[..]),
+        ];
+
+        Transfer {
+            token_program: self.token_program.key(),
+            from: self.withdraw_reserve_collateral_supply,
+            to: self.liquidator_destination_collateral,
+            authority: self.withdraw_reserve,
+            amount: result.withdraw_amount - protocol_fee,
+        }
+        .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+        if protocol_fee > 0 {
+            Burn {
+                token_program: self.token_program.key(),
+                account: self.withdraw_reserve_collateral_supply,
+                mint: self.withdraw_reserve_collateral_mint,
+                authority: self.withdraw_reserve,
+                amount: protocol_fee,
+            }
+            .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::{Decimal, Rounding},
+    utils::{load_account, DataLen},
+};
+use crate::instructions::obligations::{liquidation_terms, Liquidate, LiquidateObligationIxData};
+use crate::state::{LendingMarketState, ObligationState, ReserveState};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// Liquidates an obligation that is only unhealthy once `withdraw_reserve`'s
+/// liquidation threshold is replaced by its autodeleverage ramp. Takes the
+/// same accounts and data as `LiquidateObligation`, and seizes collateral
+/// from the autodeleveraging reserve on the same terms.
+pub fn process_liquidate_obligation_autodeleverage(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [liquidator, lending_market, obligation, repay_reserve, repay_reserve_liquidity_supply, withdraw_reserve, withdraw_reserve_collateral_supply, withdraw_reserve_collateral_mint, liquidator_source_liquidity, liquidator_destination_collateral, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(liquidator)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+    check_program_owner(repay_reserve)?;
+    check_program_owner(withdraw_reserve)?;
+
+    let ix_data =
+        bytemuck::from_bytes::<LiquidateObligationIxData>(&data[..LiquidateObligationIxData::LEN]);
+    let liquidity_amount = ix_data.liquidity_amount;
+    if liquidity_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let clock = Clock::get()?;
+
+    let (obligation_state, deposited_amount) = {
+        let obligation_state = *load_account::<ObligationState>(obligation)?;
+
+        if &obligation_state.lending_market != lending_market.key()
+            || !obligation_state.is_fresh(clock.slot)
+            || !obligation_state.has_valid_prices()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let deposit_index = obligation_state
+            .find_deposit_index(withdraw_reserve.key())
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        (
+            obligation_state,
+            obligation_state.deposits[deposit_index].deposited_amount,
+        )
+    };
+
+    let elevation_group = load_account::<LendingMarketState>(lending_market)?
+        .elevation_group(obligation_state.elevation_group);
+
+    // The threshold the last refresh valued this deposit at, less what the
+    // ramp has taken off it since autodeleveraging started.
+    let unhealthy_borrow_value = {
+        let reserve_state = load_account::<ReserveState>(withdraw_reserve)?;
+        if !reserve_state.is_fresh(clock.slot) || !reserve_state.is_price_valid() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let liquidation_threshold_pct = match elevation_group {
+            Some(group) => group.liquidation_threshold_pct,
+            None => reserve_state.config.liquidation_threshold_pct,
+        };
+        let threshold_bps = reserve_state
+            .autodeleverage_threshold_bps(liquidation_threshold_pct, clock.unix_timestamp)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        let liquidity_amount = reserve_state.collateral_to_liquidity(deposited_amount)?;
+        let deposit_value = reserve_state.market_value(Decimal::from_u64(liquidity_amount))?;
+        let reduction = deposit_value.try_mul(
+            Decimal::from_bps(liquidation_threshold_pct as u64 * 100 - threshold_bps),
+            Rounding::Up,
+        )?;
+
+        Decimal::from_wads(obligation_state.unhealthy_borrow_value_wads).saturating_sub(reduction)
+    };
+
+    let debt_value = Decimal::from_wads(obligation_state.borrow_factor_adjusted_debt_value_wads);
+    if debt_value <= unhealthy_borrow_value {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (max_repay_pct, bonus_bps) = liquidation_terms(
+        lending_market,
+        withdraw_reserve,
+        obligation_state.borrowed_value_wads,
+        obligation_state.elevation_group,
+    )?;
+
+    Liquidate {
+        liquidator,
+        lending_market,
+        obligation,
+        repay_reserve,
+        repay_reserve_liquidity_supply,
+        withdraw_reserve,
+        withdraw_reserve_collateral_supply,
+        withdraw_reserve_collateral_mint,
+        liquidator_source_liquidity,
+        liquidator_destination_collateral,
+        token_program,
+    }
+    .process(liquidity_amount, max_repay_pct, bonus_bps, clock.slot)?;
+
+    Ok(())
+}
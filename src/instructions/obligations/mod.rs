@@ -0,0 +1,23 @@
+pub mod borrow_obligation_liquidity;
+pub mod close_obligation;
+pub mod deposit_obligation_collateral;
+pub mod deposit_reserve_liquidity_and_obligation_collateral;
+pub mod init_obligation;
+pub mod liquidate_obligation;
+pub mod liquidate_obligation_autodeleverage;
+pub mod refresh_obligation;
+pub mod repay_obligation_liquidity;
+pub mod request_elevation_group;
+pub mod withdraw_obligation_collateral;
+
+pub use borrow_obligation_liquidity::*;
+pub use close_obligation::*;
+pub use deposit_obligation_collateral::*;
+pub use deposit_reserve_liquidity_and_obligation_collateral::*;
+pub use init_obligation::*;
+pub use liquidate_obligation::*;
+pub use liquidate_obligation_autodeleverage::*;
+pub use refresh_obligation::*;
+pub use repay_obligation_liquidity::*;
+pub use request_elevation_group::*;
+pub use withdraw_obligation_collateral::*;
@@ -0,0 +1,146 @@
+use crate::{
+    helper::{
+        account_checks::check_program_owner,
+        math::{Decimal, Rounding},
+        utils::{load_account, load_account_mut},
+    },
+    state::{ElevationGroup, LendingMarketState, ObligationState, ReserveState},
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// Recomputes the obligation's values. Anyone may call it; every instruction
+/// that moves the obligation's deposits or debt requires it to have run in
+/// the same slot.
+///
+/// Remaining accounts: the reserves backing the obligation, as described in
+/// [`refresh_obligation_values`].
+pub fn process_refresh_obligation(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [obligation, lending_market, reserves @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_program_owner(obligation)?;
+    check_program_owner(lending_market)?;
+
+    let clock = Clock::get()?;
+
+    let mut obligation_state = load_account_mut::<ObligationState>(obligation)?;
+
+    if &obligation_state.lending_market != lending_market.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let elevation_group = load_account::<LendingMarketState>(lending_market)?
+        .elevation_group(obligation_state.elevation_group);
+
+    refresh_obligation_values(&mut obligation_state, elevation_group, reserves, clock.slot)
+}
+
+/// Accrues every borrow to its reserve's index and stores the obligation's
+/// values at current prices. `reserves` must hold the reserve of every used
+/// deposit slot, then the reserve of every used borrow slot, each in slot
+/// order, and all of them refreshed in `current_slot`.
+///
+/// With an `elevation_group`, its LTV and liquidation threshold replace the
+/// reserves' and debt is not scaled by borrow factors.
+pub fn refresh_obligation_values(
+    obligation_state: &mut ObligationState,
+    elevation_group: Option<ElevationGroup>,
+    reserves: &[AccountInfo],
+    current_slot: u64,
+) -> ProgramResult {
+    let mut reserves = reserves.iter();
+
+    let mut deposited_value = Decimal::zero();
+    let mut allowed_borrow_value = Decimal::zero();
+    let mut unhealthy_borrow_value = Decimal::zero();
+    let mut borrowed_value = Decimal::zero();
+    let mut borrow_factor_adjusted_debt_value = Decimal::zero();
+    let mut invalid_price = false;
+
+    for deposit in obligation_state.deposits.iter().filter(|d| !d.is_empty()) {
+        let reserve = reserves.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        check_program_owner(reserve)?;
+        if reserve.key() != &deposit.deposit_reserve {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let reserve_state = load_account::<ReserveState>(reserve)?;
+        if !reserve_state.is_fresh(current_slot) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        invalid_price |= !reserve_state.is_price_valid();
+
+        let liquidity_amount = reserve_state.collateral_to_liquidity(deposit.deposited_amount)?;
+        let market_value = reserve_state.market_value(Decimal::from_u64(liquidity_amount))?;
+
+        let (loan_to_value_pct, liquidation_threshold_pct) = match elevation_group {
+            Some(group) => (group.loan_to_value_pct, group.liquidation_threshold_pct),
+            None => (
+                reserve_state.config.loan_to_value_pct,
+                reserve_state.config.liquidation_threshold_pct,
+            ),
+        };
+
+        deposited_value = deposited_value.try_add(market_value)?;
+        allowed_borrow_value = allowed_borrow_value
+            .try_add(market_value.try_mul(Decimal::from_pct(loan_to_value_pct), Rounding::Down)?)?;
+        unhealthy_borrow_value = unhealthy_borrow_value.try_add(
+            market_value.try_mul(Decimal::from_pct(liquidation_threshold_pct), Rounding::Down)?,
+        )?;
+    }
+
+    for borrow in obligation_state
+        .borrows
+        .iter_mut()
+        .filter(|b| !b.is_empty())
+    {
+        let reserve = reserves.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        check_program_owner(reserve)?;
+        if reserve.key() != &borrow.borrow_reserve {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let reserve_state = load_account::<ReserveState>(reserve)?;
+        if !reserve_state.is_fresh(current_slot) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        invalid_price |= !reserve_state.is_price_valid();
+
+        borrow.accrue_interest(reserve_state.cumulative_borrow_rate_wads)?;
+        let market_value =
+            reserve_state.market_value(Decimal::from_wads(borrow.borrowed_amount_wads))?;
+
+        let borrow_factor = match elevation_group {
+            Some(_) => Decimal::one(),
+            None => borrow_factor(reserve_state.config.borrow_factor_pct)?,
+        };
+
+        borrowed_value = borrowed_value.try_add(market_value)?;
+        borrow_factor_adjusted_debt_value = borrow_factor_adjusted_debt_value
+            .try_add(market_value.try_mul(borrow_factor, Rounding::Up)?)?;
+    }
+
+    obligation_state.deposited_value_wads = deposited_value.to_wads()?;
+    obligation_state.allowed_borrow_value_wads = allowed_borrow_value.to_wads()?;
+    obligation_state.unhealthy_borrow_value_wads = unhealthy_borrow_value.to_wads()?;
+    obligation_state.borrowed_value_wads = borrowed_value.to_wads()?;
+    obligation_state.borrow_factor_adjusted_debt_value_wads =
+        borrow_factor_adjusted_debt_value.to_wads()?;
+    obligation_state.last_update_slot = current_slot;
+    obligation_state.stale = 0;
+    obligation_state.invalid_price = invalid_price as u8;
+
+    Ok(())
+}
+
+/// A reserve's borrow factor as a multiplier.
+pub fn borrow_factor(borrow_factor_pct: u64) -> Result<Decimal, ProgramError> {
+    Decimal::from_u64(borrow_factor_pct).try_div_u64(100, Rounding::Down)
+}
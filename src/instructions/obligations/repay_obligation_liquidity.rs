@@ -0,0 +1,128 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::{Decimal, Rounding},
+    token::Transfer,
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::state::{ObligationLiquidity, ObligationState, ReserveAction, ReserveState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct RepayObligationLiquidityIxData {
+    /// `u64::MAX` repays the whole debt, including accrued interest.
+    pub liquidity_amount: u64,
+}
+
+impl DataLen for RepayObligationLiquidityIxData {
+    const LEN: usize = core::mem::size_of::<RepayObligationLiquidityIxData>();
+}
+
+pub fn process_repay_obligation_liquidity(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [repayer, lending_market, obligation, repay_reserve, reserve_liquidity_supply, user_source_liquidity, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(repayer)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+    check_program_owner(repay_reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<RepayObligationLiquidityIxData>(
+        &data[..RepayObligationLiquidityIxData::LEN],
+    );
+    let liquidity_amount = ix_data.liquidity_amount;
+    if liquidity_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let clock = Clock::get()?;
+
+    let cumulative_borrow_rate_wads = {
+        let reserve_state = load_account::<ReserveState>(repay_reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.liquidity_supply_vault != reserve_liquidity_supply.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        reserve_state.check_action(ReserveAction::Repay)?;
+
+        reserve_state.cumulative_borrow_rate_wads
+    };
+
+    let (repay_amount, settle_amount_wads) = {
+        let mut obligation_state = load_account_mut::<ObligationState>(obligation)?;
+
+        if &obligation_state.lending_market != lending_market.key()
+            || !obligation_state.is_fresh(clock.slot)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let index = obligation_state
+            .find_borrow_index(repay_reserve.key())
+            .ok_or(ProgramError::InvalidArgument)?;
+        let borrow = &mut obligation_state.borrows[index];
+        borrow.accrue_interest(cumulative_borrow_rate_wads)?;
+        let borrowed_amount_wads = borrow.borrowed_amount_wads;
+
+        let borrowed_amount = Decimal::from_wads(borrowed_amount_wads);
+        let (repay_amount, settle_amount_wads) =
+            if Decimal::from_u64(liquidity_amount) >= borrowed_amount {
+                (borrowed_amount.to_u64(Rounding::Up)?, borrowed_amount_wads)
+            } else {
+                (
+                    liquidity_amount,
+                    Decimal::from_u64(liquidity_amount).to_wads()?,
+                )
+            };
+
+        borrow.borrowed_amount_wads = borrowed_amount_wads - settle_amount_wads;
+        if borrow.borrowed_amount_wads == 0 {
+            *borrow = ObligationLiquidity::zeroed();
+        }
+        obligation_state.mark_stale();
+
+        (repay_amount, settle_amount_wads)
+    };
+
+    {
+        let mut reserve_state = load_account_mut::<ReserveState>(repay_reserve)?;
+
+        reserve_state.available_liquidity = reserve_state
+            .available_liquidity
+            .checked_add(repay_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        reserve_state.borrowed_amount_wads = reserve_state
+            .borrowed_amount_wads
+            .saturating_sub(settle_amount_wads);
+    }
+
+    Transfer {
+        token_program: token_program.key(),
+        from: user_source_liquidity,
+        to: reserve_liquidity_supply,
+        authority: repayer,
+        amount: repay_amount,
+    }
+    .invoke()?;
+
+    Ok(())
+}
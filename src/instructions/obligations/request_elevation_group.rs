@@ -0,0 +1,113 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::instructions::obligations::refresh_obligation_values;
+use crate::state::{LendingMarketState, ObligationState, ReserveState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// `elevation_group` of 0 takes the obligation out of any group.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct RequestElevationGroupIxData {
+    pub elevation_group: u8,
+}
+
+impl DataLen for RequestElevationGroupIxData {
+    const LEN: usize = core::mem::size_of::<RequestElevationGroupIxData>();
+}
+
+/// Moves the obligation into an elevation group. Every deposit must come from
+/// a reserve in the group, any debt must be in the group's debt reserve, and
+/// the obligation must stay within its borrow limit under the group's
+/// parameters.
+///
+/// Remaining accounts: the obligation's reserves, as for `RefreshObligation`.
+/// The obligation is refreshed as part of the request.
+pub fn process_request_elevation_group(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [owner, lending_market, obligation, reserves @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+
+    let ix_data = bytemuck::from_bytes::<RequestElevationGroupIxData>(
+        &data[..RequestElevationGroupIxData::LEN],
+    );
+    let id = ix_data.elevation_group;
+
+    let elevation_group = if id == 0 {
+        None
+    } else {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+        Some(
+            lending_market_state
+                .elevation_group(id)
+                .ok_or(ProgramError::InvalidArgument)?,
+        )
+    };
+
+    let clock = Clock::get()?;
+
+    let mut obligation_state = load_account_mut::<ObligationState>(obligation)?;
+
+    if &obligation_state.lending_market != lending_market.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if &obligation_state.owner != owner.key() {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if let Some(group) = elevation_group {
+        let deposits = obligation_state.deposits;
+        let mut reserves = reserves.iter();
+        for deposit in deposits.iter().filter(|d| !d.is_empty()) {
+            let reserve = reserves.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            check_program_owner(reserve)?;
+            if reserve.key() != &deposit.deposit_reserve {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let reserve_state = load_account::<ReserveState>(reserve)?;
+            if !reserve_state.config.is_in_elevation_group(id) {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        if obligation_state
+            .borrows
+            .iter()
+            .any(|b| !b.is_empty() && b.borrow_reserve != group.debt_reserve)
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    obligation_state.elevation_group = id;
+    refresh_obligation_values(&mut obligation_state, elevation_group, reserves, clock.slot)?;
+
+    let has_borrows = obligation_state.borrows.iter().any(|b| !b.is_empty());
+    if has_borrows {
+        if !obligation_state.has_valid_prices() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let debt_value = obligation_state.borrow_factor_adjusted_debt_value_wads;
+        let allowed_borrow_value = obligation_state.allowed_borrow_value_wads;
+        if debt_value > allowed_borrow_value {
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    Ok(())
+}
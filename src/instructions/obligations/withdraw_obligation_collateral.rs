@@ -0,0 +1,178 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::{Decimal, Rounding},
+    token::Transfer,
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::{
+    state::{
+        LendingMarketState, ObligationCollateral, ObligationState, ReserveAction, ReserveState,
+    },
+    RESERVE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// `collateral_amount` of `u64::MAX` withdraws the whole deposit.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct WithdrawObligationCollateralIxData {
+    pub collateral_amount: u64,
+}
+
+impl DataLen for WithdrawObligationCollateralIxData {
+    const LEN: usize = core::mem::size_of::<WithdrawObligationCollateralIxData>();
+}
+
+/// The obligation and the withdraw reserve must both have been refreshed in
+/// the current slot. While the obligation has debt, every price it was valued
+/// at must be valid.
+pub fn process_withdraw_obligation_collateral(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [owner, lending_market, obligation, withdraw_reserve, reserve_collateral_supply, user_destination_collateral, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(owner)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(obligation)?;
+    check_program_owner(withdraw_reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<WithdrawObligationCollateralIxData>(
+        &data[..WithdrawObligationCollateralIxData::LEN],
+    );
+    let requested_amount = ix_data.collateral_amount;
+    if requested_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let clock = Clock::get()?;
+
+    let (deposit_index, collateral_amount, values, elevation_group_id) = {
+        let obligation_state = load_account::<ObligationState>(obligation)?;
+
+        if &obligation_state.lending_market != lending_market.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &obligation_state.owner != owner.key() {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if !obligation_state.is_fresh(clock.slot) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let deposit_index = obligation_state
+            .find_deposit_index(withdraw_reserve.key())
+            .ok_or(ProgramError::InvalidArgument)?;
+        let deposited_amount = obligation_state.deposits[deposit_index].deposited_amount;
+        let collateral_amount = if requested_amount == u64::MAX {
+            deposited_amount
+        } else {
+            requested_amount
+        };
+        if collateral_amount > deposited_amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        let has_borrows = obligation_state.borrows.iter().any(|b| !b.is_empty());
+        if has_borrows && !obligation_state.has_valid_prices() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let values = has_borrows.then(|| {
+            (
+                Decimal::from_wads(obligation_state.borrow_factor_adjusted_debt_value_wads),
+                Decimal::from_wads(obligation_state.allowed_borrow_value_wads),
+            )
+        });
+
+        (
+            deposit_index,
+            collateral_amount,
+            values,
+            obligation_state.elevation_group,
+        )
+    };
+
+    let elevation_group =
+        { load_account::<LendingMarketState>(lending_market)?.elevation_group(elevation_group_id) };
+
+    let (liquidity_mint, bump) = {
+        let reserve_state = load_account::<ReserveState>(withdraw_reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.collateral_supply_vault != reserve_collateral_supply.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        reserve_state.check_action(ReserveAction::Withdraw)?;
+        if !reserve_state.is_fresh(clock.slot) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if let Some((borrow_factor_adjusted_debt_value, allowed_borrow_value)) = values {
+            if !reserve_state.is_price_valid() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let loan_to_value_pct = match elevation_group {
+                Some(group) => group.loan_to_value_pct,
+                None => reserve_state.config.loan_to_value_pct,
+            };
+            let liquidity_amount = reserve_state.collateral_to_liquidity(collateral_amount)?;
+            let withdrawn_allowed_value = reserve_state
+                .market_value(Decimal::from_u64(liquidity_amount))?
+                .try_mul(Decimal::from_pct(loan_to_value_pct), Rounding::Down)?;
+            let remaining_allowed_value =
+                allowed_borrow_value.saturating_sub(withdrawn_allowed_value);
+            if borrow_factor_adjusted_debt_value > remaining_allowed_value {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        (reserve_state.liquidity_mint, reserve_state.bump)
+    };
+
+    {
+        let mut obligation_state = load_account_mut::<ObligationState>(obligation)?;
+
+        let deposit = &mut obligation_state.deposits[deposit_index];
+        deposit.deposited_amount -= collateral_amount;
+        if deposit.deposited_amount == 0 {
+            *deposit = ObligationCollateral::zeroed();
+        }
+        obligation_state.mark_stale();
+    }
+
+    let bump_bytes = [bump];
+    let reserve_seeds = [
+        Seed::from(RESERVE_SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(liquidity_mint.as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    Transfer {
+        token_program: token_program.key(),
+        from: reserve_collateral_supply,
+        to: user_destination_collateral,
+        authority: withdraw_reserve,
+        amount: collateral_amount,
+    }
+    .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+    Ok(())
+}
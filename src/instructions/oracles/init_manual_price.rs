@@ -0,0 +1,104 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_init::{create_pda_account, StateDefinition},
+    utils::{load_account, DataLen},
+};
+use crate::state::{LendingMarketState, ManualPriceState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct InitManualPriceIxData {
+    pub price_wads: u128,
+}
+
+impl DataLen for InitManualPriceIxData {
+    const LEN: usize = core::mem::size_of::<InitManualPriceIxData>();
+}
+
+/// Creates the market's manual price account for `mint`. Reserves use it by
+/// setting their oracle to `OracleType::Manual` with this account as feed.
+pub fn process_init_manual_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [lending_market_owner, lending_market, manual_price, mint, rent_sysvar, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+    check_program_owner(lending_market)?;
+
+    {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+        if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+            return Err(ProgramError::IllegalOwner);
+        }
+    }
+
+    if !manual_price.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let ix_data =
+        bytemuck::from_bytes::<InitManualPriceIxData>(&data[..InitManualPriceIxData::LEN]);
+    if ix_data.price_wads == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (expected_manual_price_key, bump) = pubkey::find_program_address(
+        &[
+            ManualPriceState::SEED.as_bytes(),
+            lending_market.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        program_id,
+    );
+    if expected_manual_price_key != *manual_price.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_sysvar)?;
+    let bump_bytes = [bump];
+    let manual_price_seeds = [
+        Seed::from(ManualPriceState::SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(mint.key().as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    create_pda_account::<ManualPriceState>(
+        lending_market_owner,
+        manual_price,
+        &manual_price_seeds,
+        &rent,
+    )?;
+
+    let clock = Clock::get()?;
+
+    let data = &mut manual_price.try_borrow_mut_data()?;
+    let manual_price_state = bytemuck::from_bytes_mut::<ManualPriceState>(data);
+
+    *manual_price_state = ManualPriceState::new(
+        *lending_market.key(),
+        *mint.key(),
+        ix_data.price_wads,
+        clock.slot,
+        clock.unix_timestamp,
+        bump,
+    );
+
+    Ok(())
+}
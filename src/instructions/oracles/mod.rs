@@ -0,0 +1,5 @@
+pub mod init_manual_price;
+pub mod update_manual_price;
+
+pub use init_manual_price::*;
+pub use update_manual_price::*;
@@ -0,0 +1,86 @@
+use crate::error::PlendError;
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::state::{LendingMarketState, ManualPriceState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct UpdateManualPriceIxData {
+    pub price_wads: u128,
+}
+
+impl DataLen for UpdateManualPriceIxData {
+    const LEN: usize = core::mem::size_of::<UpdateManualPriceIxData>();
+}
+
+/// Sets a manual price. Reserves priced from it pick the new value up on
+/// their next refresh. Markets with a governance delay queue a new price
+/// through `QueueConfigChange` instead, with the price in the leading bytes
+/// of the value, but may still republish the current price at once so it
+/// does not go stale.
+pub fn process_update_manual_price(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [lending_market_owner, lending_market, manual_price, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(manual_price)?;
+
+    let ix_data =
+        bytemuck::from_bytes::<UpdateManualPriceIxData>(&data[..UpdateManualPriceIxData::LEN]);
+
+    let timelocked = {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+        if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+            return Err(ProgramError::IllegalOwner);
+        }
+        lending_market_state.is_timelocked()
+    };
+
+    let clock = Clock::get()?;
+
+    let mut manual_price_state = load_account_mut::<ManualPriceState>(manual_price)?;
+
+    if &manual_price_state.lending_market != lending_market.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let price_wads = ix_data.price_wads;
+    if timelocked && price_wads != manual_price_state.price_wads {
+        return Err(PlendError::TimelockRequired.into());
+    }
+
+    apply_manual_price_update(&mut manual_price_state, price_wads, &clock)
+}
+
+/// Validates and publishes a manual price as of `clock`.
+pub(crate) fn apply_manual_price_update(
+    manual_price_state: &mut ManualPriceState,
+    price_wads: u128,
+    clock: &Clock,
+) -> ProgramResult {
+    if price_wads == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    manual_price_state.price_wads = price_wads;
+    manual_price_state.publish_slot = clock.slot;
+    manual_price_state.publish_timestamp = clock.unix_timestamp;
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    account_init::{create_pda_account, StateDefinition},
+    utils::load_account,
+};
+use crate::state::{ReferrerState, ReserveState};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::rent::Rent,
+    ProgramResult,
+};
+
+/// Creates the account `referrer` collects its share of `reserve`'s borrow
+/// fees in. Borrows pass it to credit the referrer.
+pub fn process_init_referrer_state(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [referrer, reserve, referrer_state, rent_sysvar, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(referrer)?;
+    check_program_owner(reserve)?;
+
+    load_account::<ReserveState>(reserve)?;
+
+    if !referrer_state.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let (expected_referrer_state_key, bump) = pubkey::find_program_address(
+        &[
+            ReferrerState::SEED.as_bytes(),
+            referrer.key().as_ref(),
+            reserve.key().as_ref(),
+        ],
+        program_id,
+    );
+    if expected_referrer_state_key != *referrer_state.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_sysvar)?;
+    let bump_bytes = [bump];
+    let referrer_state_seeds = [
+        Seed::from(ReferrerState::SEED.as_bytes()),
+        Seed::from(referrer.key().as_ref()),
+        Seed::from(reserve.key().as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    create_pda_account::<ReferrerState>(referrer, referrer_state, &referrer_state_seeds, &rent)?;
+
+    let data = &mut referrer_state.try_borrow_mut_data()?;
+    let state = bytemuck::from_bytes_mut::<ReferrerState>(data);
+
+    *state = ReferrerState::new(*referrer.key(), *reserve.key(), bump);
+
+    Ok(())
+}
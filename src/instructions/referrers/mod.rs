@@ -0,0 +1,5 @@
+pub mod init_referrer_state;
+pub mod withdraw_referrer_fees;
+
+pub use init_referrer_state::*;
+pub use withdraw_referrer_fees::*;
@@ -0,0 +1,94 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::{Decimal, Rounding},
+    token::Transfer,
+    utils::load_account_mut,
+};
+use crate::{
+    state::{ReferrerState, ReserveState},
+    RESERVE_SEED,
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+/// Pays out the referrer's unclaimed fees on a reserve, as far as the vault's
+/// available liquidity covers them.
+pub fn process_withdraw_referrer_fees(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let [referrer, lending_market, reserve, referrer_state, reserve_liquidity_supply, referrer_destination_liquidity, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(referrer)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(reserve)?;
+    check_program_owner(referrer_state)?;
+
+    let mut state = load_account_mut::<ReferrerState>(referrer_state)?;
+    if &state.referrer != referrer.key() {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if &state.reserve != reserve.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (amount, liquidity_mint, bump) = {
+        let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.liquidity_supply_vault != reserve_liquidity_supply.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let unclaimed_fees = Decimal::from_wads(state.unclaimed_fees_wads);
+        let amount = unclaimed_fees
+            .to_u64(Rounding::Down)?
+            .min(reserve_state.available_liquidity);
+        if amount == 0 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        state.unclaimed_fees_wads = unclaimed_fees
+            .try_sub(Decimal::from_u64(amount))?
+            .to_wads()?;
+        reserve_state.accumulated_referrer_fees_wads =
+            Decimal::from_wads(reserve_state.accumulated_referrer_fees_wads)
+                .try_sub(Decimal::from_u64(amount))?
+                .to_wads()?;
+        reserve_state.available_liquidity -= amount;
+
+        (amount, reserve_state.liquidity_mint, reserve_state.bump)
+    };
+
+    let bump_bytes = [bump];
+    let reserve_seeds = [
+        Seed::from(RESERVE_SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(liquidity_mint.as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    Transfer {
+        token_program: token_program.key(),
+        from: reserve_liquidity_supply,
+        to: referrer_destination_liquidity,
+        authority: reserve,
+        amount,
+    }
+    .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+    Ok(())
+}
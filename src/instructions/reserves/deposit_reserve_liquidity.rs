@@ -0,0 +1,164 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::Decimal,
+    token::{MintTo, Transfer},
+    utils::{load_account_mut, DataLen},
+};
+use crate::{
+    error::PlendError,
+    state::{ReserveAction, ReserveState},
+    RESERVE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct DepositReserveLiquidityIxData {
+    pub liquidity_amount: u64,
+}
+
+impl DataLen for DepositReserveLiquidityIxData {
+    const LEN: usize = core::mem::size_of::<DepositReserveLiquidityIxData>();
+}
+
+pub fn process_deposit_reserve_liquidity(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [user, lending_market, reserve, reserve_liquidity_supply, reserve_collateral_mint, user_source_liquidity, user_destination_collateral, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(user)?;
+    check_market_operational(lending_market)?;
+
+    let ix_data = bytemuck::from_bytes::<DepositReserveLiquidityIxData>(
+        &data[..DepositReserveLiquidityIxData::LEN],
+    );
+
+    DepositLiquidity {
+        user,
+        lending_market,
+        reserve,
+        reserve_liquidity_supply,
+        reserve_collateral_mint,
+        user_source_liquidity,
+        destination_collateral: user_destination_collateral,
+        token_program,
+        liquidity_amount: ix_data.liquidity_amount,
+    }
+    .process()?;
+
+    Ok(())
+}
+
+/// Moves liquidity from the user into a reserve and mints the matching
+/// collateral to `destination_collateral`. The reserve must have been
+/// refreshed in the current slot so the exchange rate includes all accrued
+/// interest.
+pub struct DepositLiquidity<'a> {
+    pub user: &'a AccountInfo,
+    pub lending_market: &'a AccountInfo,
+    pub reserve: &'a AccountInfo,
+    pub reserve_liquidity_supply: &'a AccountInfo,
+    pub reserve_collateral_mint: &'a AccountInfo,
+    pub user_source_liquidity: &'a AccountInfo,
+    pub destination_collateral: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub liquidity_amount: u64,
+}
+
+impl DepositLiquidity<'_> {
+    /// Returns the amount of collateral minted.
+    pub fn process(&self) -> Result<u64, ProgramError> {
+        let liquidity_amount = self.liquidity_amount;
+        if liquidity_amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_program_owner(self.reserve)?;
+
+        let (collateral_amount, liquidity_mint, bump) = {
+            let mut reserve_state = load_account_mut::<ReserveState>(self.reserve)?;
+
+            if &reserve_state.lending_market != self.lending_market.key()
+                || &reserve_state.liquidity_supply_vault != self.reserve_liquidity_supply.key()
+                || &reserve_state.collateral_mint != self.reserve_collateral_mint.key()
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if &reserve_state.token_program != self.token_program.key() {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            reserve_state.check_action(ReserveAction::Deposit)?;
+            if !reserve_state.is_fresh(Clock::get()?.slot) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let collateral_amount = reserve_state.liquidity_to_collateral(liquidity_amount)?;
+            if collateral_amount == 0 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let total_liquidity = reserve_state
+                .total_liquidity()?
+                .try_add(Decimal::from_u64(liquidity_amount))?;
+            if total_liquidity > Decimal::from_u64(reserve_state.config.deposit_limit) {
+                return Err(PlendError::DepositLimitExceeded.into());
+            }
+
+            reserve_state.available_liquidity = reserve_state
+                .available_liquidity
+                .checked_add(liquidity_amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reserve_state.collateral_mint_total_supply = reserve_state
+                .collateral_mint_total_supply
+                .checked_add(collateral_amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            (
+                collateral_amount,
+                reserve_state.liquidity_mint,
+                reserve_state.bump,
+            )
+        };
+
+        Transfer {
+            token_program: self.token_program.key(),
+            from: self.user_source_liquidity,
+            to: self.reserve_liquidity_supply,
+            authority: self.user,
+            amount: liquidity_amount,
+        }
+        .invoke()?;
+
+        let bump_bytes = [bump];
+        let reserve_seeds = [
+            Seed::from(RESERVE_SEED.as_bytes()),
+            Seed::from(self.lending_market.key().as_ref()),
+            Seed::from(liquidity_mint.as_ref()),
+            Seed::from(&bump_bytes[..]),
+        ];
+
+        MintTo {
+            token_program: self.token_program.key(),
+            mint: self.reserve_collateral_mint,
+            account: self.destination_collateral,
+            mint_authority: self.reserve,
+            amount: collateral_amount,
+        }
+        .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+        Ok(collateral_amount)
+    }
+}
@@ -0,0 +1,182 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    math::Decimal,
+    token::Transfer,
+    utils::{load_account_mut, DataLen},
+};
+use crate::instructions::{reserves::FlashRepayReserveLiquidityIxData, PlendInstructions};
+use crate::{
+    state::{ReserveAction, ReserveState},
+    RESERVE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::instructions::{Instructions, IntrospectedInstruction},
+    ProgramResult,
+};
+
+/// Position of the reserve in both flash loan instructions' accounts.
+pub(crate) const FLASH_LOAN_RESERVE_ACCOUNT_INDEX: usize = 2;
+
+/// Stack height of an instruction the transaction invokes directly.
+const TRANSACTION_LEVEL_STACK_HEIGHT: u64 = 1;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct FlashBorrowReserveLiquidityIxData {
+    pub liquidity_amount: u64,
+}
+
+impl DataLen for FlashBorrowReserveLiquidityIxData {
+    const LEN: usize = core::mem::size_of::<FlashBorrowReserveLiquidityIxData>();
+}
+
+/// Lends `liquidity_amount` for the rest of the transaction. A later
+/// top-level `FlashRepayReserveLiquidity` for the same reserve and amount,
+/// pointing back at this instruction, must be in the same transaction.
+/// Neither instruction may be invoked through CPI, and flash loans cannot be
+/// nested.
+pub fn process_flash_borrow_reserve_liquidity(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [user, lending_market, reserve, reserve_liquidity_supply, user_destination_liquidity, instructions_sysvar, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(user)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<FlashBorrowReserveLiquidityIxData>(
+        &data[..FlashBorrowReserveLiquidityIxData::LEN],
+    );
+    let liquidity_amount = ix_data.liquidity_amount;
+    if liquidity_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    check_flash_repay(instructions_sysvar, reserve.key(), liquidity_amount)?;
+
+    let (liquidity_mint, bump) = {
+        let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.liquidity_supply_vault != reserve_liquidity_supply.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        reserve_state.check_action(ReserveAction::FlashLoan)?;
+        if liquidity_amount > reserve_state.borrowable_liquidity()? {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // The loan counts as borrowed until it is repaid so total liquidity,
+        // and with it the collateral exchange rate, holds steady meanwhile.
+        reserve_state.available_liquidity -= liquidity_amount;
+        reserve_state.borrowed_amount_wads = Decimal::from_wads(reserve_state.borrowed_amount_wads)
+            .try_add(Decimal::from_u64(liquidity_amount))?
+            .to_wads()?;
+
+        (reserve_state.liquidity_mint, reserve_state.bump)
+    };
+
+    let bump_bytes = [bump];
+    let reserve_seeds = [
+        Seed::from(RESERVE_SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(liquidity_mint.as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    Transfer {
+        token_program: token_program.key(),
+        from: reserve_liquidity_supply,
+        to: user_destination_liquidity,
+        authority: reserve,
+        amount: liquidity_amount,
+    }
+    .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+    Ok(())
+}
+
+/// Checks the current instruction is a top-level call to this program and
+/// that the first flash loan instruction after it repays `reserve`'s loan of
+/// `liquidity_amount`.
+fn check_flash_repay(
+    instructions_sysvar: &AccountInfo,
+    reserve: &Pubkey,
+    liquidity_amount: u64,
+) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let current_index = instructions.load_current_index() as usize;
+    check_top_level(&instructions.load_instruction_at(current_index)?)?;
+
+    for index in current_index + 1..instructions.num_instructions() as usize {
+        let instruction = instructions.load_instruction_at(index)?;
+        if instruction.get_program_id() != &crate::ID {
+            continue;
+        }
+        let data = instruction.get_instruction_data();
+        let Some((&discriminator, payload)) = data.split_first() else {
+            continue;
+        };
+
+        if discriminator == PlendInstructions::FlashBorrowReserveLiquidity as u8 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if discriminator != PlendInstructions::FlashRepayReserveLiquidity as u8 {
+            continue;
+        }
+
+        let repay = bytemuck::try_from_bytes::<FlashRepayReserveLiquidityIxData>(payload)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        let repay_amount = repay.liquidity_amount;
+        if repay_amount != liquidity_amount
+            || repay.borrow_instruction_index as usize != current_index
+            || &instruction
+                .get_account_meta_at(FLASH_LOAN_RESERVE_ACCOUNT_INDEX)?
+                .key
+                != reserve
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        return Ok(());
+    }
+
+    Err(ProgramError::InvalidInstructionData)
+}
+
+/// The instructions sysvar only lists top-level instructions, so the one at
+/// the current index belongs to this program unless it reached us through
+/// CPI. That alone misses a CPI back into this program from a top-level
+/// instruction of ours, so the stack height is checked too.
+pub(crate) fn check_top_level(current_instruction: &IntrospectedInstruction) -> ProgramResult {
+    if current_instruction.get_program_id() != &crate::ID
+        || get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "solana")]
+fn get_stack_height() -> u64 {
+    unsafe { pinocchio::syscalls::sol_get_stack_height() }
+}
+
+#[cfg(not(target_os = "solana"))]
+fn get_stack_height() -> u64 {
+    TRANSACTION_LEVEL_STACK_HEIGHT
+}
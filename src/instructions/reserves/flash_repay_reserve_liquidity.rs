@@ -0,0 +1,151 @@
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    math::Decimal,
+    token::Transfer,
+    utils::{load_account_mut, DataLen},
+};
+use crate::instructions::{
+    reserves::{
+        check_top_level, FlashBorrowReserveLiquidityIxData, FLASH_LOAN_RESERVE_ACCOUNT_INDEX,
+    },
+    PlendInstructions,
+};
+use crate::state::ReserveState;
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey,
+    sysvars::instructions::Instructions, ProgramResult,
+};
+
+/// `liquidity_amount` is the amount borrowed; the flash loan fee is paid on
+/// top of it.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct FlashRepayReserveLiquidityIxData {
+    pub liquidity_amount: u64,
+    /// Index in the transaction of the `FlashBorrowReserveLiquidity` being
+    /// repaid.
+    pub borrow_instruction_index: u8,
+}
+
+impl DataLen for FlashRepayReserveLiquidityIxData {
+    const LEN: usize = core::mem::size_of::<FlashRepayReserveLiquidityIxData>();
+}
+
+/// Repays a flash loan plus the reserve's flash loan fee, which is credited
+/// to the protocol. Works in emergency mode so an open loan can always be
+/// closed.
+pub fn process_flash_repay_reserve_liquidity(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [user, lending_market, reserve, reserve_liquidity_supply, user_source_liquidity, instructions_sysvar, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(user)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<FlashRepayReserveLiquidityIxData>(
+        &data[..FlashRepayReserveLiquidityIxData::LEN],
+    );
+    let liquidity_amount = ix_data.liquidity_amount;
+    if liquidity_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    check_flash_borrow(
+        instructions_sysvar,
+        reserve.key(),
+        liquidity_amount,
+        ix_data.borrow_instruction_index as usize,
+    )?;
+
+    let repay_amount = {
+        let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.liquidity_supply_vault != reserve_liquidity_supply.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let fee = reserve_state.flash_loan_fee(liquidity_amount)?;
+        let repay_amount = liquidity_amount
+            .checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        reserve_state.available_liquidity = reserve_state
+            .available_liquidity
+            .checked_add(repay_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        reserve_state.borrowed_amount_wads = Decimal::from_wads(reserve_state.borrowed_amount_wads)
+            .try_sub(Decimal::from_u64(liquidity_amount))?
+            .to_wads()?;
+        reserve_state.add_protocol_fee(Decimal::from_u64(fee))?;
+
+        repay_amount
+    };
+
+    Transfer {
+        token_program: token_program.key(),
+        from: user_source_liquidity,
+        to: reserve_liquidity_supply,
+        authority: user,
+        amount: repay_amount,
+    }
+    .invoke()?;
+
+    Ok(())
+}
+
+/// Checks the current instruction is a top-level call to this program and
+/// that the instruction at `borrow_instruction_index` flash borrowed
+/// `liquidity_amount` from `reserve` earlier in the transaction.
+fn check_flash_borrow(
+    instructions_sysvar: &AccountInfo,
+    reserve: &Pubkey,
+    liquidity_amount: u64,
+    borrow_instruction_index: usize,
+) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let current_index = instructions.load_current_index() as usize;
+    check_top_level(&instructions.load_instruction_at(current_index)?)?;
+
+    if borrow_instruction_index >= current_index {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let instruction = instructions.load_instruction_at(borrow_instruction_index)?;
+    if instruction.get_program_id() != &crate::ID {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let data = instruction.get_instruction_data();
+    let Some((&discriminator, payload)) = data.split_first() else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    if discriminator != PlendInstructions::FlashBorrowReserveLiquidity as u8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let borrow = bytemuck::try_from_bytes::<FlashBorrowReserveLiquidityIxData>(payload)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let borrow_amount = borrow.liquidity_amount;
+    if borrow_amount != liquidity_amount
+        || &instruction
+            .get_account_meta_at(FLASH_LOAN_RESERVE_ACCOUNT_INDEX)?
+            .key
+            != reserve
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
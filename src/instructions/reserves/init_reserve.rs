@@ -0,0 +1,192 @@
+use crate::state::{LendingMarketState, ReserveState};
+use crate::{
+    helper::{
+        account_checks::check_signer,
+        account_init::{create_pda_account, create_pda_account_owned_by, StateDefinition},
+        token::{
+            check_mint_without_extensions, check_token_program, mint_decimals, InitializeAccount3,
+            InitializeMint2, MINT_LEN, TOKEN_ACCOUNT_LEN,
+        },
+        utils::load_account,
+    },
+    RESERVE_COLLATERAL_MINT_SEED, RESERVE_COLLATERAL_SUPPLY_SEED, RESERVE_LIQUIDITY_SUPPLY_SEED,
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+pub fn process_init_reserve(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [lending_market_owner, lending_market, reserve, liquidity_mint, reserve_liquidity_supply, reserve_collateral_mint, reserve_collateral_supply, rent_sysvar, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+    check_token_program(token_program)?;
+
+    if !lending_market.is_owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+        if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+            return Err(ProgramError::IllegalOwner);
+        }
+    }
+
+    if !reserve.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    check_mint_without_extensions(liquidity_mint)?;
+    let liquidity_mint_decimals = mint_decimals(liquidity_mint)?;
+
+    let (expected_reserve_key, reserve_bump) = pubkey::find_program_address(
+        &[
+            ReserveState::SEED.as_bytes(),
+            lending_market.key().as_ref(),
+            liquidity_mint.key().as_ref(),
+        ],
+        program_id,
+    );
+    if expected_reserve_key != *reserve.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (expected_supply_key, supply_bump) = pubkey::find_program_address(
+        &[
+            RESERVE_LIQUIDITY_SUPPLY_SEED.as_bytes(),
+            reserve.key().as_ref(),
+        ],
+        program_id,
+    );
+    if expected_supply_key != *reserve_liquidity_supply.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (expected_collateral_mint_key, collateral_mint_bump) = pubkey::find_program_address(
+        &[
+            RESERVE_COLLATERAL_MINT_SEED.as_bytes(),
+            reserve.key().as_ref(),
+        ],
+        program_id,
+    );
+    if expected_collateral_mint_key != *reserve_collateral_mint.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (expected_collateral_supply_key, collateral_supply_bump) = pubkey::find_program_address(
+        &[
+            RESERVE_COLLATERAL_SUPPLY_SEED.as_bytes(),
+            reserve.key().as_ref(),
+        ],
+        program_id,
+    );
+    if expected_collateral_supply_key != *reserve_collateral_supply.key() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let rent = Rent::from_account_info(rent_sysvar)?;
+
+    let reserve_bump_bytes = [reserve_bump];
+    let reserve_seeds = [
+        Seed::from(ReserveState::SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(liquidity_mint.key().as_ref()),
+        Seed::from(&reserve_bump_bytes[..]),
+    ];
+    create_pda_account::<ReserveState>(lending_market_owner, reserve, &reserve_seeds, &rent)?;
+
+    let supply_bump_bytes = [supply_bump];
+    let supply_seeds = [
+        Seed::from(RESERVE_LIQUIDITY_SUPPLY_SEED.as_bytes()),
+        Seed::from(reserve.key().as_ref()),
+        Seed::from(&supply_bump_bytes[..]),
+    ];
+    create_pda_account_owned_by(
+        lending_market_owner,
+        reserve_liquidity_supply,
+        TOKEN_ACCOUNT_LEN,
+        token_program.key(),
+        &supply_seeds,
+        &rent,
+    )?;
+    InitializeAccount3 {
+        token_program: token_program.key(),
+        account: reserve_liquidity_supply,
+        mint: liquidity_mint,
+        owner: reserve.key(),
+    }
+    .invoke()?;
+
+    let collateral_mint_bump_bytes = [collateral_mint_bump];
+    let collateral_mint_seeds = [
+        Seed::from(RESERVE_COLLATERAL_MINT_SEED.as_bytes()),
+        Seed::from(reserve.key().as_ref()),
+        Seed::from(&collateral_mint_bump_bytes[..]),
+    ];
+    create_pda_account_owned_by(
+        lending_market_owner,
+        reserve_collateral_mint,
+        MINT_LEN,
+        token_program.key(),
+        &collateral_mint_seeds,
+        &rent,
+    )?;
+    InitializeMint2 {
+        token_program: token_program.key(),
+        mint: reserve_collateral_mint,
+        decimals: liquidity_mint_decimals,
+        mint_authority: reserve.key(),
+    }
+    .invoke()?;
+
+    let collateral_supply_bump_bytes = [collateral_supply_bump];
+    let collateral_supply_seeds = [
+        Seed::from(RESERVE_COLLATERAL_SUPPLY_SEED.as_bytes()),
+        Seed::from(reserve.key().as_ref()),
+        Seed::from(&collateral_supply_bump_bytes[..]),
+    ];
+    create_pda_account_owned_by(
+        lending_market_owner,
+        reserve_collateral_supply,
+        TOKEN_ACCOUNT_LEN,
+        token_program.key(),
+        &collateral_supply_seeds,
+        &rent,
+    )?;
+    InitializeAccount3 {
+        token_program: token_program.key(),
+        account: reserve_collateral_supply,
+        mint: reserve_collateral_mint,
+        owner: reserve.key(),
+    }
+    .invoke()?;
+
+    let clock = Clock::get()?;
+
+    let data = &mut reserve.try_borrow_mut_data()?;
+    let reserve_state = bytemuck::from_bytes_mut::<ReserveState>(data);
+
+    *reserve_state = ReserveState::new(
+        *lending_market.key(),
+        *liquidity_mint.key(),
+        *reserve_liquidity_supply.key(),
+        *reserve_collateral_mint.key(),
+        *reserve_collateral_supply.key(),
+        *token_program.key(),
+        liquidity_mint_decimals,
+        clock.slot,
+        reserve_bump,
+    );
+
+    reserve_state.config.validate_collateral_params()
+}
@@ -0,0 +1,21 @@
+pub mod deposit_reserve_liquidity;
+pub mod flash_borrow_reserve_liquidity;
+pub mod flash_repay_reserve_liquidity;
+pub mod init_reserve;
+pub mod redeem_fees;
+pub mod redeem_reserve_collateral;
+pub mod refresh_reserve;
+pub mod set_reserve_autodeleverage;
+pub mod set_reserve_status;
+pub mod update_reserve_config;
+
+pub use deposit_reserve_liquidity::*;
+pub use flash_borrow_reserve_liquidity::*;
+pub use flash_repay_reserve_liquidity::*;
+pub use init_reserve::*;
+pub use redeem_fees::*;
+pub use redeem_reserve_collateral::*;
+pub use refresh_reserve::*;
+pub use set_reserve_autodeleverage::*;
+pub use set_reserve_status::*;
+pub use update_reserve_config::*;
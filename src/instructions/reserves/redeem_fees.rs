@@ -0,0 +1,91 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner},
+    math::{Decimal, Rounding},
+    token::{check_token_account, Transfer},
+    utils::{load_account, load_account_mut},
+};
+use crate::{
+    state::{LendingMarketState, ReserveState},
+    RESERVE_SEED,
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// Sends the reserve's accumulated protocol fees, as far as the vault's
+/// available liquidity covers them, to `fee_receiver`. Anyone may call it:
+/// the receiver must be a liquidity token account owned by the lending
+/// market owner. The reserve must have been refreshed in the current slot.
+pub fn process_redeem_fees(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [lending_market, reserve, reserve_liquidity_supply, fee_receiver, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_market_operational(lending_market)?;
+    check_program_owner(reserve)?;
+
+    let lending_market_owner =
+        load_account::<LendingMarketState>(lending_market)?.lending_market_owner;
+
+    let clock = Clock::get()?;
+
+    let (amount, liquidity_mint, bump) = {
+        let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.liquidity_supply_vault != reserve_liquidity_supply.key()
+            || !reserve_state.is_fresh(clock.slot)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        check_token_account(
+            fee_receiver,
+            &reserve_state.liquidity_mint,
+            &lending_market_owner,
+        )?;
+
+        let accumulated_fees = Decimal::from_wads(reserve_state.accumulated_protocol_fees_wads);
+        let amount = accumulated_fees
+            .to_u64(Rounding::Down)?
+            .min(reserve_state.available_liquidity);
+        if amount == 0 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        reserve_state.accumulated_protocol_fees_wads = accumulated_fees
+            .try_sub(Decimal::from_u64(amount))?
+            .to_wads()?;
+        reserve_state.available_liquidity -= amount;
+
+        (amount, reserve_state.liquidity_mint, reserve_state.bump)
+    };
+
+    let bump_bytes = [bump];
+    let reserve_seeds = [
+        Seed::from(RESERVE_SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(liquidity_mint.as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    Transfer {
+        token_program: token_program.key(),
+        from: reserve_liquidity_supply,
+        to: fee_receiver,
+        authority: reserve,
+        amount,
+    }
+    .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+    Ok(())
+}
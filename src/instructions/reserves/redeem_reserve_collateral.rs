@@ -0,0 +1,129 @@
+use crate::helper::{
+    account_checks::{check_market_operational, check_program_owner, check_signer},
+    token::{Burn, Transfer},
+    utils::{load_account_mut, DataLen},
+};
+use crate::{
+    error::PlendError,
+    state::{ReserveAction, ReserveState},
+    RESERVE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct RedeemReserveCollateralIxData {
+    pub collateral_amount: u64,
+}
+
+impl DataLen for RedeemReserveCollateralIxData {
+    const LEN: usize = core::mem::size_of::<RedeemReserveCollateralIxData>();
+}
+
+/// Burns the user's collateral and pays out the matching liquidity. The
+/// reserve must have been refreshed in the current slot.
+pub fn process_redeem_reserve_collateral(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [user, lending_market, reserve, reserve_liquidity_supply, reserve_collateral_mint, user_source_collateral, user_destination_liquidity, token_program, _remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(user)?;
+    check_market_operational(lending_market)?;
+    check_program_owner(reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<RedeemReserveCollateralIxData>(
+        &data[..RedeemReserveCollateralIxData::LEN],
+    );
+    let collateral_amount = ix_data.collateral_amount;
+    if collateral_amount == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let clock = Clock::get()?;
+
+    let (liquidity_amount, liquidity_mint, bump) = {
+        let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+        if &reserve_state.lending_market != lending_market.key()
+            || &reserve_state.liquidity_supply_vault != reserve_liquidity_supply.key()
+            || &reserve_state.collateral_mint != reserve_collateral_mint.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &reserve_state.token_program != token_program.key() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        reserve_state.check_action(ReserveAction::Withdraw)?;
+        if !reserve_state.is_fresh(clock.slot) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let liquidity_amount = reserve_state.collateral_to_liquidity(collateral_amount)?;
+        if liquidity_amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if liquidity_amount > reserve_state.borrowable_liquidity()? {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        if !reserve_state
+            .withdrawal_cap
+            .try_consume(liquidity_amount, clock.unix_timestamp)
+        {
+            return Err(PlendError::WithdrawalCapReached.into());
+        }
+
+        reserve_state.available_liquidity -= liquidity_amount;
+        reserve_state.collateral_mint_total_supply = reserve_state
+            .collateral_mint_total_supply
+            .checked_sub(collateral_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        (
+            liquidity_amount,
+            reserve_state.liquidity_mint,
+            reserve_state.bump,
+        )
+    };
+
+    Burn {
+        token_program: token_program.key(),
+        account: user_source_collateral,
+        mint: reserve_collateral_mint,
+        authority: user,
+        amount: collateral_amount,
+    }
+    .invoke()?;
+
+    let bump_bytes = [bump];
+    let reserve_seeds = [
+        Seed::from(RESERVE_SEED.as_bytes()),
+        Seed::from(lending_market.key().as_ref()),
+        Seed::from(liquidity_mint.as_ref()),
+        Seed::from(&bump_bytes[..]),
+    ];
+
+    Transfer {
+        token_program: token_program.key(),
+        from: reserve_liquidity_supply,
+        to: user_destination_liquidity,
+        authority: reserve,
+        amount: liquidity_amount,
+    }
+    .invoke_signed(&[Signer::from(&reserve_seeds)])?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use crate::helper::{account_checks::check_program_owner, utils::load_account_mut};
+use crate::oracle::get_market_price;
+use crate::state::ReserveState;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// Reads the reserve's price from its oracle feeds and accrues interest up to
+/// the current slot. Anyone may call it; deposits, redemptions, borrows,
+/// withdrawals and liquidations require it to have run in the same slot.
+///
+/// Failing to agree on a price does not fail the refresh: the last accepted
+/// price is kept and the reserve is flagged as having no valid price, which
+/// still lets users deposit and repay.
+///
+/// Remaining accounts: the feed of every configured oracle source, in source
+/// order.
+pub fn process_refresh_reserve(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [reserve, price_feeds @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_program_owner(reserve)?;
+
+    let clock = Clock::get()?;
+
+    let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+    let market_price = get_market_price(
+        &reserve_state.config.oracle,
+        &reserve_state.config.price_guards,
+        price_feeds,
+        clock.unix_timestamp,
+    )?;
+    match market_price {
+        Some(market_price) => {
+            reserve_state.market_price_wads = market_price.to_wads()?;
+            reserve_state.price_valid = 1;
+        }
+        None => reserve_state.price_valid = 0,
+    }
+
+    reserve_state.accrue_interest(clock.slot)
+}
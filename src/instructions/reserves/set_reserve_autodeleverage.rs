@@ -0,0 +1,121 @@
+use crate::error::PlendError;
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::state::{LendingMarketState, ReserveState};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// `target_liquidation_threshold_pct` of 0 winds positions down completely;
+/// anything above lowers the threshold to that value. Ignored when
+/// `enable` is 0.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct SetReserveAutodeleverageIxData {
+    pub enable: u8,
+    pub target_liquidation_threshold_pct: u8,
+}
+
+impl DataLen for SetReserveAutodeleverageIxData {
+    const LEN: usize = core::mem::size_of::<SetReserveAutodeleverageIxData>();
+}
+
+/// Starts or stops autodeleveraging a reserve. While it runs, the reserve's
+/// liquidation threshold ramps down towards the target at
+/// `autodeleverage_ramp_bps_per_day`, and obligations above the ramped
+/// threshold can be liquidated through `LiquidateObligationAutodeleverage`.
+///
+/// Changing the target of a running ramp keeps its start time, so progress
+/// already made is not undone. Either the market owner or the risk council
+/// may call it.
+///
+/// On a market with a governance delay the owner may only start a ramp or
+/// lower its target here. Stopping a ramp or raising its target goes through
+/// `QueueConfigChange` with `UpdateReserveConfigMode::Autodeleverage`.
+pub fn process_set_reserve_autodeleverage(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [authority, lending_market, reserve, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(authority)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(reserve)?;
+
+    let ix_data = bytemuck::from_bytes::<SetReserveAutodeleverageIxData>(
+        &data[..SetReserveAutodeleverageIxData::LEN],
+    );
+
+    let owner_timelocked = {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+        if authority.key() != &lending_market_state.lending_market_owner
+            && authority.key() != &lending_market_state.risk_council
+        {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        authority.key() != &lending_market_state.risk_council
+            && lending_market_state.is_timelocked()
+    };
+
+    let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+    if &reserve_state.lending_market != lending_market.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let enable = ix_data.enable != 0;
+    let target_liquidation_threshold_pct = ix_data.target_liquidation_threshold_pct;
+    if owner_timelocked
+        && reserve_state.is_autodeleveraging()
+        && (!enable
+            || target_liquidation_threshold_pct > reserve_state.autodeleverage_target_threshold_pct)
+    {
+        return Err(PlendError::TimelockRequired.into());
+    }
+
+    apply_reserve_autodeleverage(
+        &mut reserve_state,
+        enable,
+        target_liquidation_threshold_pct,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+/// Validates and starts, retargets or stops a reserve's autodeleverage ramp.
+pub(crate) fn apply_reserve_autodeleverage(
+    reserve_state: &mut ReserveState,
+    enable: bool,
+    target_liquidation_threshold_pct: u8,
+    unix_timestamp: i64,
+) -> ProgramResult {
+    if !enable {
+        reserve_state.autodeleverage = 0;
+        reserve_state.autodeleverage_target_threshold_pct = 0;
+        reserve_state.autodeleverage_started_at = 0;
+        return Ok(());
+    }
+
+    if target_liquidation_threshold_pct >= reserve_state.config.liquidation_threshold_pct {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if !reserve_state.is_autodeleveraging() {
+        reserve_state.autodeleverage = 1;
+        reserve_state.autodeleverage_started_at = unix_timestamp;
+    }
+    reserve_state.autodeleverage_target_threshold_pct = target_liquidation_threshold_pct;
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::error::PlendError;
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::state::{LendingMarketState, ReserveAction, ReserveState, ReserveStatus};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// `status` is a `ReserveStatus`; `paused_actions` holds the `ReserveAction`
+/// bits to pause, replacing the current set.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct SetReserveStatusIxData {
+    pub status: u8,
+    pub paused_actions: u8,
+}
+
+impl DataLen for SetReserveStatusIxData {
+    const LEN: usize = core::mem::size_of::<SetReserveStatusIxData>();
+}
+
+/// Sets a reserve's status and paused actions. Either the market owner or
+/// the risk council may call it. An obsolete reserve stays obsolete.
+///
+/// On a market with a governance delay the owner may only tighten a reserve
+/// here: raising its status or pausing more actions. Loosening it goes
+/// through `QueueConfigChange` with `UpdateReserveConfigMode::Status`.
+pub fn process_set_reserve_status(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [authority, lending_market, reserve, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(authority)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(reserve)?;
+
+    let ix_data =
+        bytemuck::from_bytes::<SetReserveStatusIxData>(&data[..SetReserveStatusIxData::LEN]);
+    let status = ReserveStatus::try_from(ix_data.status)?;
+
+    let owner_timelocked = {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+        if authority.key() != &lending_market_state.lending_market_owner
+            && authority.key() != &lending_market_state.risk_council
+        {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        authority.key() != &lending_market_state.risk_council
+            && lending_market_state.is_timelocked()
+    };
+
+    let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+    if &reserve_state.lending_market != lending_market.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if owner_timelocked && loosens_reserve_status(&reserve_state, status, ix_data.paused_actions) {
+        return Err(PlendError::TimelockRequired.into());
+    }
+
+    apply_reserve_status(&mut reserve_state, status, ix_data.paused_actions)
+}
+
+/// Validates and writes a reserve's status and paused actions.
+pub(crate) fn apply_reserve_status(
+    reserve_state: &mut ReserveState,
+    status: ReserveStatus,
+    paused_actions: u8,
+) -> ProgramResult {
+    if paused_actions & !ReserveAction::ALL != 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if reserve_state.status == ReserveStatus::Obsolete as u8 && status != ReserveStatus::Obsolete {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    reserve_state.status = status as u8;
+    reserve_state.paused_actions = paused_actions;
+
+    Ok(())
+}
+
+/// Whether the update reopens the reserve to anything: a less restrictive
+/// status or an action that is no longer paused.
+fn loosens_reserve_status(
+    reserve_state: &ReserveState,
+    status: ReserveStatus,
+    paused_actions: u8,
+) -> bool {
+    (status as u8) < reserve_state.status || reserve_state.paused_actions & !paused_actions != 0
+}
@@ -0,0 +1,384 @@
+use crate::error::PlendError;
+use crate::helper::{
+    account_checks::{check_program_owner, check_signer},
+    utils::{load_account, load_account_mut, DataLen},
+};
+use crate::instructions::reserves::{apply_reserve_autodeleverage, apply_reserve_status};
+use crate::oracle::{OracleSource, OracleType, PriceAggregation};
+use crate::state::{BorrowRateCurve, LendingMarketState, ReserveState, ReserveStatus};
+use crate::{
+    MAX_ELEVATION_GROUPS, MAX_LIQUIDATION_BONUS_BPS, MAX_ORACLE_SOURCES, REFERRER_FEE_PCT_INHERIT,
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpdateReserveConfigMode {
+    LoanToValuePct = 0,
+    BorrowFactorPct = 1,
+    /// Source index byte, oracle type byte, then the feed account.
+    OracleSource = 2,
+    /// Can only be lowered to the target of a finished autodeleverage ramp.
+    LiquidationThresholdPct = 3,
+    LiquidationBonusBps = 4,
+    BorrowRateCurve = 5,
+    ProtocolTakeRatePct = 6,
+    MaxPriceAgeSeconds = 7,
+    MaxPriceConfidenceBps = 8,
+    MaxTwapDivergenceBps = 9,
+    OraclePriceAggregation = 10,
+    MaxOracleSourceDivergenceBps = 11,
+    /// One elevation group id per byte, 0 for unused entries.
+    ElevationGroups = 12,
+    AutodeleverageRampBpsPerDay = 13,
+    FlashLoanFeeBps = 14,
+    BorrowFeeBps = 15,
+    ProtocolLiquidationFeePct = 16,
+    /// A percentage, or `REFERRER_FEE_PCT_INHERIT` for the market's
+    /// referral fee.
+    ReferrerFeePct = 17,
+    DepositLimit = 18,
+    BorrowLimit = 19,
+    /// `value` holds the cap's max amount then its interval in seconds, both
+    /// u64.
+    WithdrawalCap = 20,
+    /// Laid out like `WithdrawalCap`.
+    BorrowCap = 21,
+    /// A `ReserveStatus` byte then the paused `ReserveAction` bits, as
+    /// `SetReserveStatus` takes them.
+    Status = 22,
+    /// An enable byte then the target liquidation threshold, as
+    /// `SetReserveAutodeleverage` takes them.
+    Autodeleverage = 23,
+}
+
+impl TryFrom<u64> for UpdateReserveConfigMode {
+    type Error = ProgramError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(UpdateReserveConfigMode::LoanToValuePct),
+            1 => Ok(UpdateReserveConfigMode::BorrowFactorPct),
+            2 => Ok(UpdateReserveConfigMode::OracleSource),
+            3 => Ok(UpdateReserveConfigMode::LiquidationThresholdPct),
+            4 => Ok(UpdateReserveConfigMode::LiquidationBonusBps),
+            5 => Ok(UpdateReserveConfigMode::BorrowRateCurve),
+            6 => Ok(UpdateReserveConfigMode::ProtocolTakeRatePct),
+            7 => Ok(UpdateReserveConfigMode::MaxPriceAgeSeconds),
+            8 => Ok(UpdateReserveConfigMode::MaxPriceConfidenceBps),
+            9 => Ok(UpdateReserveConfigMode::MaxTwapDivergenceBps),
+            10 => Ok(UpdateReserveConfigMode::OraclePriceAggregation),
+            11 => Ok(UpdateReserveConfigMode::MaxOracleSourceDivergenceBps),
+            12 => Ok(UpdateReserveConfigMode::ElevationGroups),
+            13 => Ok(UpdateReserveConfigMode::AutodeleverageRampBpsPerDay),
+            14 => Ok(UpdateReserveConfigMode::FlashLoanFeeBps),
+            15 => Ok(UpdateReserveConfigMode::BorrowFeeBps),
+            16 => Ok(UpdateReserveConfigMode::ProtocolLiquidationFeePct),
+            17 => Ok(UpdateReserveConfigMode::ReferrerFeePct),
+            18 => Ok(UpdateReserveConfigMode::DepositLimit),
+            19 => Ok(UpdateReserveConfigMode::BorrowLimit),
+            20 => Ok(UpdateReserveConfigMode::WithdrawalCap),
+            21 => Ok(UpdateReserveConfigMode::BorrowCap),
+            22 => Ok(UpdateReserveConfigMode::Status),
+            23 => Ok(UpdateReserveConfigMode::Autodeleverage),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+impl UpdateReserveConfigMode {
+    /// Settings that change how interest accrues. Interest up to the current
+    /// slot has to be accrued under the old setting before they are written.
+    pub fn changes_interest(&self) -> bool {
+        matches!(
+            self,
+            UpdateReserveConfigMode::BorrowRateCurve | UpdateReserveConfigMode::ProtocolTakeRatePct
+        )
+    }
+}
+
+/// `value` is little-endian and sized for the widest setting; narrower
+/// settings only read its leading bytes.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct UpdateReserveConfigIxData {
+    pub mode: u64,
+    pub value: [u8; 128],
+}
+
+impl DataLen for UpdateReserveConfigIxData {
+    const LEN: usize = core::mem::size_of::<UpdateReserveConfigIxData>();
+}
+
+pub fn process_update_reserve_config(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [lending_market_owner, lending_market, reserve, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_signer(lending_market_owner)?;
+    check_program_owner(lending_market)?;
+    check_program_owner(reserve)?;
+
+    let ix_data =
+        bytemuck::from_bytes::<UpdateReserveConfigIxData>(&data[..UpdateReserveConfigIxData::LEN]);
+    let mode = UpdateReserveConfigMode::try_from(ix_data.mode)?;
+
+    {
+        let lending_market_state = load_account::<LendingMarketState>(lending_market)?;
+
+        if lending_market_owner.key() != &lending_market_state.lending_market_owner {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if lending_market_state.is_timelocked() {
+            return Err(PlendError::TimelockRequired.into());
+        }
+    }
+
+    let mut reserve_state = load_account_mut::<ReserveState>(reserve)?;
+
+    if &reserve_state.lending_market != lending_market.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    check_accrued_before_update(&reserve_state, mode)?;
+    finish_autodeleverage_before_update(&mut reserve_state, mode, &ix_data.value)?;
+
+    apply_reserve_config_update(&mut reserve_state, mode, &ix_data.value)
+}
+
+/// Rejects updates to interest settings unless the reserve was refreshed in
+/// the current slot, so no interest is accrued retroactively under the new
+/// setting.
+pub fn check_accrued_before_update(
+    reserve_state: &ReserveState,
+    mode: UpdateReserveConfigMode,
+) -> ProgramResult {
+    if mode.changes_interest() && !reserve_state.is_fresh(Clock::get()?.slot) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+/// Ends a completed autodeleverage ramp when `mode` lowers the liquidation
+/// threshold to its target, and rejects any other decrease. The ramp depends
+/// on the clock, so queued changes are only checked on execution.
+pub fn finish_autodeleverage_before_update(
+    reserve_state: &mut ReserveState,
+    mode: UpdateReserveConfigMode,
+    value: &[u8; 128],
+) -> ProgramResult {
+    if mode == UpdateReserveConfigMode::LiquidationThresholdPct
+        && value[0] < reserve_state.config.liquidation_threshold_pct
+    {
+        finish_autodeleverage(reserve_state, value[0])?;
+    }
+    Ok(())
+}
+
+/// Validates and writes a single reserve setting.
+pub fn apply_reserve_config_update(
+    reserve_state: &mut ReserveState,
+    mode: UpdateReserveConfigMode,
+    value: &[u8; 128],
+) -> ProgramResult {
+    match mode {
+        UpdateReserveConfigMode::LoanToValuePct => {
+            reserve_state.config.loan_to_value_pct = value[0];
+            reserve_state.config.validate_collateral_params()?;
+        }
+        UpdateReserveConfigMode::BorrowFactorPct => {
+            let borrow_factor_pct = read_u64(value);
+            if borrow_factor_pct < 100 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.borrow_factor_pct = borrow_factor_pct;
+        }
+        UpdateReserveConfigMode::OracleSource => {
+            let index = value[0] as usize;
+            if index >= MAX_ORACLE_SOURCES {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let oracle_type = OracleType::try_from(value[1])?;
+            let feed: Pubkey = value[2..34].try_into().unwrap();
+            reserve_state.config.oracle.sources[index] = OracleSource {
+                oracle_type: oracle_type as u8,
+                feed,
+            };
+        }
+        UpdateReserveConfigMode::LiquidationThresholdPct => {
+            reserve_state.config.liquidation_threshold_pct = value[0];
+            reserve_state.config.validate_collateral_params()?;
+        }
+        UpdateReserveConfigMode::LiquidationBonusBps => {
+            let liquidation_bonus_bps = read_u64(value);
+            if liquidation_bonus_bps > MAX_LIQUIDATION_BONUS_BPS {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.liquidation_bonus_bps = liquidation_bonus_bps;
+        }
+        UpdateReserveConfigMode::BorrowRateCurve => {
+            let borrow_rate_curve =
+                *bytemuck::from_bytes::<BorrowRateCurve>(&value[..BorrowRateCurve::LEN]);
+            borrow_rate_curve.validate()?;
+            reserve_state.config.borrow_rate_curve = borrow_rate_curve;
+        }
+        UpdateReserveConfigMode::ProtocolTakeRatePct => {
+            let protocol_take_rate_pct = value[0];
+            if protocol_take_rate_pct > 100 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.protocol_take_rate_pct = protocol_take_rate_pct;
+        }
+        UpdateReserveConfigMode::MaxPriceAgeSeconds => {
+            reserve_state.config.price_guards.max_age_seconds = read_u64(value);
+        }
+        UpdateReserveConfigMode::MaxPriceConfidenceBps => {
+            let max_confidence_bps = read_u64(value);
+            if max_confidence_bps > 10_000 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.price_guards.max_confidence_bps = max_confidence_bps;
+        }
+        UpdateReserveConfigMode::MaxTwapDivergenceBps => {
+            let max_twap_divergence_bps = read_u64(value);
+            if max_twap_divergence_bps > 10_000 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.price_guards.max_twap_divergence_bps = max_twap_divergence_bps;
+        }
+        UpdateReserveConfigMode::OraclePriceAggregation => {
+            let aggregation = PriceAggregation::try_from(value[0])?;
+            reserve_state.config.oracle.aggregation = aggregation as u8;
+        }
+        UpdateReserveConfigMode::MaxOracleSourceDivergenceBps => {
+            let max_source_divergence_bps = read_u64(value);
+            if max_source_divergence_bps > 10_000 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.oracle.max_source_divergence_bps = max_source_divergence_bps;
+        }
+        UpdateReserveConfigMode::ElevationGroups => {
+            let elevation_groups: [u8; MAX_ELEVATION_GROUPS] =
+                value[..MAX_ELEVATION_GROUPS].try_into().unwrap();
+            if elevation_groups
+                .iter()
+                .any(|&id| id as usize > MAX_ELEVATION_GROUPS)
+            {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.elevation_groups = elevation_groups;
+        }
+        UpdateReserveConfigMode::AutodeleverageRampBpsPerDay => {
+            let ramp_bps_per_day = read_u64(value);
+            if ramp_bps_per_day == 0 || ramp_bps_per_day > 10_000 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.autodeleverage_ramp_bps_per_day = ramp_bps_per_day;
+        }
+        UpdateReserveConfigMode::FlashLoanFeeBps => {
+            let flash_loan_fee_bps = read_u64(value);
+            if flash_loan_fee_bps > 10_000 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.flash_loan_fee_bps = flash_loan_fee_bps;
+        }
+        UpdateReserveConfigMode::BorrowFeeBps => {
+            let borrow_fee_bps = read_u64(value);
+            if borrow_fee_bps > 10_000 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.borrow_fee_bps = borrow_fee_bps;
+        }
+        UpdateReserveConfigMode::ProtocolLiquidationFeePct => {
+            let protocol_liquidation_fee_pct = value[0];
+            if protocol_liquidation_fee_pct > 100 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.protocol_liquidation_fee_pct = protocol_liquidation_fee_pct;
+        }
+        UpdateReserveConfigMode::ReferrerFeePct => {
+            let referrer_fee_pct = value[0];
+            if referrer_fee_pct > 100 && referrer_fee_pct != REFERRER_FEE_PCT_INHERIT {
+                return Err(ProgramError::InvalidArgument);
+            }
+            reserve_state.config.referrer_fee_pct = referrer_fee_pct;
+        }
+        UpdateReserveConfigMode::DepositLimit => {
+            reserve_state.config.deposit_limit = read_u64(value);
+        }
+        UpdateReserveConfigMode::BorrowLimit => {
+            reserve_state.config.borrow_limit = read_u64(value);
+        }
+        UpdateReserveConfigMode::WithdrawalCap => {
+            let (max_amount, interval_seconds) = read_rolling_cap(value)?;
+            reserve_state.withdrawal_cap.max_amount = max_amount;
+            reserve_state.withdrawal_cap.interval_seconds = interval_seconds;
+        }
+        UpdateReserveConfigMode::BorrowCap => {
+            let (max_amount, interval_seconds) = read_rolling_cap(value)?;
+            reserve_state.borrow_cap.max_amount = max_amount;
+            reserve_state.borrow_cap.interval_seconds = interval_seconds;
+        }
+        UpdateReserveConfigMode::Status => {
+            let status = ReserveStatus::try_from(value[0])?;
+            apply_reserve_status(reserve_state, status, value[1])?;
+        }
+        UpdateReserveConfigMode::Autodeleverage => {
+            apply_reserve_autodeleverage(
+                reserve_state,
+                value[0] != 0,
+                value[1],
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Lowering the liquidation threshold at once could make healthy
+/// obligations liquidatable. It is ramped down with `SetReserveAutodeleverage`
+/// instead, and once the ramp has reached its target the target can be made
+/// the reserve's threshold, which ends the ramp.
+fn finish_autodeleverage(
+    reserve_state: &mut ReserveState,
+    liquidation_threshold_pct: u8,
+) -> ProgramResult {
+    let unix_timestamp = Clock::get()?.unix_timestamp;
+    let ramped_threshold_bps = reserve_state.autodeleverage_threshold_bps(
+        reserve_state.config.liquidation_threshold_pct,
+        unix_timestamp,
+    );
+    if reserve_state.autodeleverage_target_threshold_pct != liquidation_threshold_pct
+        || ramped_threshold_bps != Some(liquidation_threshold_pct as u64 * 100)
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    apply_reserve_autodeleverage(reserve_state, false, 0, unix_timestamp)
+}
+
+#[inline(always)]
+fn read_u64(value: &[u8; 128]) -> u64 {
+    u64::from_le_bytes(value[..8].try_into().unwrap())
+}
+
+/// Reads a rolling cap's max amount and interval. The interval must fit a
+/// unix timestamp.
+fn read_rolling_cap(value: &[u8; 128]) -> Result<(u64, u64), ProgramError> {
+    let max_amount = read_u64(value);
+    let interval_seconds = u64::from_le_bytes(value[8..16].try_into().unwrap());
+    if interval_seconds > i64::MAX as u64 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok((max_amount, interval_seconds))
+}
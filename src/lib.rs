@@ -3,13 +3,17 @@ use pinocchio::{
     ProgramResult,
 };
 
+pub mod error;
 pub mod helper;
 pub mod instructions;
+pub mod oracle;
 pub mod state;
 
 pub mod constants;
+pub use error::*;
 pub use helper::*;
 pub use instructions::*;
+pub use oracle::*;
 pub use state::*;
 
 pub use constants::*;
@@ -31,7 +35,9 @@ pub fn process_instruction(
         .split_first()
         .ok_or(ProgramError::InvalidInstructionData)?;
 
-    use instructions::{market, PlendInstructions::*};
+    use instructions::{
+        governance, market, obligations, oracles, referrers, reserves, PlendInstructions::*,
+    };
 
     let instruction = instructions::PlendInstructions::try_from(*discriminant)?;
 
@@ -48,9 +54,112 @@ pub fn process_instruction(
             ensure_payload_len::<market::UpdateRiskCouncilIxData>(payload)?;
             market::process_update_risk_council(program_id, accounts, payload)
         }
-        UpdateLendingMarketOwner => {
-            ensure_payload_len::<market::UpdateLendingMarketOwnerIxData>(payload)?;
-            market::process_update_lending_market_owner(program_id, accounts, payload)
+        ProposeLendingMarketOwner => {
+            ensure_payload_len::<market::ProposeLendingMarketOwnerIxData>(payload)?;
+            market::process_propose_lending_market_owner(program_id, accounts, payload)
+        }
+        AcceptLendingMarketOwner => {
+            market::process_accept_lending_market_owner(program_id, accounts)
+        }
+        CancelOwnerProposal => market::process_cancel_owner_proposal(program_id, accounts),
+        AcceptRiskCouncil => market::process_accept_risk_council(program_id, accounts),
+        QueueConfigChange => {
+            ensure_payload_len::<governance::QueueConfigChangeIxData>(payload)?;
+            governance::process_queue_config_change(program_id, accounts, payload)
+        }
+        ExecuteConfigChange => governance::process_execute_config_change(program_id, accounts),
+        VetoConfigChange => governance::process_veto_config_change(program_id, accounts),
+        UpdateLendingMarket => {
+            ensure_payload_len::<market::UpdateLendingMarketIxData>(payload)?;
+            market::process_update_lending_market(program_id, accounts, payload)
+        }
+        MigrateLendingMarket => market::process_migrate_lending_market(program_id, accounts),
+        InitReserve => reserves::process_init_reserve(program_id, accounts),
+        DepositReserveLiquidity => {
+            ensure_payload_len::<reserves::DepositReserveLiquidityIxData>(payload)?;
+            reserves::process_deposit_reserve_liquidity(program_id, accounts, payload)
+        }
+        RedeemReserveCollateral => {
+            ensure_payload_len::<reserves::RedeemReserveCollateralIxData>(payload)?;
+            reserves::process_redeem_reserve_collateral(program_id, accounts, payload)
+        }
+        InitObligation => {
+            ensure_payload_len::<obligations::InitObligationIxData>(payload)?;
+            obligations::process_init_obligation(program_id, accounts, payload)
+        }
+        CloseObligation => obligations::process_close_obligation(program_id, accounts),
+        UpdateReserveConfig => {
+            ensure_payload_len::<reserves::UpdateReserveConfigIxData>(payload)?;
+            reserves::process_update_reserve_config(program_id, accounts, payload)
+        }
+        BorrowObligationLiquidity => {
+            ensure_payload_len::<obligations::BorrowObligationLiquidityIxData>(payload)?;
+            obligations::process_borrow_obligation_liquidity(program_id, accounts, payload)
+        }
+        RepayObligationLiquidity => {
+            ensure_payload_len::<obligations::RepayObligationLiquidityIxData>(payload)?;
+            obligations::process_repay_obligation_liquidity(program_id, accounts, payload)
+        }
+        DepositObligationCollateral => {
+            ensure_payload_len::<obligations::DepositObligationCollateralIxData>(payload)?;
+            obligations::process_deposit_obligation_collateral(program_id, accounts, payload)
+        }
+        WithdrawObligationCollateral => {
+            ensure_payload_len::<obligations::WithdrawObligationCollateralIxData>(payload)?;
+            obligations::process_withdraw_obligation_collateral(program_id, accounts, payload)
+        }
+        DepositReserveLiquidityAndObligationCollateral => {
+            ensure_payload_len::<obligations::DepositReserveLiquidityAndObligationCollateralIxData>(
+                payload,
+            )?;
+            obligations::process_deposit_reserve_liquidity_and_obligation_collateral(
+                program_id, accounts, payload,
+            )
+        }
+        LiquidateObligation => {
+            ensure_payload_len::<obligations::LiquidateObligationIxData>(payload)?;
+            obligations::process_liquidate_obligation(program_id, accounts, payload)
+        }
+        RefreshReserve => reserves::process_refresh_reserve(program_id, accounts),
+        RefreshObligation => obligations::process_refresh_obligation(program_id, accounts),
+        InitManualPrice => {
+            ensure_payload_len::<oracles::InitManualPriceIxData>(payload)?;
+            oracles::process_init_manual_price(program_id, accounts, payload)
+        }
+        UpdateManualPrice => {
+            ensure_payload_len::<oracles::UpdateManualPriceIxData>(payload)?;
+            oracles::process_update_manual_price(program_id, accounts, payload)
+        }
+        UpdateElevationGroup => {
+            ensure_payload_len::<market::UpdateElevationGroupIxData>(payload)?;
+            market::process_update_elevation_group(program_id, accounts, payload)
+        }
+        RequestElevationGroup => {
+            ensure_payload_len::<obligations::RequestElevationGroupIxData>(payload)?;
+            obligations::process_request_elevation_group(program_id, accounts, payload)
+        }
+        SetReserveAutodeleverage => {
+            ensure_payload_len::<reserves::SetReserveAutodeleverageIxData>(payload)?;
+            reserves::process_set_reserve_autodeleverage(program_id, accounts, payload)
+        }
+        LiquidateObligationAutodeleverage => {
+            ensure_payload_len::<obligations::LiquidateObligationIxData>(payload)?;
+            obligations::process_liquidate_obligation_autodeleverage(program_id, accounts, payload)
+        }
+        FlashBorrowReserveLiquidity => {
+            ensure_payload_len::<reserves::FlashBorrowReserveLiquidityIxData>(payload)?;
+            reserves::process_flash_borrow_reserve_liquidity(program_id, accounts, payload)
+        }
+        FlashRepayReserveLiquidity => {
+            ensure_payload_len::<reserves::FlashRepayReserveLiquidityIxData>(payload)?;
+            reserves::process_flash_repay_reserve_liquidity(program_id, accounts, payload)
+        }
+        RedeemFees => reserves::process_redeem_fees(program_id, accounts),
+        InitReferrerState => referrers::process_init_referrer_state(program_id, accounts),
+        WithdrawReferrerFees => referrers::process_withdraw_referrer_fees(program_id, accounts),
+        SetReserveStatus => {
+            ensure_payload_len::<reserves::SetReserveStatusIxData>(payload)?;
+            reserves::process_set_reserve_status(program_id, accounts, payload)
         }
     }
 }
@@ -0,0 +1,33 @@
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    helper::utils::{DataLen, Initialized},
+    oracle::{OracleAdapter, Price, WAD_EXPONENT},
+    state::ManualPriceState,
+};
+
+/// Program-owned [`ManualPriceState`] accounts.
+pub struct ManualOracle;
+
+impl OracleAdapter for ManualOracle {
+    const OWNER: Pubkey = crate::ID;
+
+    fn parse_price(data: &[u8]) -> Result<Price, ProgramError> {
+        if data.len() != ManualPriceState::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let manual_price = bytemuck::from_bytes::<ManualPriceState>(data);
+        if !manual_price.is_initialized() || manual_price.price_wads == 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Price {
+            value: manual_price.price_wads,
+            exponent: WAD_EXPONENT,
+            confidence: 0,
+            publish_slot: manual_price.publish_slot,
+            publish_time: manual_price.publish_timestamp,
+            twap: None,
+        })
+    }
+}
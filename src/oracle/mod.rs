@@ -0,0 +1,306 @@
+//! Price feeds a reserve can be valued against.
+//!
+//! Every supported feed format is read through [`OracleAdapter`] and
+//! normalized to a [`Price`], so the rest of the program never looks at
+//! provider-specific layouts.
+
+pub mod manual;
+pub mod pyth;
+pub mod switchboard;
+
+pub use manual::*;
+pub use pyth::*;
+pub use switchboard::*;
+
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    helper::math::{Decimal, Rounding},
+    MAX_ORACLE_SOURCES,
+};
+
+/// Fixed-point exponent of a `_wads` value.
+pub(crate) const WAD_EXPONENT: i32 = -18;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OracleType {
+    None = 0,
+    Pyth = 1,
+    Switchboard = 2,
+    Manual = 3,
+    PythEma = 4,
+}
+
+impl TryFrom<u8> for OracleType {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OracleType::None),
+            1 => Ok(OracleType::Pyth),
+            2 => Ok(OracleType::Switchboard),
+            3 => Ok(OracleType::Manual),
+            4 => Ok(OracleType::PythEma),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriceAggregation {
+    /// Median of every source that produced an acceptable price.
+    Median = 0,
+    /// The first source, in configured order, that produced an acceptable
+    /// price.
+    PrimaryWithFallback = 1,
+}
+
+impl TryFrom<u8> for PriceAggregation {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PriceAggregation::Median),
+            1 => Ok(PriceAggregation::PrimaryWithFallback),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+}
+
+/// One feed a reserve may be priced from.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct OracleSource {
+    pub oracle_type: u8,
+    pub feed: Pubkey,
+}
+
+impl OracleSource {
+    pub fn is_empty(&self) -> bool {
+        self.oracle_type == OracleType::None as u8
+    }
+}
+
+/// The feeds a reserve is priced from and how their prices are combined.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct OracleConfig {
+    /// Unused slots have `OracleType::None`.
+    pub sources: [OracleSource; MAX_ORACLE_SOURCES],
+    pub aggregation: u8,
+    /// Largest distance between the aggregate price and any accepted source
+    /// price, in basis points of the aggregate. Zero disables the check.
+    pub max_source_divergence_bps: u64,
+}
+
+/// A feed's latest price: `value * 10^exponent` quote units per whole token,
+/// give or take `confidence` in the same units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Price {
+    pub value: u128,
+    pub exponent: i32,
+    pub confidence: u128,
+    pub publish_slot: u64,
+    /// Unix timestamp the price was published at.
+    pub publish_time: i64,
+    /// Time-weighted average price at the same exponent, for feeds that
+    /// provide one.
+    pub twap: Option<u128>,
+}
+
+impl Price {
+    pub fn to_decimal(&self) -> Result<Decimal, ProgramError> {
+        scale(self.value, self.exponent)
+    }
+
+    pub fn confidence_to_decimal(&self) -> Result<Decimal, ProgramError> {
+        scale(self.confidence, self.exponent)
+    }
+}
+
+/// Limits a reserve places on the prices it accepts.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct PriceGuards {
+    pub max_age_seconds: u64,
+    /// Widest confidence interval accepted, in basis points of the price.
+    pub max_confidence_bps: u64,
+    /// Largest distance from the feed's TWAP accepted, in basis points of the
+    /// TWAP. Zero disables the check; when set, feeds without a TWAP are
+    /// rejected.
+    pub max_twap_divergence_bps: u64,
+}
+
+impl PriceGuards {
+    pub fn accepts(&self, price: &Price, unix_timestamp: i64) -> Result<bool, ProgramError> {
+        let age = unix_timestamp.saturating_sub(price.publish_time).max(0) as u64;
+        if age > self.max_age_seconds {
+            return Ok(false);
+        }
+
+        let value = Decimal::from_wads(price.value);
+        let max_confidence = value
+            .try_mul_u64(self.max_confidence_bps)?
+            .try_div_u64(10_000, Rounding::Down)?;
+        if Decimal::from_wads(price.confidence) > max_confidence {
+            return Ok(false);
+        }
+
+        if self.max_twap_divergence_bps > 0 {
+            let Some(twap) = price.twap else {
+                return Ok(false);
+            };
+            let divergence = Decimal::from_wads(price.value.abs_diff(twap));
+            let max_divergence = Decimal::from_wads(twap)
+                .try_mul_u64(self.max_twap_divergence_bps)?
+                .try_div_u64(10_000, Rounding::Down)?;
+            if divergence > max_divergence {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+fn scale(value: u128, exponent: i32) -> Result<Decimal, ProgramError> {
+    let shift = exponent - WAD_EXPONENT;
+    let factor = Decimal::from_u64(10).try_pow(shift.unsigned_abs() as u64)?;
+    let value = Decimal::from_wads(value);
+    if shift >= 0 {
+        value.try_mul(factor, Rounding::Down)
+    } else {
+        value.try_div(factor, Rounding::Down)
+    }
+}
+
+/// One price feed format.
+pub trait OracleAdapter {
+    /// Program that must own the feed account.
+    const OWNER: Pubkey;
+
+    /// Decodes the feed account's data.
+    fn parse_price(data: &[u8]) -> Result<Price, ProgramError>;
+
+    fn read_price(feed: &AccountInfo) -> Result<Price, ProgramError> {
+        if !feed.is_owned_by(&Self::OWNER) {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let data = feed.try_borrow_data()?;
+        Self::parse_price(&data)
+    }
+}
+
+/// Reads `feed` with the adapter `source` names, after checking it is the
+/// configured feed account.
+pub fn get_price(source: &OracleSource, feed: &AccountInfo) -> Result<Price, ProgramError> {
+    if feed.key() != &source.feed {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    match OracleType::try_from(source.oracle_type)? {
+        OracleType::None => Err(ProgramError::InvalidAccountData),
+        OracleType::Pyth => PythPullOracle::read_price(feed),
+        OracleType::Switchboard => SwitchboardOnDemand::read_price(feed),
+        OracleType::Manual => ManualOracle::read_price(feed),
+        OracleType::PythEma => PythEmaOracle::read_price(feed),
+    }
+}
+
+/// Reads every configured source and combines the prices that pass
+/// `price_guards`. `feeds` holds the feed account of each configured source,
+/// in source order.
+///
+/// Returns `None` when no source produced an acceptable price or the
+/// accepted prices disagree by more than the configured tolerance. A source
+/// whose account cannot be decoded, or whose price does not fit a `Decimal`,
+/// counts as having no acceptable price.
+pub fn get_market_price(
+    oracle_config: &OracleConfig,
+    price_guards: &PriceGuards,
+    feeds: &[AccountInfo],
+    unix_timestamp: i64,
+) -> Result<Option<Decimal>, ProgramError> {
+    let sources = oracle_config.sources;
+    let mut feeds = feeds.iter();
+    let mut prices = [Decimal::zero(); MAX_ORACLE_SOURCES];
+    let mut accepted = 0;
+
+    for source in sources.iter().filter(|s| !s.is_empty()) {
+        let feed = feeds.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if feed.key() != &source.feed {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let Ok(price) = get_price(source, feed) else {
+            continue;
+        };
+        if !price_guards.accepts(&price, unix_timestamp)? {
+            continue;
+        }
+        // A price that cannot be scaled to wads is as unusable as a feed
+        // that cannot be decoded; the other sources can still price the
+        // reserve.
+        let Ok(price) = price.to_decimal() else {
+            continue;
+        };
+        if price.is_zero() {
+            continue;
+        }
+        prices[accepted] = price;
+        accepted += 1;
+    }
+
+    aggregate_prices(
+        &mut prices[..accepted],
+        PriceAggregation::try_from(oracle_config.aggregation)?,
+        oracle_config.max_source_divergence_bps,
+    )
+}
+
+/// Combines accepted source prices, given in source order.
+pub fn aggregate_prices(
+    prices: &mut [Decimal],
+    aggregation: PriceAggregation,
+    max_source_divergence_bps: u64,
+) -> Result<Option<Decimal>, ProgramError> {
+    let Some(&primary) = prices.first() else {
+        return Ok(None);
+    };
+
+    let aggregate = match aggregation {
+        PriceAggregation::PrimaryWithFallback => primary,
+        PriceAggregation::Median => {
+            prices.sort_unstable();
+            let middle = prices.len() / 2;
+            if prices.len() % 2 == 1 {
+                prices[middle]
+            } else {
+                prices[middle - 1]
+                    .try_add(prices[middle])?
+                    .try_div_u64(2, Rounding::Down)?
+            }
+        }
+    };
+
+    if max_source_divergence_bps > 0 {
+        let max_divergence = aggregate
+            .try_mul_u64(max_source_divergence_bps)?
+            .try_div_u64(10_000, Rounding::Down)?;
+        for &price in prices.iter() {
+            let divergence = price.max(aggregate).try_sub(price.min(aggregate))?;
+            if divergence > max_divergence {
+                return Ok(None);
+            }
+        }
+    }
+
+    Ok(Some(aggregate))
+}
+
+#[inline(always)]
+pub(crate) fn read_array<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    data[offset..offset + N].try_into().unwrap()
+}
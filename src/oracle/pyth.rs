@@ -0,0 +1,76 @@
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::oracle::{read_array, OracleAdapter, Price};
+
+/// Pyth pull-oracle `PriceUpdateV2` accounts, as posted by the Pyth receiver
+/// program.
+pub struct PythPullOracle;
+
+/// The exponential moving average carried in the same `PriceUpdateV2`
+/// accounts, for use as a TWAP source.
+pub struct PythEmaOracle;
+
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Anchor discriminator of `PriceUpdateV2`.
+pub const PYTH_PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// Borsh tag of `VerificationLevel::Full`. Partially verified updates carry
+/// an extra signature-count byte and are rejected.
+const VERIFICATION_LEVEL_FULL: u8 = 1;
+
+const VERIFICATION_LEVEL_OFFSET: usize = 8 + 32;
+// Offsets below assume a fully verified update.
+const PRICE_OFFSET: usize = VERIFICATION_LEVEL_OFFSET + 1 + 32;
+const CONFIDENCE_OFFSET: usize = PRICE_OFFSET + 8;
+const EXPONENT_OFFSET: usize = CONFIDENCE_OFFSET + 8;
+const PUBLISH_TIME_OFFSET: usize = EXPONENT_OFFSET + 4;
+const EMA_PRICE_OFFSET: usize = PUBLISH_TIME_OFFSET + 8 + 8;
+const EMA_CONFIDENCE_OFFSET: usize = EMA_PRICE_OFFSET + 8;
+const POSTED_SLOT_OFFSET: usize = EMA_CONFIDENCE_OFFSET + 8;
+pub const PYTH_PRICE_UPDATE_LEN: usize = POSTED_SLOT_OFFSET + 8;
+
+impl OracleAdapter for PythPullOracle {
+    const OWNER: Pubkey = PYTH_RECEIVER_PROGRAM_ID;
+
+    fn parse_price(data: &[u8]) -> Result<Price, ProgramError> {
+        if data.len() < PYTH_PRICE_UPDATE_LEN || data[..8] != PYTH_PRICE_UPDATE_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[VERIFICATION_LEVEL_OFFSET] != VERIFICATION_LEVEL_FULL {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let price = i64::from_le_bytes(read_array(data, PRICE_OFFSET));
+        if price <= 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let ema_price = i64::from_le_bytes(read_array(data, EMA_PRICE_OFFSET));
+
+        Ok(Price {
+            value: price as u128,
+            exponent: i32::from_le_bytes(read_array(data, EXPONENT_OFFSET)),
+            confidence: u64::from_le_bytes(read_array(data, CONFIDENCE_OFFSET)) as u128,
+            publish_slot: u64::from_le_bytes(read_array(data, POSTED_SLOT_OFFSET)),
+            publish_time: i64::from_le_bytes(read_array(data, PUBLISH_TIME_OFFSET)),
+            twap: (ema_price > 0).then_some(ema_price as u128),
+        })
+    }
+}
+
+impl OracleAdapter for PythEmaOracle {
+    const OWNER: Pubkey = PYTH_RECEIVER_PROGRAM_ID;
+
+    fn parse_price(data: &[u8]) -> Result<Price, ProgramError> {
+        let price = PythPullOracle::parse_price(data)?;
+        let ema_price = price.twap.ok_or(ProgramError::InvalidAccountData)?;
+
+        Ok(Price {
+            value: ema_price,
+            confidence: u64::from_le_bytes(read_array(data, EMA_CONFIDENCE_OFFSET)) as u128,
+            ..price
+        })
+    }
+}
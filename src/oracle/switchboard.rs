@@ -0,0 +1,51 @@
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::oracle::{read_array, OracleAdapter, Price};
+
+/// Switchboard on-demand `PullFeedAccountData` accounts. The aggregated
+/// `result` is used; its values are 18-decimal fixed point.
+pub struct SwitchboardOnDemand;
+
+pub const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+
+/// Anchor discriminator of `PullFeedAccountData`.
+pub const SWITCHBOARD_PULL_FEED_DISCRIMINATOR: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+
+const SWITCHBOARD_EXPONENT: i32 = -18;
+
+/// Discriminator, 32 oracle submissions of 64 bytes, then the feed's
+/// configuration up to `last_update_timestamp`.
+const LAST_UPDATE_TIMESTAMP_OFFSET: usize = 8 + 32 * 64 + 32 * 3 + 8 * 3 + 4 + 32 + 4;
+const RESULT_OFFSET: usize = LAST_UPDATE_TIMESTAMP_OFFSET + 8 * 2 + 32;
+const VALUE_OFFSET: usize = RESULT_OFFSET;
+const STD_DEV_OFFSET: usize = VALUE_OFFSET + 16;
+const SLOT_OFFSET: usize = STD_DEV_OFFSET + 16 * 5 + 8;
+pub const SWITCHBOARD_PULL_FEED_MIN_LEN: usize = SLOT_OFFSET + 8;
+
+impl OracleAdapter for SwitchboardOnDemand {
+    const OWNER: Pubkey = SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
+
+    fn parse_price(data: &[u8]) -> Result<Price, ProgramError> {
+        if data.len() < SWITCHBOARD_PULL_FEED_MIN_LEN
+            || data[..8] != SWITCHBOARD_PULL_FEED_DISCRIMINATOR
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let value = i128::from_le_bytes(read_array(data, VALUE_OFFSET));
+        let std_dev = i128::from_le_bytes(read_array(data, STD_DEV_OFFSET));
+        if value <= 0 || std_dev < 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Price {
+            value: value as u128,
+            exponent: SWITCHBOARD_EXPONENT,
+            confidence: std_dev as u128,
+            publish_slot: u64::from_le_bytes(read_array(data, SLOT_OFFSET)),
+            publish_time: i64::from_le_bytes(read_array(data, LAST_UPDATE_TIMESTAMP_OFFSET)),
+            twap: None,
+        })
+    }
+}
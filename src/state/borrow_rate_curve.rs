@@ -0,0 +1,115 @@
+use pinocchio::program_error::ProgramError;
+
+use crate::helper::{
+    math::{Decimal, Rounding},
+    utils::DataLen,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// Number of points in a reserve's borrow rate curve.
+pub const BORROW_RATE_CURVE_POINTS: usize = 11;
+
+/// Utilization is expressed against this many basis points.
+pub const FULL_UTILIZATION_BPS: u32 = 10_000;
+
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct CurvePoint {
+    pub utilization_rate_bps: u32,
+    pub borrow_rate_bps: u32,
+}
+
+/// Piecewise-linear map from utilization to borrow APR.
+///
+/// The first point sits at 0% utilization and utilization strictly increases
+/// until a point reaches 100%; any points after that repeat it.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct BorrowRateCurve {
+    pub points: [CurvePoint; BORROW_RATE_CURVE_POINTS],
+}
+
+impl DataLen for BorrowRateCurve {
+    const LEN: usize = core::mem::size_of::<Self>();
+}
+
+impl Default for BorrowRateCurve {
+    /// A flat 0% curve.
+    fn default() -> Self {
+        let mut points = [CurvePoint {
+            utilization_rate_bps: FULL_UTILIZATION_BPS,
+            borrow_rate_bps: 0,
+        }; BORROW_RATE_CURVE_POINTS];
+        points[0].utilization_rate_bps = 0;
+        Self { points }
+    }
+}
+
+impl BorrowRateCurve {
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        let points = self.points;
+        if points[0].utilization_rate_bps != 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        for pair in points.windows(2) {
+            let (previous, next) = (pair[0], pair[1]);
+            let previous_utilization = previous.utilization_rate_bps;
+            let next_utilization = next.utilization_rate_bps;
+
+            if previous_utilization == FULL_UTILIZATION_BPS {
+                if next != previous {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                continue;
+            }
+            if next_utilization <= previous_utilization
+                || next_utilization > FULL_UTILIZATION_BPS
+                || next.borrow_rate_bps < previous.borrow_rate_bps
+            {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        if points[BORROW_RATE_CURVE_POINTS - 1].utilization_rate_bps != FULL_UTILIZATION_BPS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+
+    /// Borrow APR at `utilization_rate`, interpolating between the
+    /// surrounding points. Utilization above 100% is treated as 100%.
+    pub fn borrow_rate(&self, utilization_rate: Decimal) -> Result<Decimal, ProgramError> {
+        let utilization_rate = utilization_rate.min(Decimal::one());
+        let points = self.points;
+
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let start_utilization = Decimal::from_bps(start.utilization_rate_bps as u64);
+            let end_utilization = Decimal::from_bps(end.utilization_rate_bps as u64);
+            if utilization_rate > end_utilization {
+                continue;
+            }
+
+            let start_rate = Decimal::from_bps(start.borrow_rate_bps as u64);
+            if end_utilization == start_utilization {
+                return Ok(start_rate);
+            }
+
+            let end_rate = Decimal::from_bps(end.borrow_rate_bps as u64);
+            let progress = utilization_rate
+                .try_sub(start_utilization)?
+                .try_div(end_utilization.try_sub(start_utilization)?, Rounding::Down)?;
+            return start_rate.try_add(
+                end_rate
+                    .try_sub(start_rate)?
+                    .try_mul(progress, Rounding::Down)?,
+            );
+        }
+
+        Ok(Decimal::from_bps(
+            points[BORROW_RATE_CURVE_POINTS - 1].borrow_rate_bps as u64,
+        ))
+    }
+}
@@ -1,30 +1,92 @@
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
 
 use crate::{
-    helper::{account_init::StateDefinition, utils::DataLen},
-    LENDING_MARKET_SEED,
+    helper::{
+        account_init::StateDefinition,
+        utils::{DataLen, Initialized},
+    },
+    DEFAULT_LIQUIDATION_CLOSE_FACTOR_PCT, DEFAULT_MIN_FULL_LIQUIDATION_VALUE_THRESHOLD_WADS,
+    LENDING_MARKET_SEED, LENDING_MARKET_VERSION, MAX_ELEVATION_GROUPS,
 };
 use bytemuck::{Pod, Zeroable};
 
+/// Risk parameters for obligations that only hold correlated assets: every
+/// deposit from a reserve in the group and debt in `debt_reserve` alone.
+/// Obligations in a group use these in place of their reserves' LTV,
+/// liquidation threshold and bonus, and ignore borrow factors.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ElevationGroup {
+    pub id: u8,
+    pub loan_to_value_pct: u8,
+    pub liquidation_threshold_pct: u8,
+    pub liquidation_bonus_bps: u64,
+    /// The only reserve members of the group may borrow from. A zeroed key
+    /// disables the group.
+    pub debt_reserve: Pubkey,
+}
+
+impl ElevationGroup {
+    pub fn is_enabled(&self) -> bool {
+        self.debt_reserve != Pubkey::default()
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
 pub struct LendingMarketState {
+    pub discriminator: [u8; 8],
     pub version: u64,
     pub lending_market_owner: Pubkey,
     pub quote_currency: [u8; 32],
     pub risk_council: Pubkey,
     pub emergency_mode: u8,
+    /// Share of a borrow a liquidator may repay in one instruction.
+    pub liquidation_max_debt_close_factor_pct: u8,
+    /// Debt value (quote wads) under which an obligation may be liquidated
+    /// in full regardless of the close factor.
+    pub min_full_liquidation_value_threshold_wads: u128,
+    /// Group `id` lives at index `id - 1`.
+    pub elevation_groups: [ElevationGroup; MAX_ELEVATION_GROUPS],
+    /// Proposed owner, zeroed when no transfer is pending. Becomes
+    /// `lending_market_owner` once it signs `AcceptLendingMarketOwner`.
+    pub pending_owner: Pubkey,
+    /// Proposed risk council, zeroed when no change is pending.
+    pub pending_risk_council: Pubkey,
+    /// How long queued configuration changes wait before they can be
+    /// executed. While nonzero, reserve configuration and risk council
+    /// changes must go through the queue.
+    pub governance_delay_seconds: u64,
+    /// Id of the next queued configuration change.
+    pub queued_config_change_count: u64,
+    /// Share of borrow fees paid to referrers, in basis points, on reserves
+    /// whose `referrer_fee_pct` is `REFERRER_FEE_PCT_INHERIT`.
+    pub referral_fee_bps: u64,
+    /// Most any single obligation may borrow, in quote wads.
+    pub global_allowed_borrow_value_wads: u128,
+    /// For keepers: how old a price may get, as a share of the reserve's max
+    /// price age, before they refresh it. 0 leaves it to them.
+    pub price_refresh_trigger_to_max_age_pct: u8,
+    /// Display name, UTF-8 padded with zeroes.
+    pub name: [u8; 32],
 }
 
 impl StateDefinition for LendingMarketState {
     const LEN: usize = core::mem::size_of::<Self>();
     const SEED: &'static str = LENDING_MARKET_SEED;
+    const DISCRIMINATOR: [u8; 8] = *b"lendmrkt";
 }
 
 impl DataLen for LendingMarketState {
     const LEN: usize = <Self as StateDefinition>::LEN;
 }
 
+impl Initialized for LendingMarketState {
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::DISCRIMINATOR
+    }
+}
+
 impl LendingMarketState {
     pub fn new(
         lending_market_owner: Pubkey,
@@ -32,11 +94,57 @@ impl LendingMarketState {
         risk_council: Pubkey,
     ) -> Self {
         Self {
-            version: 0,
+            discriminator: Self::DISCRIMINATOR,
+            version: LENDING_MARKET_VERSION,
             lending_market_owner,
             quote_currency,
             risk_council,
             emergency_mode: 0,
+            liquidation_max_debt_close_factor_pct: DEFAULT_LIQUIDATION_CLOSE_FACTOR_PCT,
+            min_full_liquidation_value_threshold_wads:
+                DEFAULT_MIN_FULL_LIQUIDATION_VALUE_THRESHOLD_WADS,
+            elevation_groups: [ElevationGroup::zeroed(); MAX_ELEVATION_GROUPS],
+            pending_owner: Pubkey::default(),
+            pending_risk_council: Pubkey::default(),
+            governance_delay_seconds: 0,
+            queued_config_change_count: 0,
+            referral_fee_bps: 0,
+            global_allowed_borrow_value_wads: u128::MAX,
+            price_refresh_trigger_to_max_age_pct: 0,
+            name: [0; 32],
+        }
+    }
+
+    /// Size of the original, version 0 market: version, owner, quote
+    /// currency, risk council and emergency mode, with no discriminator.
+    pub const V0_LEN: usize = core::mem::offset_of!(Self, liquidation_max_debt_close_factor_pct)
+        - <Self as StateDefinition>::DISCRIMINATOR.len();
+
+    /// Rebuilds a version 0 market at the current layout, with defaults for
+    /// everything it lacked.
+    pub fn from_v0(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::V0_LEN || data[..8] != 0u64.to_le_bytes() {
+            return Err(ProgramError::InvalidAccountData);
         }
+        let mut state = Self::new(
+            data[8..40].try_into().unwrap(),
+            data[40..72].try_into().unwrap(),
+            data[72..104].try_into().unwrap(),
+        );
+        state.emergency_mode = data[104];
+        Ok(state)
+    }
+
+    pub fn is_timelocked(&self) -> bool {
+        self.governance_delay_seconds != 0
+    }
+
+    /// The enabled group with `id`, if any. Id 0 never names a group.
+    pub fn elevation_group(&self, id: u8) -> Option<ElevationGroup> {
+        let index = (id as usize).checked_sub(1)?;
+        self.elevation_groups
+            .get(index)
+            .copied()
+            .filter(ElevationGroup::is_enabled)
     }
 }
@@ -0,0 +1,67 @@
+use pinocchio::pubkey::Pubkey;
+
+use crate::{
+    helper::{
+        account_init::StateDefinition,
+        utils::{DataLen, Initialized},
+    },
+    MANUAL_PRICE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// A price set by the lending market owner, for assets without an external
+/// feed.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ManualPriceState {
+    pub discriminator: [u8; 8],
+    pub version: u64,
+    pub lending_market: Pubkey,
+    /// Mint the price is quoted for.
+    pub mint: Pubkey,
+    /// Quote-currency price of one whole token.
+    pub price_wads: u128,
+    /// Slot the price was last set in.
+    pub publish_slot: u64,
+    /// Unix timestamp the price was last set at.
+    pub publish_timestamp: i64,
+    pub bump: u8,
+}
+
+impl StateDefinition for ManualPriceState {
+    const LEN: usize = core::mem::size_of::<Self>();
+    const SEED: &'static str = MANUAL_PRICE_SEED;
+    const DISCRIMINATOR: [u8; 8] = *b"manprice";
+}
+
+impl DataLen for ManualPriceState {
+    const LEN: usize = <Self as StateDefinition>::LEN;
+}
+
+impl Initialized for ManualPriceState {
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::DISCRIMINATOR
+    }
+}
+
+impl ManualPriceState {
+    pub fn new(
+        lending_market: Pubkey,
+        mint: Pubkey,
+        price_wads: u128,
+        current_slot: u64,
+        unix_timestamp: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: 0,
+            lending_market,
+            mint,
+            price_wads,
+            publish_slot: current_slot,
+            publish_timestamp: unix_timestamp,
+            bump,
+        }
+    }
+}
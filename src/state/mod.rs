@@ -1,3 +1,15 @@
+pub mod borrow_rate_curve;
 pub mod lending_market;
+pub mod manual_price;
+pub mod obligation;
+pub mod queued_config_change;
+pub mod referrer;
+pub mod reserve;
 
+pub use borrow_rate_curve::*;
 pub use lending_market::*;
+pub use manual_price::*;
+pub use obligation::*;
+pub use queued_config_change::*;
+pub use referrer::*;
+pub use reserve::*;
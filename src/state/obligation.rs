@@ -0,0 +1,230 @@
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    helper::{
+        account_init::StateDefinition,
+        math::{Decimal, Rounding},
+        utils::{DataLen, Initialized},
+    },
+    MAX_OBLIGATION_BORROWS, MAX_OBLIGATION_DEPOSITS, OBLIGATION_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// Collateral tokens of one reserve locked in an obligation. A zeroed
+/// `deposit_reserve` marks the slot as free.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ObligationCollateral {
+    pub deposit_reserve: Pubkey,
+    pub deposited_amount: u64,
+}
+
+/// Liquidity borrowed from one reserve. `borrowed_amount_wads` is only
+/// accurate as of `cumulative_borrow_rate_wads`. A zeroed `borrow_reserve`
+/// marks the slot as free.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ObligationLiquidity {
+    pub borrow_reserve: Pubkey,
+    pub cumulative_borrow_rate_wads: u128,
+    pub borrowed_amount_wads: u128,
+}
+
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ObligationState {
+    pub discriminator: [u8; 8],
+    pub version: u64,
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    pub id: u64,
+    pub deposits: [ObligationCollateral; MAX_OBLIGATION_DEPOSITS],
+    pub borrows: [ObligationLiquidity; MAX_OBLIGATION_BORROWS],
+    /// Quote value of every deposit, as of the last refresh.
+    pub deposited_value_wads: u128,
+    /// Quote value of every borrow, as of the last refresh.
+    pub borrowed_value_wads: u128,
+    pub borrow_factor_adjusted_debt_value_wads: u128,
+    /// Debt value the deposits allow borrowing up to.
+    pub allowed_borrow_value_wads: u128,
+    /// Debt value above which the obligation can be liquidated.
+    pub unhealthy_borrow_value_wads: u128,
+    /// Slot the values above were last computed in.
+    pub last_update_slot: u64,
+    /// Set by any instruction that moves deposits or debt, so the values
+    /// must be recomputed before the next one.
+    pub stale: u8,
+    /// Set when the last refresh valued a reserve without a valid price, so
+    /// the values above cannot be trusted to borrow or withdraw against.
+    pub invalid_price: u8,
+    /// Elevation group the obligation is valued under, or 0 for none.
+    pub elevation_group: u8,
+    pub bump: u8,
+}
+
+impl StateDefinition for ObligationState {
+    const LEN: usize = core::mem::size_of::<Self>();
+    const SEED: &'static str = OBLIGATION_SEED;
+    const DISCRIMINATOR: [u8; 8] = *b"obligatn";
+}
+
+impl DataLen for ObligationState {
+    const LEN: usize = <Self as StateDefinition>::LEN;
+}
+
+impl Initialized for ObligationState {
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::DISCRIMINATOR
+    }
+}
+
+impl ObligationCollateral {
+    pub fn is_empty(&self) -> bool {
+        self.deposit_reserve == Pubkey::default()
+    }
+}
+
+impl ObligationLiquidity {
+    pub fn is_empty(&self) -> bool {
+        self.borrow_reserve == Pubkey::default()
+    }
+
+    /// Scales the debt by how far the reserve's borrow index has moved since
+    /// this slot last caught up with it.
+    pub fn accrue_interest(
+        &mut self,
+        cumulative_borrow_rate_wads: u128,
+    ) -> Result<(), ProgramError> {
+        let previous_rate = self.cumulative_borrow_rate_wads;
+        if cumulative_borrow_rate_wads < previous_rate {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if cumulative_borrow_rate_wads > previous_rate {
+            self.borrowed_amount_wads = Decimal::from_wads(self.borrowed_amount_wads)
+                .try_mul(
+                    Decimal::from_wads(cumulative_borrow_rate_wads),
+                    Rounding::Up,
+                )?
+                .try_div(Decimal::from_wads(previous_rate), Rounding::Up)?
+                .to_wads()?;
+            self.cumulative_borrow_rate_wads = cumulative_borrow_rate_wads;
+        }
+        Ok(())
+    }
+}
+
+impl ObligationState {
+    pub fn new(
+        lending_market: Pubkey,
+        owner: Pubkey,
+        id: u64,
+        current_slot: u64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: 0,
+            lending_market,
+            owner,
+            id,
+            deposits: [ObligationCollateral::zeroed(); MAX_OBLIGATION_DEPOSITS],
+            borrows: [ObligationLiquidity::zeroed(); MAX_OBLIGATION_BORROWS],
+            deposited_value_wads: 0,
+            borrowed_value_wads: 0,
+            borrow_factor_adjusted_debt_value_wads: 0,
+            allowed_borrow_value_wads: 0,
+            unhealthy_borrow_value_wads: 0,
+            last_update_slot: current_slot,
+            stale: 0,
+            invalid_price: 0,
+            elevation_group: 0,
+            bump,
+        }
+    }
+
+    /// True when no collateral is locked and nothing is borrowed.
+    pub fn is_empty(&self) -> bool {
+        self.deposits.iter().all(ObligationCollateral::is_empty)
+            && self.borrows.iter().all(ObligationLiquidity::is_empty)
+    }
+
+    pub fn has_valid_prices(&self) -> bool {
+        self.invalid_price == 0
+    }
+
+    /// True when the stored values were computed in `current_slot` and
+    /// nothing has changed since.
+    pub fn is_fresh(&self, current_slot: u64) -> bool {
+        self.stale == 0 && self.last_update_slot == current_slot
+    }
+
+    pub fn mark_stale(&mut self) {
+        self.stale = 1;
+    }
+
+    pub fn is_unhealthy(&self) -> bool {
+        self.borrow_factor_adjusted_debt_value_wads > self.unhealthy_borrow_value_wads
+    }
+
+    pub fn find_deposit_index(&self, deposit_reserve: &Pubkey) -> Option<usize> {
+        self.deposits
+            .iter()
+            .position(|deposit| &deposit.deposit_reserve == deposit_reserve)
+    }
+
+    /// Returns the deposit slot for `deposit_reserve`, claiming a free slot if
+    /// the obligation holds no collateral from it yet.
+    pub fn find_or_add_deposit(
+        &mut self,
+        deposit_reserve: &Pubkey,
+    ) -> Result<&mut ObligationCollateral, ProgramError> {
+        let index = match self.find_deposit_index(deposit_reserve) {
+            Some(index) => index,
+            None => {
+                let index = self
+                    .deposits
+                    .iter()
+                    .position(ObligationCollateral::is_empty)
+                    .ok_or(ProgramError::InvalidArgument)?;
+                self.deposits[index] = ObligationCollateral {
+                    deposit_reserve: *deposit_reserve,
+                    deposited_amount: 0,
+                };
+                index
+            }
+        };
+        Ok(&mut self.deposits[index])
+    }
+
+    pub fn find_borrow_index(&self, borrow_reserve: &Pubkey) -> Option<usize> {
+        self.borrows
+            .iter()
+            .position(|borrow| &borrow.borrow_reserve == borrow_reserve)
+    }
+
+    /// Returns the borrow slot for `borrow_reserve`, claiming a free slot if
+    /// the obligation has not borrowed from it yet.
+    pub fn find_or_add_borrow(
+        &mut self,
+        borrow_reserve: &Pubkey,
+        cumulative_borrow_rate_wads: u128,
+    ) -> Result<&mut ObligationLiquidity, ProgramError> {
+        let index = match self.find_borrow_index(borrow_reserve) {
+            Some(index) => index,
+            None => {
+                let index = self
+                    .borrows
+                    .iter()
+                    .position(ObligationLiquidity::is_empty)
+                    .ok_or(ProgramError::InvalidArgument)?;
+                self.borrows[index] = ObligationLiquidity {
+                    borrow_reserve: *borrow_reserve,
+                    cumulative_borrow_rate_wads,
+                    borrowed_amount_wads: 0,
+                };
+                index
+            }
+        };
+        Ok(&mut self.borrows[index])
+    }
+}
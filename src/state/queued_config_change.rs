@@ -0,0 +1,59 @@
+use pinocchio::pubkey::Pubkey;
+
+use crate::{
+    helper::{
+        account_init::StateDefinition,
+        utils::{DataLen, Initialized},
+    },
+    QUEUED_CONFIG_CHANGE_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// A market, reserve or manual price setting the owner has queued behind the market's
+/// governance delay. It can be executed from `executable_at` on, and the
+/// risk council may veto it until then.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct QueuedConfigChange {
+    pub discriminator: [u8; 8],
+    pub version: u64,
+    pub lending_market: Pubkey,
+    /// The reserve or manual price to update, or the lending market itself
+    /// for market settings.
+    pub target: Pubkey,
+    /// An `UpdateReserveConfigMode` for reserves, an
+    /// `UpdateLendingMarketMode` for the market, 0 for a manual price.
+    pub mode: u64,
+    pub value: [u8; 128],
+    /// Seeds the account's address; taken from the market's counter.
+    pub id: u64,
+    pub queued_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
+}
+
+impl StateDefinition for QueuedConfigChange {
+    const LEN: usize = core::mem::size_of::<Self>();
+    const SEED: &'static str = QUEUED_CONFIG_CHANGE_SEED;
+    const DISCRIMINATOR: [u8; 8] = *b"cfgqueue";
+}
+
+impl DataLen for QueuedConfigChange {
+    const LEN: usize = <Self as StateDefinition>::LEN;
+}
+
+impl Initialized for QueuedConfigChange {
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::DISCRIMINATOR
+    }
+}
+
+impl QueuedConfigChange {
+    pub fn is_market_change(&self) -> bool {
+        self.target == self.lending_market
+    }
+
+    pub fn is_executable(&self, unix_timestamp: i64) -> bool {
+        unix_timestamp >= self.executable_at
+    }
+}
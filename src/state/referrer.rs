@@ -0,0 +1,51 @@
+use pinocchio::pubkey::Pubkey;
+
+use crate::{
+    helper::{
+        account_init::StateDefinition,
+        utils::{DataLen, Initialized},
+    },
+    REFERRER_SEED,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// Borrow fees a referrer has earned on one reserve and not yet withdrawn.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ReferrerState {
+    pub discriminator: [u8; 8],
+    pub version: u64,
+    pub referrer: Pubkey,
+    pub reserve: Pubkey,
+    pub unclaimed_fees_wads: u128,
+    pub bump: u8,
+}
+
+impl StateDefinition for ReferrerState {
+    const LEN: usize = core::mem::size_of::<Self>();
+    const SEED: &'static str = REFERRER_SEED;
+    const DISCRIMINATOR: [u8; 8] = *b"referrer";
+}
+
+impl DataLen for ReferrerState {
+    const LEN: usize = <Self as StateDefinition>::LEN;
+}
+
+impl Initialized for ReferrerState {
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::DISCRIMINATOR
+    }
+}
+
+impl ReferrerState {
+    pub fn new(referrer: Pubkey, reserve: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: 0,
+            referrer,
+            reserve,
+            unclaimed_fees_wads: 0,
+            bump,
+        }
+    }
+}
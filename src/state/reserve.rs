@@ -0,0 +1,510 @@
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    error::PlendError,
+    helper::{
+        account_init::StateDefinition,
+        math::{Decimal, Rounding},
+        utils::{DataLen, Initialized},
+    },
+    oracle::{OracleConfig, PriceGuards},
+    state::BorrowRateCurve,
+    DEFAULT_AUTODELEVERAGE_RAMP_BPS_PER_DAY, DEFAULT_FLASH_LOAN_FEE_BPS,
+    DEFAULT_MAX_PRICE_AGE_SECONDS, DEFAULT_MAX_PRICE_CONFIDENCE_BPS, MAX_ELEVATION_GROUPS,
+    REFERRER_FEE_PCT_INHERIT, RESERVE_SEED, SECONDS_PER_DAY, SLOTS_PER_YEAR, WAD,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// Risk parameters set by the lending market owner.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ReserveConfig {
+    /// Share of a deposit's value that can be borrowed against.
+    pub loan_to_value_pct: u8,
+    /// Multiplier (>= 100) applied to the value of debt in this reserve.
+    pub borrow_factor_pct: u64,
+    /// Share of a deposit's value that debt may reach before the obligation
+    /// can be liquidated.
+    pub liquidation_threshold_pct: u8,
+    /// Extra collateral value, in basis points of the repaid value, paid to
+    /// liquidators seizing this reserve's collateral.
+    pub liquidation_bonus_bps: u64,
+    /// Borrow APR as a function of utilization.
+    pub borrow_rate_curve: BorrowRateCurve,
+    /// Share of borrow interest kept by the protocol instead of suppliers.
+    pub protocol_take_rate_pct: u8,
+    /// Where `RefreshReserve` reads the market price from.
+    pub oracle: OracleConfig,
+    /// Prices failing these leave the reserve's price invalid.
+    pub price_guards: PriceGuards,
+    /// Ids of the elevation groups this reserve's collateral counts in;
+    /// unused entries are 0.
+    pub elevation_groups: [u8; MAX_ELEVATION_GROUPS],
+    /// How fast the liquidation threshold falls while the reserve is being
+    /// autodeleveraged, in basis points per day.
+    pub autodeleverage_ramp_bps_per_day: u64,
+    /// Fee on flash loans, in basis points of the amount, kept by the
+    /// protocol.
+    pub flash_loan_fee_bps: u64,
+    /// Origination fee added to each borrow, in basis points of the amount,
+    /// kept by the protocol.
+    pub borrow_fee_bps: u64,
+    /// Share of a liquidator's bonus on this reserve's collateral kept by the
+    /// protocol.
+    pub protocol_liquidation_fee_pct: u8,
+    /// Share of the origination fee credited to the referrer of a borrow,
+    /// when one is given. `REFERRER_FEE_PCT_INHERIT` uses the market's
+    /// `referral_fee_bps` instead.
+    pub referrer_fee_pct: u8,
+    /// Most liquidity, in native units, suppliers may hold in the reserve.
+    /// Deposits past it are rejected.
+    pub deposit_limit: u64,
+    /// Most liquidity, in native units, that may be lent out at once.
+    pub borrow_limit: u64,
+}
+
+impl ReserveConfig {
+    /// A position may not be opened at a loan-to-value it could be
+    /// liquidated at.
+    pub fn validate_collateral_params(&self) -> Result<(), ProgramError> {
+        if self.liquidation_threshold_pct > 100
+            || self.loan_to_value_pct > self.liquidation_threshold_pct
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// Share of a borrow fee owed to its referrer, given the market's
+    /// `referral_fee_bps`.
+    pub fn referrer_share(&self, referral_fee_bps: u64) -> Decimal {
+        match self.referrer_fee_pct {
+            REFERRER_FEE_PCT_INHERIT => Decimal::from_bps(referral_fee_bps),
+            referrer_fee_pct => Decimal::from_pct(referrer_fee_pct),
+        }
+    }
+
+    pub fn is_in_elevation_group(&self, id: u8) -> bool {
+        id != 0 && self.elevation_groups.contains(&id)
+    }
+}
+
+/// Anything but `Active` stops new deposits, borrows and flash loans while
+/// letting existing positions unwind.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReserveStatus {
+    Active = 0,
+    /// Closed to new positions until set back to `Active`.
+    Frozen = 1,
+    /// Closed to new positions for good.
+    Obsolete = 2,
+}
+
+impl TryFrom<u8> for ReserveStatus {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ReserveStatus::Active),
+            1 => Ok(ReserveStatus::Frozen),
+            2 => Ok(ReserveStatus::Obsolete),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+}
+
+/// User actions that can be paused on a reserve, as bits of
+/// `ReserveState::paused_actions`.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReserveAction {
+    Deposit = 1 << 0,
+    Withdraw = 1 << 1,
+    Borrow = 1 << 2,
+    Repay = 1 << 3,
+    Liquidate = 1 << 4,
+    FlashLoan = 1 << 5,
+}
+
+impl ReserveAction {
+    /// Every action's bit.
+    pub const ALL: u8 = 0b0011_1111;
+}
+
+/// Most liquidity that may leave a reserve in each fixed window of time.
+/// Windows start at multiples of `interval_seconds`.
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct RollingCap {
+    /// Liquidity allowed per window, in native units.
+    pub max_amount: u64,
+    /// Window length. 0 turns the cap off.
+    pub interval_seconds: u64,
+    /// Unix timestamp the current window started at.
+    pub window_start: i64,
+    /// Liquidity counted against the cap in the current window.
+    pub amount_in_window: u64,
+}
+
+impl RollingCap {
+    /// Counts `amount` against the window containing `unix_timestamp`.
+    /// Returns false, without counting it, if that would exceed the cap.
+    pub fn try_consume(&mut self, amount: u64, unix_timestamp: i64) -> bool {
+        if self.interval_seconds == 0 {
+            return true;
+        }
+
+        let window_start = unix_timestamp - unix_timestamp.rem_euclid(self.interval_seconds as i64);
+        let amount_in_window = if window_start == self.window_start {
+            self.amount_in_window
+        } else {
+            0
+        };
+        match amount_in_window.checked_add(amount) {
+            Some(total) if total <= self.max_amount => {
+                self.window_start = window_start;
+                self.amount_in_window = total;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Pod, Zeroable, Debug, Clone, Copy, PartialEq)]
+pub struct ReserveState {
+    pub discriminator: [u8; 8],
+    pub version: u64,
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply_vault: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_mint_total_supply: u64,
+    /// Holds the collateral tokens locked in obligations.
+    pub collateral_supply_vault: Pubkey,
+    pub token_program: Pubkey,
+    pub available_liquidity: u64,
+    pub borrowed_amount_wads: u128,
+    pub cumulative_borrow_rate_wads: u128,
+    /// Interest and fees owed to the protocol. They sit in the vault or in
+    /// `borrowed_amount_wads` but are not part of the suppliers' share of the
+    /// reserve.
+    pub accumulated_protocol_fees_wads: u128,
+    /// Borrow fees credited to referrers and not yet withdrawn. Like protocol
+    /// fees, they are not part of the suppliers' share of the reserve.
+    pub accumulated_referrer_fees_wads: u128,
+    /// Slot interest was last accrued in.
+    pub last_update_slot: u64,
+    /// Quote-currency price of one whole liquidity token, as of the last
+    /// refresh.
+    pub market_price_wads: u128,
+    /// Set when the last refresh read a price that passed the reserve's
+    /// guards. Borrowing and withdrawing against the reserve need it.
+    pub price_valid: u8,
+    /// Set by the risk council to wind down positions backed by this
+    /// reserve's collateral.
+    pub autodeleverage: u8,
+    /// Liquidation threshold the autodeleverage ramp stops at.
+    pub autodeleverage_target_threshold_pct: u8,
+    /// Unix timestamp the autodeleverage ramp started at.
+    pub autodeleverage_started_at: i64,
+    /// Limits liquidity redeemed from the reserve per window.
+    pub withdrawal_cap: RollingCap,
+    /// Limits liquidity borrowed from the reserve per window.
+    pub borrow_cap: RollingCap,
+    /// A `ReserveStatus`.
+    pub status: u8,
+    /// `ReserveAction` bits for the actions currently paused.
+    pub paused_actions: u8,
+    pub config: ReserveConfig,
+    pub liquidity_mint_decimals: u8,
+    pub bump: u8,
+}
+
+impl StateDefinition for ReserveState {
+    const LEN: usize = core::mem::size_of::<Self>();
+    const SEED: &'static str = RESERVE_SEED;
+    const DISCRIMINATOR: [u8; 8] = *b"reserve\0";
+}
+
+impl DataLen for ReserveState {
+    const LEN: usize = <Self as StateDefinition>::LEN;
+}
+
+impl Initialized for ReserveState {
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::DISCRIMINATOR
+    }
+}
+
+impl ReserveState {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        lending_market: Pubkey,
+        liquidity_mint: Pubkey,
+        liquidity_supply_vault: Pubkey,
+        collateral_mint: Pubkey,
+        collateral_supply_vault: Pubkey,
+        token_program: Pubkey,
+        liquidity_mint_decimals: u8,
+        current_slot: u64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: 0,
+            lending_market,
+            liquidity_mint,
+            liquidity_supply_vault,
+            collateral_mint,
+            collateral_mint_total_supply: 0,
+            collateral_supply_vault,
+            token_program,
+            available_liquidity: 0,
+            borrowed_amount_wads: 0,
+            cumulative_borrow_rate_wads: WAD,
+            accumulated_protocol_fees_wads: 0,
+            accumulated_referrer_fees_wads: 0,
+            last_update_slot: current_slot,
+            market_price_wads: 0,
+            price_valid: 0,
+            autodeleverage: 0,
+            autodeleverage_target_threshold_pct: 0,
+            autodeleverage_started_at: 0,
+            withdrawal_cap: RollingCap::zeroed(),
+            borrow_cap: RollingCap::zeroed(),
+            status: ReserveStatus::Active as u8,
+            paused_actions: 0,
+            config: ReserveConfig {
+                loan_to_value_pct: 0,
+                borrow_factor_pct: 100,
+                liquidation_threshold_pct: 0,
+                liquidation_bonus_bps: 0,
+                borrow_rate_curve: BorrowRateCurve::default(),
+                protocol_take_rate_pct: 0,
+                oracle: OracleConfig::zeroed(),
+                price_guards: PriceGuards {
+                    max_age_seconds: DEFAULT_MAX_PRICE_AGE_SECONDS,
+                    max_confidence_bps: DEFAULT_MAX_PRICE_CONFIDENCE_BPS,
+                    max_twap_divergence_bps: 0,
+                },
+                elevation_groups: [0; MAX_ELEVATION_GROUPS],
+                autodeleverage_ramp_bps_per_day: DEFAULT_AUTODELEVERAGE_RAMP_BPS_PER_DAY,
+                flash_loan_fee_bps: DEFAULT_FLASH_LOAN_FEE_BPS,
+                borrow_fee_bps: 0,
+                protocol_liquidation_fee_pct: 0,
+                referrer_fee_pct: REFERRER_FEE_PCT_INHERIT,
+                deposit_limit: u64::MAX,
+                borrow_limit: u64::MAX,
+            },
+            liquidity_mint_decimals,
+            bump,
+        }
+    }
+
+    /// Liquidity owned by suppliers: what sits in the vault plus what is lent
+    /// out, less the protocol's and referrers' fees.
+    pub fn total_liquidity(&self) -> Result<Decimal, ProgramError> {
+        Decimal::from_u64(self.available_liquidity)
+            .try_add(Decimal::from_wads(self.borrowed_amount_wads))?
+            .try_sub(Decimal::from_wads(self.accumulated_protocol_fees_wads))?
+            .try_sub(Decimal::from_wads(self.accumulated_referrer_fees_wads))
+    }
+
+    /// Vault liquidity that may be lent out or withdrawn: what is not set
+    /// aside for the protocol's and referrers' fees.
+    pub fn borrowable_liquidity(&self) -> Result<u64, ProgramError> {
+        let available_liquidity = Decimal::from_u64(self.available_liquidity);
+        let fees = Decimal::from_wads(self.accumulated_protocol_fees_wads)
+            .try_add(Decimal::from_wads(self.accumulated_referrer_fees_wads))?;
+        if fees >= available_liquidity {
+            return Ok(0);
+        }
+        available_liquidity.try_sub(fees)?.to_u64(Rounding::Down)
+    }
+
+    /// Collateral tokens minted for `liquidity_amount` at the current exchange
+    /// rate, rounded down in favour of the reserve.
+    pub fn liquidity_to_collateral(&self, liquidity_amount: u64) -> Result<u64, ProgramError> {
+        let total_liquidity = self.total_liquidity()?;
+        if self.collateral_mint_total_supply == 0 || total_liquidity.is_zero() {
+            return Ok(liquidity_amount);
+        }
+        Decimal::from_u64(liquidity_amount)
+            .try_mul_u64(self.collateral_mint_total_supply)?
+            .try_div(total_liquidity, Rounding::Down)?
+            .to_u64(Rounding::Down)
+    }
+
+    /// Credits `fee` to the protocol. It must already be held by the reserve,
+    /// as available liquidity or as debt.
+    pub fn add_protocol_fee(&mut self, fee: Decimal) -> Result<(), ProgramError> {
+        self.accumulated_protocol_fees_wads =
+            Decimal::from_wads(self.accumulated_protocol_fees_wads)
+                .try_add(fee)?
+                .to_wads()?;
+        Ok(())
+    }
+
+    /// Credits `fee` to referrers, on the same terms as `add_protocol_fee`.
+    pub fn add_referrer_fee(&mut self, fee: Decimal) -> Result<(), ProgramError> {
+        self.accumulated_referrer_fees_wads =
+            Decimal::from_wads(self.accumulated_referrer_fees_wads)
+                .try_add(fee)?
+                .to_wads()?;
+        Ok(())
+    }
+
+    /// Liquidity returned for burning `collateral_amount`, rounded down in
+    /// favour of the reserve.
+    pub fn collateral_to_liquidity(&self, collateral_amount: u64) -> Result<u64, ProgramError> {
+        if self.collateral_mint_total_supply == 0 {
+            return Ok(collateral_amount);
+        }
+        self.total_liquidity()?
+            .try_mul_u64(collateral_amount)?
+            .try_div_u64(self.collateral_mint_total_supply, Rounding::Down)?
+            .to_u64(Rounding::Down)
+    }
+
+    /// Quote value of `liquidity_amount` base units of liquidity.
+    pub fn market_value(&self, liquidity_amount: Decimal) -> Result<Decimal, ProgramError> {
+        let decimals = 10u64
+            .checked_pow(self.liquidity_mint_decimals as u32)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        liquidity_amount
+            .try_mul(Decimal::from_wads(self.market_price_wads), Rounding::Down)?
+            .try_div_u64(decimals, Rounding::Down)
+    }
+
+    /// Share of total liquidity currently lent out.
+    pub fn utilization_rate(&self) -> Result<Decimal, ProgramError> {
+        let total_liquidity = self.total_liquidity()?;
+        if total_liquidity.is_zero() {
+            return Ok(Decimal::zero());
+        }
+        Decimal::from_wads(self.borrowed_amount_wads).try_div(total_liquidity, Rounding::Down)
+    }
+
+    /// Borrow APR at the current utilization.
+    pub fn current_borrow_rate(&self) -> Result<Decimal, ProgramError> {
+        let borrow_rate_curve = self.config.borrow_rate_curve;
+        borrow_rate_curve.borrow_rate(self.utilization_rate()?)
+    }
+
+    /// Supply APR: borrow interest spread over all liquidity, less the
+    /// protocol's take.
+    pub fn current_supply_rate(&self) -> Result<Decimal, ProgramError> {
+        let supplier_share =
+            Decimal::one().try_sub(Decimal::from_pct(self.config.protocol_take_rate_pct))?;
+        self.current_borrow_rate()?
+            .try_mul(self.utilization_rate()?, Rounding::Down)?
+            .try_mul(supplier_share, Rounding::Down)
+    }
+
+    pub fn is_price_valid(&self) -> bool {
+        self.price_valid != 0
+    }
+
+    /// Origination fee on a borrow of `liquidity_amount`, rounded up.
+    pub fn borrow_fee(&self, liquidity_amount: u64) -> Result<Decimal, ProgramError> {
+        Decimal::from_u64(liquidity_amount)
+            .try_mul(Decimal::from_bps(self.config.borrow_fee_bps), Rounding::Up)
+    }
+
+    /// Fee on a flash loan of `liquidity_amount`, rounded up.
+    pub fn flash_loan_fee(&self, liquidity_amount: u64) -> Result<u64, ProgramError> {
+        Decimal::from_u64(liquidity_amount)
+            .try_mul(
+                Decimal::from_bps(self.config.flash_loan_fee_bps),
+                Rounding::Up,
+            )?
+            .to_u64(Rounding::Up)
+    }
+
+    /// Rejects `action` if it is paused, or if it would open a position on
+    /// a reserve that is not active.
+    pub fn check_action(&self, action: ReserveAction) -> Result<(), ProgramError> {
+        if self.paused_actions & action as u8 != 0 {
+            return Err(PlendError::ReserveActionPaused.into());
+        }
+        let opens_position = matches!(
+            action,
+            ReserveAction::Deposit | ReserveAction::Borrow | ReserveAction::FlashLoan
+        );
+        if opens_position && self.status != ReserveStatus::Active as u8 {
+            return Err(PlendError::ReserveNotActive.into());
+        }
+        Ok(())
+    }
+
+    pub fn is_autodeleveraging(&self) -> bool {
+        self.autodeleverage != 0
+    }
+
+    /// Liquidation threshold, in basis points, that `liquidation_threshold_pct`
+    /// has ramped down to by `unix_timestamp`, or `None` when the reserve is
+    /// not being autodeleveraged. The ramp never goes below the target.
+    pub fn autodeleverage_threshold_bps(
+        &self,
+        liquidation_threshold_pct: u8,
+        unix_timestamp: i64,
+    ) -> Option<u64> {
+        if !self.is_autodeleveraging() {
+            return None;
+        }
+
+        let elapsed = unix_timestamp
+            .saturating_sub(self.autodeleverage_started_at)
+            .max(0) as u128;
+        let decrease =
+            elapsed * self.config.autodeleverage_ramp_bps_per_day as u128 / SECONDS_PER_DAY as u128;
+
+        let start = liquidation_threshold_pct as u64 * 100;
+        let target = (self.autodeleverage_target_threshold_pct as u64 * 100).min(start);
+        let ramped = start.saturating_sub(decrease.min(u64::MAX as u128) as u64);
+        Some(ramped.max(target))
+    }
+
+    /// True once interest has been accrued in `current_slot`.
+    pub fn is_fresh(&self, current_slot: u64) -> bool {
+        self.last_update_slot == current_slot
+    }
+
+    /// Compounds the borrow index and outstanding debt from `last_update_slot`
+    /// to `current_slot`, setting aside the protocol's take of the interest.
+    pub fn accrue_interest(&mut self, current_slot: u64) -> Result<(), ProgramError> {
+        let slots_elapsed = current_slot
+            .checked_sub(self.last_update_slot)
+            .ok_or(ProgramError::InvalidArgument)?;
+        if slots_elapsed == 0 {
+            return Ok(());
+        }
+
+        let slot_rate = self
+            .current_borrow_rate()?
+            .try_div_u64(SLOTS_PER_YEAR, Rounding::Down)?;
+        let compounded_interest_rate = Decimal::one().try_add(slot_rate)?.try_pow(slots_elapsed)?;
+
+        self.cumulative_borrow_rate_wads = Decimal::from_wads(self.cumulative_borrow_rate_wads)
+            .try_mul(compounded_interest_rate, Rounding::Down)?
+            .to_wads()?;
+
+        let previous_borrowed_amount = Decimal::from_wads(self.borrowed_amount_wads);
+        let borrowed_amount =
+            previous_borrowed_amount.try_mul(compounded_interest_rate, Rounding::Up)?;
+        let interest = borrowed_amount.try_sub(previous_borrowed_amount)?;
+        let protocol_fees = interest.try_mul(
+            Decimal::from_pct(self.config.protocol_take_rate_pct),
+            Rounding::Down,
+        )?;
+
+        self.borrowed_amount_wads = borrowed_amount.to_wads()?;
+        self.add_protocol_fee(protocol_fees)?;
+        self.last_update_slot = current_slot;
+
+        Ok(())
+    }
+}
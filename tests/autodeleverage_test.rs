@@ -0,0 +1,351 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use plend::{
+    instructions::update_reserve_config::UpdateReserveConfigMode, state::ReserveState, WAD,
+};
+use solana_instruction::{error::InstructionError, Instruction};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// One whole token at 6 decimals.
+const TOKEN: u64 = 1_000_000;
+const DAY: i64 = 86_400;
+
+struct Position {
+    collateral: ReserveAccounts,
+    debt: ReserveAccounts,
+    obligation: Pubkey,
+}
+
+/// A borrower with 1_000 tokens of collateral (LTV 50%, liquidation
+/// threshold 60%, ramp 10% a day) and a 400 token borrow, both priced at 1.
+fn open_position(ctx: &mut InitializedMarket) -> Position {
+    let collateral = ctx.init_reserve(6);
+    let debt = ctx.init_reserve(6);
+    for (mode, value) in [
+        (UpdateReserveConfigMode::LiquidationThresholdPct, vec![60u8]),
+        (UpdateReserveConfigMode::LoanToValuePct, vec![50u8]),
+        (
+            UpdateReserveConfigMode::LiquidationBonusBps,
+            500u64.to_le_bytes().to_vec(),
+        ),
+        (
+            UpdateReserveConfigMode::AutodeleverageRampBpsPerDay,
+            1_000u64.to_le_bytes().to_vec(),
+        ),
+    ] {
+        ctx.update_reserve_config(&collateral.reserve, mode, &value);
+    }
+    ctx.set_market_price(&collateral.reserve, WAD);
+    ctx.set_market_price(&debt.reserve, WAD);
+
+    let (lender, liquidity, lender_collateral) = ctx.create_lender(&debt, 1_000 * TOKEN);
+    let supply = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &debt,
+        &liquidity,
+        &lender_collateral,
+        1_000 * TOKEN,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], supply)
+        .unwrap();
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let source = ctx.create_token_account(
+        &collateral.liquidity_mint,
+        &borrower.pubkey(),
+        1_000 * TOKEN,
+    );
+    let destination = ctx.create_token_account(&debt.liquidity_mint, &borrower.pubkey(), 0);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &collateral,
+        &source,
+        1_000 * TOKEN,
+    );
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &debt,
+        &destination,
+        400 * TOKEN,
+    );
+    let refresh = ctx.build_refresh_obligation_instruction(&obligation, &[collateral.reserve]);
+    ctx.send_instructions(
+        vec![borrower.insecure_clone()],
+        vec![deposit, refresh, borrow],
+    )
+    .expect("opening the position should succeed");
+
+    Position {
+        collateral,
+        debt,
+        obligation,
+    }
+}
+
+fn refresh_position(ctx: &InitializedMarket, position: &Position) -> Instruction {
+    ctx.build_refresh_obligation_instruction(
+        &position.obligation,
+        &[position.collateral.reserve, position.debt.reserve],
+    )
+}
+
+/// Moves the clock forward and republishes both prices so they stay fresh.
+fn advance_days(ctx: &mut InitializedMarket, position: &Position, days: i64) {
+    ctx.advance_clock(days * DAY);
+    ctx.set_market_price(&position.collateral.reserve, WAD);
+    ctx.set_market_price(&position.debt.reserve, WAD);
+}
+
+fn start_autodeleverage(ctx: &mut InitializedMarket, reserve: &Pubkey, target_pct: u8) {
+    let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+        &ctx.risk_council.pubkey(),
+        reserve,
+        1,
+        target_pct,
+    );
+    ctx.send_instruction(vec![ctx.risk_council.insecure_clone()], instruction)
+        .expect("risk council should start autodeleveraging");
+}
+
+fn liquidate(
+    ctx: &mut InitializedMarket,
+    position: &Position,
+    autodeleverage: bool,
+) -> Result<(Pubkey, Pubkey), TransactionError> {
+    let liquidator = Keypair::new();
+    ctx.airdrop(&liquidator.pubkey(), 1_000_000_000);
+    let source = ctx.create_token_account(
+        &position.debt.liquidity_mint,
+        &liquidator.pubkey(),
+        400 * TOKEN,
+    );
+    let destination = ctx.create_token_account(
+        &position.collateral.collateral_mint,
+        &liquidator.pubkey(),
+        0,
+    );
+    let build = if autodeleverage {
+        InitializedMarket::build_liquidate_obligation_autodeleverage_instruction
+    } else {
+        InitializedMarket::build_liquidate_obligation_instruction
+    };
+    let liquidate = build(
+        ctx,
+        &liquidator.pubkey(),
+        &position.obligation,
+        &position.debt,
+        &position.collateral,
+        &source,
+        &destination,
+        400 * TOKEN,
+    );
+    ctx.send_instructions(
+        vec![liquidator.insecure_clone()],
+        vec![refresh_position(ctx, position), liquidate],
+    )
+    .map(|_| (source, destination))
+    .map_err(|err| err.err)
+}
+
+fn expect_error(result: Result<(Pubkey, Pubkey), TransactionError>, expected: InstructionError) {
+    match result {
+        Err(TransactionError::InstructionError(_, err)) if err == expected => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_autodeleverage_ramps_in_gradually() {
+    let mut ctx = initialize_lending_market();
+    let position = open_position(&mut ctx);
+    start_autodeleverage(&mut ctx, &position.collateral.reserve, 0);
+
+    // After one day the threshold is 50%: 400 of debt is still under the
+    // 500 limit.
+    advance_days(&mut ctx, &position, 1);
+    expect_error(
+        liquidate(&mut ctx, &position, true),
+        InstructionError::InvalidArgument,
+    );
+
+    // After three days it is 30%, so 400 is past the 300 limit. The regular
+    // path still sees a healthy obligation.
+    advance_days(&mut ctx, &position, 2);
+    expect_error(
+        liquidate(&mut ctx, &position, false),
+        InstructionError::InvalidArgument,
+    );
+    let (source, destination) =
+        liquidate(&mut ctx, &position, true).expect("autodeleverage liquidation should succeed");
+
+    // The usual 20% close factor and 5% bonus apply.
+    assert_eq!(ctx.token_balance(&source), 320 * TOKEN);
+    assert_eq!(ctx.token_balance(&destination), 84 * TOKEN);
+}
+
+#[test]
+fn test_autodeleverage_stops_at_target() {
+    let mut ctx = initialize_lending_market();
+    let position = open_position(&mut ctx);
+    start_autodeleverage(&mut ctx, &position.collateral.reserve, 45);
+
+    advance_days(&mut ctx, &position, 30);
+    expect_error(
+        liquidate(&mut ctx, &position, true),
+        InstructionError::InvalidArgument,
+    );
+}
+
+#[test]
+fn test_autodeleverage_requires_marked_reserve() {
+    let mut ctx = initialize_lending_market();
+    let position = open_position(&mut ctx);
+
+    advance_days(&mut ctx, &position, 30);
+    expect_error(
+        liquidate(&mut ctx, &position, true),
+        InstructionError::InvalidArgument,
+    );
+
+    start_autodeleverage(&mut ctx, &position.collateral.reserve, 0);
+    let stop = ctx.build_set_reserve_autodeleverage_instruction(
+        &ctx.fee_payer.pubkey(),
+        &position.collateral.reserve,
+        0,
+        0,
+    );
+    ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], stop)
+        .expect("market owner should stop autodeleveraging");
+    let state = ctx.reserve_state(&position.collateral.reserve);
+    assert!(!state.is_autodeleveraging());
+}
+
+#[test]
+fn test_set_reserve_autodeleverage_validation() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+
+    let unauthorized = Keypair::new();
+    ctx.airdrop(&unauthorized.pubkey(), 1_000_000_000);
+    let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+        &unauthorized.pubkey(),
+        &accounts.reserve,
+        1,
+        0,
+    );
+    let err = ctx
+        .send_instruction(vec![unauthorized.insecure_clone()], instruction)
+        .expect_err("only the owner or risk council may autodeleverage");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    // The target must be below the current threshold.
+    let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+        &ctx.risk_council.pubkey(),
+        &accounts.reserve,
+        1,
+        60,
+    );
+    let err = ctx
+        .send_instruction(vec![ctx.risk_council.insecure_clone()], instruction)
+        .expect_err("target at the current threshold should be rejected");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_threshold_decrease_waits_for_ramp() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::AutodeleverageRampBpsPerDay,
+        &1_000u64.to_le_bytes(),
+    );
+
+    let expect_rejected = |ctx: &mut InitializedMarket, threshold: u8| {
+        let instruction = ctx.build_update_reserve_config_instruction(
+            &accounts.reserve,
+            UpdateReserveConfigMode::LiquidationThresholdPct,
+            &[threshold],
+        );
+        let err = ctx
+            .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+            .expect_err("the threshold may only drop to a finished ramp's target");
+        match err.err {
+            TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+            other => panic!("unexpected error: {:?}", other),
+        }
+    };
+    expect_rejected(&mut ctx, 50);
+
+    let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+        &ctx.risk_council.pubkey(),
+        &accounts.reserve,
+        1,
+        50,
+    );
+    ctx.send_instruction(vec![ctx.risk_council.insecure_clone()], instruction)
+        .expect("starting the ramp should succeed");
+    expect_rejected(&mut ctx, 50);
+
+    ctx.advance_clock(DAY);
+    expect_rejected(&mut ctx, 40);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[50],
+    );
+    let state = ctx.reserve_state(&accounts.reserve);
+    assert_eq!(state.config.liquidation_threshold_pct, 50);
+    assert!(!state.is_autodeleveraging());
+}
+
+#[test]
+fn test_autodeleverage_threshold_ramp() {
+    let mut reserve = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    reserve.config.autodeleverage_ramp_bps_per_day = 1_000;
+    assert_eq!(reserve.autodeleverage_threshold_bps(60, 0), None);
+
+    reserve.autodeleverage = 1;
+    reserve.autodeleverage_started_at = 1_000;
+    reserve.autodeleverage_target_threshold_pct = 20;
+    assert_eq!(reserve.autodeleverage_threshold_bps(60, 1_000), Some(6_000));
+    // Before the start and half way through a day.
+    assert_eq!(reserve.autodeleverage_threshold_bps(60, 0), Some(6_000));
+    assert_eq!(
+        reserve.autodeleverage_threshold_bps(60, 1_000 + DAY / 2),
+        Some(5_500)
+    );
+    assert_eq!(
+        reserve.autodeleverage_threshold_bps(60, 1_000 + 10 * DAY),
+        Some(2_000)
+    );
+    // A threshold already under the target does not move.
+    assert_eq!(
+        reserve.autodeleverage_threshold_bps(10, 1_000 + 10 * DAY),
+        Some(1_000)
+    );
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use common::initialize_lending_market;
+use plend::{
+    helper::math::Decimal,
+    instructions::update_reserve_config::UpdateReserveConfigMode,
+    state::{BorrowRateCurve, CurvePoint, ReserveState, BORROW_RATE_CURVE_POINTS},
+    WAD,
+};
+use solana_instruction::error::InstructionError;
+use solana_transaction_error::TransactionError;
+
+/// 0% -> 4% at 80% utilization, then a steep 4% -> 100% kink to full.
+fn stablecoin_curve() -> BorrowRateCurve {
+    let mut curve = BorrowRateCurve::default();
+    curve.points[1] = CurvePoint {
+        utilization_rate_bps: 8_000,
+        borrow_rate_bps: 400,
+    };
+    for point in curve.points[2..].iter_mut() {
+        *point = CurvePoint {
+            utilization_rate_bps: 10_000,
+            borrow_rate_bps: 10_000,
+        };
+    }
+    curve
+}
+
+fn rate_at(curve: &BorrowRateCurve, utilization_rate_wads: u128) -> u128 {
+    curve
+        .borrow_rate(Decimal::from_wads(utilization_rate_wads))
+        .unwrap()
+        .to_wads()
+        .unwrap()
+}
+
+fn curve_bytes(curve: &BorrowRateCurve) -> Vec<u8> {
+    bytemuck::bytes_of(curve).to_vec()
+}
+
+#[test]
+fn test_default_curve_is_valid_and_flat() {
+    let curve = BorrowRateCurve::default();
+    curve.validate().unwrap();
+    assert_eq!(rate_at(&curve, 0), 0);
+    assert_eq!(rate_at(&curve, WAD), 0);
+}
+
+#[test]
+fn test_borrow_rate_interpolates_between_points() {
+    let curve = stablecoin_curve();
+    curve.validate().unwrap();
+
+    assert_eq!(rate_at(&curve, 0), 0);
+    assert_eq!(rate_at(&curve, WAD * 4 / 10), WAD * 2 / 100);
+    assert_eq!(rate_at(&curve, WAD * 8 / 10), WAD * 4 / 100);
+    assert_eq!(rate_at(&curve, WAD * 9 / 10), WAD * 52 / 100);
+    assert_eq!(rate_at(&curve, WAD), WAD);
+    assert_eq!(rate_at(&curve, 2 * WAD), WAD);
+}
+
+#[test]
+fn test_invalid_curves_are_rejected() {
+    let mut starts_above_zero = stablecoin_curve();
+    starts_above_zero.points[0].utilization_rate_bps = 1;
+
+    let mut decreasing_rate = stablecoin_curve();
+    decreasing_rate.points[1].borrow_rate_bps = 0;
+    decreasing_rate.points[0].borrow_rate_bps = 100;
+
+    let mut never_reaches_full = stablecoin_curve();
+    never_reaches_full.points[BORROW_RATE_CURVE_POINTS - 1] = never_reaches_full.points[1];
+
+    let mut changes_after_full = stablecoin_curve();
+    changes_after_full.points[BORROW_RATE_CURVE_POINTS - 1].borrow_rate_bps = 20_000;
+
+    for curve in [
+        starts_above_zero,
+        decreasing_rate,
+        never_reaches_full,
+        changes_after_full,
+    ] {
+        assert!(curve.validate().is_err());
+    }
+}
+
+#[test]
+fn test_supply_rate_accounts_for_utilization_and_take_rate() {
+    let mut state = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    state.config.borrow_rate_curve = stablecoin_curve();
+    state.config.protocol_take_rate_pct = 25;
+    state.available_liquidity = 600;
+    state.borrowed_amount_wads = 400 * WAD;
+
+    // 40% utilization borrows at 2%; suppliers earn 2% * 40% * 75%.
+    assert_eq!(
+        state.utilization_rate().unwrap().to_wads().unwrap(),
+        WAD * 4 / 10
+    );
+    assert_eq!(
+        state.current_borrow_rate().unwrap().to_wads().unwrap(),
+        WAD * 2 / 100
+    );
+    assert_eq!(
+        state.current_supply_rate().unwrap().to_wads().unwrap(),
+        WAD * 6 / 1_000
+    );
+}
+
+#[test]
+fn test_update_borrow_rate_curve() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let curve = stablecoin_curve();
+
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::BorrowRateCurve,
+        &curve_bytes(&curve),
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::ProtocolTakeRatePct,
+        &[10],
+    );
+
+    let state = ctx.reserve_state(&accounts.reserve);
+    assert_eq!(state.config.borrow_rate_curve, curve);
+    assert_eq!(state.config.protocol_take_rate_pct, 10);
+
+    let mut invalid = curve;
+    invalid.points[1].borrow_rate_bps = 0;
+    invalid.points[0].borrow_rate_bps = 1;
+    let instruction = ctx.build_update_reserve_config_instruction(
+        &accounts.reserve,
+        UpdateReserveConfigMode::BorrowRateCurve,
+        &curve_bytes(&invalid),
+    );
+    let err = ctx
+        .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .expect_err("an invalid curve should be rejected");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_interest_settings_require_refreshed_reserve() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.warp_to_slot(100);
+
+    for (mode, value) in [
+        (
+            UpdateReserveConfigMode::BorrowRateCurve,
+            curve_bytes(&stablecoin_curve()),
+        ),
+        (UpdateReserveConfigMode::ProtocolTakeRatePct, vec![10u8]),
+    ] {
+        let instruction =
+            ctx.build_update_reserve_config_instruction(&accounts.reserve, mode, &value);
+        let err = ctx
+            .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction.clone())
+            .expect_err("a reserve not refreshed this slot cannot change its rates");
+        match err.err {
+            TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let refresh = ctx.build_refresh_reserve_instruction(&accounts.reserve);
+        ctx.send_instructions(
+            vec![ctx.fee_payer.insecure_clone()],
+            vec![refresh, instruction],
+        )
+        .expect("updating after a refresh should succeed");
+    }
+
+    let state = ctx.reserve_state(&accounts.reserve);
+    assert_eq!(state.config.borrow_rate_curve, stablecoin_curve());
+    assert_eq!(state.config.protocol_take_rate_pct, 10);
+}
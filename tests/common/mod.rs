@@ -1,3 +1,5 @@
+#![allow(clippy::result_large_err, dead_code)]
+
 use bytemuck::try_from_bytes;
 use litesvm::{
     types::{FailedTransactionMetadata, TransactionMetadata},
@@ -5,25 +7,59 @@ use litesvm::{
 };
 use pinocchio::sysvars::rent::RENT_ID;
 use plend::{
-    helper::utils::DataLen,
+    helper::{token::TOKEN_PROGRAM_ID, utils::DataLen},
     instructions::{
-        init_lending_market::InitLendingMarketIxData, set_emergency_mode::SetEmergencyModeIxData,
-        update_lending_market_owner::UpdateLendingMarketOwnerIxData,
-        update_risk_council::UpdateRiskCouncilIxData, PlendInstructions,
+        borrow_obligation_liquidity::BorrowObligationLiquidityIxData,
+        deposit_obligation_collateral::DepositObligationCollateralIxData,
+        deposit_reserve_liquidity::DepositReserveLiquidityIxData,
+        deposit_reserve_liquidity_and_obligation_collateral::DepositReserveLiquidityAndObligationCollateralIxData,
+        flash_borrow_reserve_liquidity::FlashBorrowReserveLiquidityIxData,
+        flash_repay_reserve_liquidity::FlashRepayReserveLiquidityIxData,
+        init_lending_market::InitLendingMarketIxData,
+        init_manual_price::InitManualPriceIxData,
+        init_obligation::InitObligationIxData,
+        liquidate_obligation::LiquidateObligationIxData,
+        propose_lending_market_owner::ProposeLendingMarketOwnerIxData,
+        queue_config_change::QueueConfigChangeIxData,
+        redeem_reserve_collateral::RedeemReserveCollateralIxData,
+        repay_obligation_liquidity::RepayObligationLiquidityIxData,
+        request_elevation_group::RequestElevationGroupIxData,
+        set_emergency_mode::SetEmergencyModeIxData,
+        set_reserve_autodeleverage::SetReserveAutodeleverageIxData,
+        set_reserve_status::SetReserveStatusIxData,
+        update_elevation_group::UpdateElevationGroupIxData,
+        update_lending_market::{UpdateLendingMarketIxData, UpdateLendingMarketMode},
+        update_manual_price::UpdateManualPriceIxData,
+        update_reserve_config::{UpdateReserveConfigIxData, UpdateReserveConfigMode},
+        update_risk_council::UpdateRiskCouncilIxData,
+        withdraw_obligation_collateral::WithdrawObligationCollateralIxData,
+        PlendInstructions,
+    },
+    oracle::OracleType,
+    state::{
+        ElevationGroup, LendingMarketState, ManualPriceState, ObligationState, QueuedConfigChange,
+        ReferrerState, ReserveState, ReserveStatus,
     },
-    state::LendingMarketState,
-    ID,
+    ID, MANUAL_PRICE_SEED, OBLIGATION_SEED, QUEUED_CONFIG_CHANGE_SEED, REFERRER_SEED,
+    RESERVE_COLLATERAL_MINT_SEED, RESERVE_COLLATERAL_SUPPLY_SEED, RESERVE_LIQUIDITY_SUPPLY_SEED,
+    RESERVE_SEED,
 };
+use solana_account::Account;
+use solana_clock::Clock;
 use solana_instruction::{account_meta::AccountMeta, Instruction};
 use solana_keypair::Keypair;
 use solana_message::{v0, VersionedMessage};
 use solana_pubkey::Pubkey;
-use solana_sdk_ids::system_program;
+use solana_sdk_ids::{system_program, sysvar};
 use solana_signer::Signer;
 use solana_transaction::versioned::VersionedTransaction;
 
 const PROGRAM_ID: Pubkey = Pubkey::new_from_array(ID);
 const MARKET_SEED: &[u8] = b"lending_market";
+pub const TOKEN_PROGRAM: Pubkey = Pubkey::new_from_array(TOKEN_PROGRAM_ID);
+
+const MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
 
 pub fn serialize_struct<T>(value: &T) -> &[u8] {
     unsafe {
@@ -58,6 +94,14 @@ pub fn build_and_send_transaction(
     svm.send_transaction(tx)
 }
 
+pub struct ReserveAccounts {
+    pub reserve: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_supply: Pubkey,
+}
+
 pub struct InitializedMarket {
     pub program_id: Pubkey,
     pub market_pubkey: Pubkey,
@@ -83,6 +127,63 @@ impl InitializedMarket {
         *try_from_bytes::<LendingMarketState>(data).expect("invalid lending market account state")
     }
 
+    pub fn reserve_state(&self, reserve: &Pubkey) -> ReserveState {
+        let account = self
+            .svm
+            .get_account(reserve)
+            .expect("reserve account missing");
+        *try_from_bytes::<ReserveState>(&account.data).expect("invalid reserve account state")
+    }
+
+    pub fn obligation_state(&self, obligation: &Pubkey) -> ObligationState {
+        let account = self
+            .svm
+            .get_account(obligation)
+            .expect("obligation account missing");
+        *try_from_bytes::<ObligationState>(&account.data).expect("invalid obligation account state")
+    }
+
+    pub fn referrer_state(&self, referrer_state: &Pubkey) -> ReferrerState {
+        let account = self
+            .svm
+            .get_account(referrer_state)
+            .expect("referrer account missing");
+        *try_from_bytes::<ReferrerState>(&account.data).expect("invalid referrer account state")
+    }
+
+    pub fn queued_config_change(&self, queued_change: &Pubkey) -> QueuedConfigChange {
+        let account = self
+            .svm
+            .get_account(queued_change)
+            .expect("queued config change account missing");
+        *try_from_bytes::<QueuedConfigChange>(&account.data)
+            .expect("invalid queued config change account state")
+    }
+
+    pub fn lamports(&self, pubkey: &Pubkey) -> u64 {
+        self.svm
+            .get_account(pubkey)
+            .map(|account| account.lamports)
+            .unwrap_or_default()
+    }
+
+    pub fn minimum_balance(&self, data_len: usize) -> u64 {
+        self.svm.minimum_balance_for_rent_exemption(data_len)
+    }
+
+    pub fn account_data_len(&self, pubkey: &Pubkey) -> usize {
+        self.svm
+            .get_account(pubkey)
+            .map(|account| account.data.len())
+            .unwrap_or_default()
+    }
+
+    pub fn account_exists(&self, pubkey: &Pubkey) -> bool {
+        self.svm
+            .get_account(pubkey)
+            .is_some_and(|account| account.lamports > 0)
+    }
+
     pub fn owner_pubkey(&self) -> [u8; 32] {
         self.fee_payer.pubkey().to_bytes()
     }
@@ -100,12 +201,994 @@ impl InitializedMarket {
         build_and_send_transaction(&mut self.svm, &signer_refs, vec![instruction])
     }
 
+    pub fn send_instructions(
+        &mut self,
+        signers: Vec<Keypair>,
+        instructions: Vec<Instruction>,
+    ) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+        let signer_refs: Vec<&Keypair> = signers.iter().collect();
+        build_and_send_transaction(&mut self.svm, &signer_refs, instructions)
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.svm.warp_to_slot(slot);
+        self.svm.expire_blockhash();
+    }
+
+    pub fn unix_timestamp(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    /// Moves the clock's unix timestamp forward without changing the slot.
+    pub fn advance_clock(&mut self, seconds: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += seconds;
+        self.svm.set_sysvar(&clock);
+        self.svm.expire_blockhash();
+    }
+
+    /// Writes an initialized SPL mint directly into the bank.
+    pub fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; MINT_LEN];
+        data[44] = decimals;
+        data[45] = 1;
+        self.set_token_program_account(mint, data);
+        mint
+    }
+
+    /// Writes an initialized SPL token account holding `amount` of `mint`.
+    pub fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let token_account = Pubkey::new_unique();
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1;
+        self.set_token_program_account(token_account, data);
+        token_account
+    }
+
+    pub fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        let account = self
+            .svm
+            .get_account(token_account)
+            .expect("token account missing");
+        u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+    }
+
+    fn set_token_program_account(&mut self, pubkey: Pubkey, data: Vec<u8>) {
+        self.set_foreign_account(&pubkey, &TOKEN_PROGRAM, data);
+    }
+
+    /// Writes an account owned by another program, e.g. an oracle feed.
+    pub fn set_foreign_account(&mut self, pubkey: &Pubkey, owner: &Pubkey, data: Vec<u8>) {
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(
+                *pubkey,
+                Account {
+                    lamports,
+                    data,
+                    owner: *owner,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .expect("failed to write account");
+    }
+
+    pub fn reserve_accounts(&self, liquidity_mint: Pubkey) -> ReserveAccounts {
+        let (reserve, _) = Pubkey::find_program_address(
+            &[
+                RESERVE_SEED.as_bytes(),
+                self.market_pubkey.as_ref(),
+                liquidity_mint.as_ref(),
+            ],
+            &self.program_id,
+        );
+        let (liquidity_supply, _) = Pubkey::find_program_address(
+            &[RESERVE_LIQUIDITY_SUPPLY_SEED.as_bytes(), reserve.as_ref()],
+            &self.program_id,
+        );
+        let (collateral_mint, _) = Pubkey::find_program_address(
+            &[RESERVE_COLLATERAL_MINT_SEED.as_bytes(), reserve.as_ref()],
+            &self.program_id,
+        );
+        let (collateral_supply, _) = Pubkey::find_program_address(
+            &[RESERVE_COLLATERAL_SUPPLY_SEED.as_bytes(), reserve.as_ref()],
+            &self.program_id,
+        );
+        ReserveAccounts {
+            reserve,
+            liquidity_mint,
+            liquidity_supply,
+            collateral_mint,
+            collateral_supply,
+        }
+    }
+
+    pub fn build_init_reserve_instruction(&self, accounts: &ReserveAccounts) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.fee_payer.pubkey(), true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new_readonly(accounts.liquidity_mint, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(accounts.collateral_mint, false),
+                AccountMeta::new(accounts.collateral_supply, false),
+                AccountMeta::new_readonly(Pubkey::new_from_array(RENT_ID), false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: vec![PlendInstructions::InitReserve as u8],
+        }
+    }
+
+    /// Creates a fresh liquidity mint and lists it as a reserve on the market.
+    pub fn init_reserve(&mut self, decimals: u8) -> ReserveAccounts {
+        let liquidity_mint = self.create_mint(decimals);
+        let accounts = self.reserve_accounts(liquidity_mint);
+        let instruction = self.build_init_reserve_instruction(&accounts);
+        self.send_instruction(vec![self.fee_payer.insecure_clone()], instruction)
+            .expect("reserve initialization failed");
+        accounts
+    }
+
+    /// Passes the price feeds currently configured on `reserve`.
+    pub fn build_refresh_reserve_instruction(&self, reserve: &Pubkey) -> Instruction {
+        let sources = self.reserve_state(reserve).config.oracle.sources;
+        let mut metas = vec![AccountMeta::new(*reserve, false)];
+        metas.extend(
+            sources
+                .iter()
+                .filter(|source| !source.is_empty())
+                .map(|source| {
+                    AccountMeta::new_readonly(Pubkey::new_from_array(source.feed), false)
+                }),
+        );
+        Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data: vec![PlendInstructions::RefreshReserve as u8],
+        }
+    }
+
+    pub fn set_oracle_source(
+        &mut self,
+        reserve: &Pubkey,
+        index: u8,
+        oracle_type: OracleType,
+        feed: &Pubkey,
+    ) {
+        let mut value = vec![index, oracle_type as u8];
+        value.extend_from_slice(feed.as_ref());
+        self.update_reserve_config(reserve, UpdateReserveConfigMode::OracleSource, &value);
+    }
+
+    pub fn refresh_reserve(&mut self, reserve: &Pubkey) {
+        let instruction = self.build_refresh_reserve_instruction(reserve);
+        self.send_instruction(vec![self.fee_payer.insecure_clone()], instruction)
+            .expect("reserve refresh failed");
+    }
+
+    pub fn manual_price_pubkey(&self, mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                MANUAL_PRICE_SEED.as_bytes(),
+                self.market_pubkey.as_ref(),
+                mint.as_ref(),
+            ],
+            &self.program_id,
+        )
+        .0
+    }
+
+    pub fn manual_price_state(&self, manual_price: &Pubkey) -> ManualPriceState {
+        let account = self
+            .svm
+            .get_account(manual_price)
+            .expect("manual price account missing");
+        *try_from_bytes::<ManualPriceState>(&account.data).expect("invalid manual price data")
+    }
+
+    pub fn build_init_manual_price_instruction(
+        &self,
+        mint: &Pubkey,
+        price_wads: u128,
+    ) -> Instruction {
+        let ix_data = InitManualPriceIxData { price_wads };
+        let mut data = Vec::with_capacity(1 + InitManualPriceIxData::LEN);
+        data.push(PlendInstructions::InitManualPrice as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.fee_payer.pubkey(), true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(self.manual_price_pubkey(mint), false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(Pubkey::new_from_array(RENT_ID), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_update_manual_price_instruction(
+        &self,
+        manual_price: &Pubkey,
+        price_wads: u128,
+    ) -> Instruction {
+        let ix_data = UpdateManualPriceIxData { price_wads };
+        let mut data = Vec::with_capacity(1 + UpdateManualPriceIxData::LEN);
+        data.push(PlendInstructions::UpdateManualPrice as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.fee_payer.pubkey(), true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*manual_price, false),
+            ],
+            data,
+        }
+    }
+
+    /// Prices `reserve` from a manual price account, creating it and making it
+    /// the reserve's first oracle source, then refreshes the reserve.
+    pub fn set_market_price(&mut self, reserve: &Pubkey, price_wads: u128) {
+        let mint = Pubkey::new_from_array(self.reserve_state(reserve).liquidity_mint);
+        let manual_price = self.manual_price_pubkey(&mint);
+        let instruction = if self.account_exists(&manual_price) {
+            self.build_update_manual_price_instruction(&manual_price, price_wads)
+        } else {
+            self.build_init_manual_price_instruction(&mint, price_wads)
+        };
+        self.send_instruction(vec![self.fee_payer.insecure_clone()], instruction)
+            .expect("manual price update failed");
+
+        self.set_oracle_source(reserve, 0, OracleType::Manual, &manual_price);
+        self.refresh_reserve(reserve);
+    }
+
+    /// `reserves` lists the reserve of every used deposit slot, then of every
+    /// used borrow slot.
+    pub fn build_refresh_obligation_instruction(
+        &self,
+        obligation: &Pubkey,
+        reserves: &[Pubkey],
+    ) -> Instruction {
+        let mut metas = vec![
+            AccountMeta::new(*obligation, false),
+            AccountMeta::new_readonly(self.market_pubkey, false),
+        ];
+        metas.extend(
+            reserves
+                .iter()
+                .map(|reserve| AccountMeta::new_readonly(*reserve, false)),
+        );
+        Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data: vec![PlendInstructions::RefreshObligation as u8],
+        }
+    }
+
+    pub fn build_request_elevation_group_instruction(
+        &self,
+        owner: &Pubkey,
+        obligation: &Pubkey,
+        elevation_group: u8,
+        reserves: &[Pubkey],
+    ) -> Instruction {
+        let ix_data = RequestElevationGroupIxData { elevation_group };
+        let mut data = Vec::with_capacity(1 + RequestElevationGroupIxData::LEN);
+        data.push(PlendInstructions::RequestElevationGroup as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        let mut metas = vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(self.market_pubkey, false),
+            AccountMeta::new(*obligation, false),
+        ];
+        metas.extend(
+            reserves
+                .iter()
+                .map(|reserve| AccountMeta::new_readonly(*reserve, false)),
+        );
+        Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data,
+        }
+    }
+
+    pub fn build_deposit_reserve_liquidity_instruction(
+        &self,
+        user: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_source_liquidity: &Pubkey,
+        user_destination_collateral: &Pubkey,
+        liquidity_amount: u64,
+    ) -> Instruction {
+        let ix_data = DepositReserveLiquidityIxData { liquidity_amount };
+        let mut data = Vec::with_capacity(1 + DepositReserveLiquidityIxData::LEN);
+        data.push(PlendInstructions::DepositReserveLiquidity as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*user, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(accounts.collateral_mint, false),
+                AccountMeta::new(*user_source_liquidity, false),
+                AccountMeta::new(*user_destination_collateral, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_redeem_reserve_collateral_instruction(
+        &self,
+        user: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_source_collateral: &Pubkey,
+        user_destination_liquidity: &Pubkey,
+        collateral_amount: u64,
+    ) -> Instruction {
+        let ix_data = RedeemReserveCollateralIxData { collateral_amount };
+        let mut data = Vec::with_capacity(1 + RedeemReserveCollateralIxData::LEN);
+        data.push(PlendInstructions::RedeemReserveCollateral as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*user, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(accounts.collateral_mint, false),
+                AccountMeta::new(*user_source_collateral, false),
+                AccountMeta::new(*user_destination_liquidity, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_flash_borrow_reserve_liquidity_instruction(
+        &self,
+        user: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_destination_liquidity: &Pubkey,
+        liquidity_amount: u64,
+    ) -> Instruction {
+        let ix_data = FlashBorrowReserveLiquidityIxData { liquidity_amount };
+        let mut data = Vec::with_capacity(1 + FlashBorrowReserveLiquidityIxData::LEN);
+        data.push(PlendInstructions::FlashBorrowReserveLiquidity as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*user, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(*user_destination_liquidity, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_flash_repay_reserve_liquidity_instruction(
+        &self,
+        user: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_source_liquidity: &Pubkey,
+        liquidity_amount: u64,
+        borrow_instruction_index: u8,
+    ) -> Instruction {
+        let ix_data = FlashRepayReserveLiquidityIxData {
+            liquidity_amount,
+            borrow_instruction_index,
+        };
+        let mut data = Vec::with_capacity(1 + FlashRepayReserveLiquidityIxData::LEN);
+        data.push(PlendInstructions::FlashRepayReserveLiquidity as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*user, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(*user_source_liquidity, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_redeem_fees_instruction(
+        &self,
+        accounts: &ReserveAccounts,
+        fee_receiver: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(*fee_receiver, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data: vec![PlendInstructions::RedeemFees as u8],
+        }
+    }
+
+    pub fn referrer_state_pubkey(&self, referrer: &Pubkey, reserve: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                REFERRER_SEED.as_bytes(),
+                referrer.as_ref(),
+                reserve.as_ref(),
+            ],
+            &self.program_id,
+        )
+        .0
+    }
+
+    pub fn build_init_referrer_state_instruction(
+        &self,
+        referrer: &Pubkey,
+        reserve: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(*referrer, true),
+                AccountMeta::new_readonly(*reserve, false),
+                AccountMeta::new(self.referrer_state_pubkey(referrer, reserve), false),
+                AccountMeta::new_readonly(Pubkey::new_from_array(RENT_ID), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: vec![PlendInstructions::InitReferrerState as u8],
+        }
+    }
+
+    pub fn build_withdraw_referrer_fees_instruction(
+        &self,
+        referrer: &Pubkey,
+        accounts: &ReserveAccounts,
+        referrer_destination_liquidity: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*referrer, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(
+                    self.referrer_state_pubkey(referrer, &accounts.reserve),
+                    false,
+                ),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(*referrer_destination_liquidity, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data: vec![PlendInstructions::WithdrawReferrerFees as u8],
+        }
+    }
+
+    /// Funds a new lender with `amount` liquidity and returns their
+    /// (keypair, liquidity account, collateral account).
+    pub fn create_lender(
+        &mut self,
+        accounts: &ReserveAccounts,
+        amount: u64,
+    ) -> (Keypair, Pubkey, Pubkey) {
+        let lender = Keypair::new();
+        self.airdrop(&lender.pubkey(), 1_000_000_000);
+        let liquidity =
+            self.create_token_account(&accounts.liquidity_mint, &lender.pubkey(), amount);
+        let collateral = self.create_token_account(&accounts.collateral_mint, &lender.pubkey(), 0);
+        (lender, liquidity, collateral)
+    }
+
+    pub fn obligation_pubkey(&self, owner: &Pubkey, id: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                OBLIGATION_SEED.as_bytes(),
+                self.market_pubkey.as_ref(),
+                owner.as_ref(),
+                &id.to_le_bytes(),
+            ],
+            &self.program_id,
+        )
+        .0
+    }
+
+    pub fn build_init_obligation_instruction(&self, owner: &Pubkey, id: u64) -> Instruction {
+        let ix_data = InitObligationIxData { id };
+        let mut data = Vec::with_capacity(1 + InitObligationIxData::LEN);
+        data.push(PlendInstructions::InitObligation as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(*owner, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(self.obligation_pubkey(owner, id), false),
+                AccountMeta::new_readonly(Pubkey::new_from_array(RENT_ID), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_close_obligation_instruction(
+        &self,
+        owner: &Pubkey,
+        obligation: &Pubkey,
+        destination: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(*obligation, false),
+                AccountMeta::new(*destination, false),
+            ],
+            data: vec![PlendInstructions::CloseObligation as u8],
+        }
+    }
+
+    /// Funds a new borrower and opens obligation `id` for them.
+    pub fn create_borrower(&mut self, id: u64) -> (Keypair, Pubkey) {
+        let borrower = Keypair::new();
+        self.airdrop(&borrower.pubkey(), 1_000_000_000);
+        let instruction = self.build_init_obligation_instruction(&borrower.pubkey(), id);
+        self.send_instruction(vec![borrower.insecure_clone()], instruction)
+            .expect("obligation initialization failed");
+        let obligation = self.obligation_pubkey(&borrower.pubkey(), id);
+        (borrower, obligation)
+    }
+
+    pub fn build_update_reserve_config_instruction(
+        &self,
+        reserve: &Pubkey,
+        mode: UpdateReserveConfigMode,
+        value: &[u8],
+    ) -> Instruction {
+        let mut ix_data = UpdateReserveConfigIxData {
+            mode: mode as u64,
+            value: [0u8; 128],
+        };
+        ix_data.value[..value.len()].copy_from_slice(value);
+        let mut data = Vec::with_capacity(1 + UpdateReserveConfigIxData::LEN);
+        data.push(PlendInstructions::UpdateReserveConfig as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.fee_payer.pubkey(), true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*reserve, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn update_reserve_config(
+        &mut self,
+        reserve: &Pubkey,
+        mode: UpdateReserveConfigMode,
+        value: &[u8],
+    ) {
+        let instruction = self.build_update_reserve_config_instruction(reserve, mode, value);
+        self.send_instruction(vec![self.fee_payer.insecure_clone()], instruction)
+            .expect("reserve config update failed");
+    }
+
+    pub fn build_update_lending_market_instruction(
+        &self,
+        mode: UpdateLendingMarketMode,
+        value: &[u8],
+    ) -> Instruction {
+        let mut ix_data = UpdateLendingMarketIxData {
+            mode: mode as u64,
+            value: [0u8; 72],
+        };
+        ix_data.value[..value.len()].copy_from_slice(value);
+        let mut data = Vec::with_capacity(1 + UpdateLendingMarketIxData::LEN);
+        data.push(PlendInstructions::UpdateLendingMarket as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.fee_payer.pubkey(), true),
+                AccountMeta::new(self.market_pubkey, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn update_lending_market(&mut self, mode: UpdateLendingMarketMode, value: &[u8]) {
+        let instruction = self.build_update_lending_market_instruction(mode, value);
+        self.send_instruction(vec![self.fee_payer.insecure_clone()], instruction)
+            .expect("lending market update failed");
+    }
+
+    pub fn queued_config_change_pubkey(&self, id: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                QUEUED_CONFIG_CHANGE_SEED.as_bytes(),
+                self.market_pubkey.as_ref(),
+                &id.to_le_bytes(),
+            ],
+            &self.program_id,
+        )
+        .0
+    }
+
+    /// Queues `mode` for `target`, a reserve or the market itself, under the
+    /// market's next queued change id, and returns that change's address.
+    pub fn build_queue_config_change_instruction(
+        &self,
+        target: &Pubkey,
+        mode: u64,
+        value: &[u8],
+    ) -> (Instruction, Pubkey) {
+        let mut ix_data = QueueConfigChangeIxData {
+            mode,
+            value: [0u8; 128],
+        };
+        ix_data.value[..value.len()].copy_from_slice(value);
+        let mut data = Vec::with_capacity(1 + QueueConfigChangeIxData::LEN);
+        data.push(PlendInstructions::QueueConfigChange as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        let queued_change =
+            self.queued_config_change_pubkey(self.market_state().queued_config_change_count);
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.fee_payer.pubkey(), true),
+                AccountMeta::new(self.market_pubkey, false),
+                AccountMeta::new(*target, false),
+                AccountMeta::new(queued_change, false),
+                AccountMeta::new_readonly(Pubkey::new_from_array(RENT_ID), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data,
+        };
+        (instruction, queued_change)
+    }
+
+    pub fn build_execute_config_change_instruction(
+        &self,
+        target: &Pubkey,
+        queued_change: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.fee_payer.pubkey(), true),
+                AccountMeta::new(self.market_pubkey, false),
+                AccountMeta::new(*target, false),
+                AccountMeta::new(*queued_change, false),
+            ],
+            data: vec![PlendInstructions::ExecuteConfigChange as u8],
+        }
+    }
+
+    pub fn build_veto_config_change_instruction(
+        &self,
+        authority: &Pubkey,
+        queued_change: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*queued_change, false),
+                AccountMeta::new(self.fee_payer.pubkey(), false),
+            ],
+            data: vec![PlendInstructions::VetoConfigChange as u8],
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_borrow_obligation_liquidity_instruction(
+        &self,
+        owner: &Pubkey,
+        obligation: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_destination_liquidity: &Pubkey,
+        liquidity_amount: u64,
+    ) -> Instruction {
+        let ix_data = BorrowObligationLiquidityIxData { liquidity_amount };
+        let mut data = Vec::with_capacity(1 + BorrowObligationLiquidityIxData::LEN);
+        data.push(PlendInstructions::BorrowObligationLiquidity as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        let metas = vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(self.market_pubkey, false),
+            AccountMeta::new(*obligation, false),
+            AccountMeta::new(accounts.reserve, false),
+            AccountMeta::new(accounts.liquidity_supply, false),
+            AccountMeta::new(*user_destination_liquidity, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+        ];
+        Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data,
+        }
+    }
+
+    pub fn build_repay_obligation_liquidity_instruction(
+        &self,
+        repayer: &Pubkey,
+        obligation: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_source_liquidity: &Pubkey,
+        liquidity_amount: u64,
+    ) -> Instruction {
+        let ix_data = RepayObligationLiquidityIxData { liquidity_amount };
+        let mut data = Vec::with_capacity(1 + RepayObligationLiquidityIxData::LEN);
+        data.push(PlendInstructions::RepayObligationLiquidity as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*repayer, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*obligation, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(*user_source_liquidity, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_deposit_obligation_collateral_instruction(
+        &self,
+        owner: &Pubkey,
+        obligation: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_source_collateral: &Pubkey,
+        collateral_amount: u64,
+    ) -> Instruction {
+        let ix_data = DepositObligationCollateralIxData { collateral_amount };
+        let mut data = Vec::with_capacity(1 + DepositObligationCollateralIxData::LEN);
+        data.push(PlendInstructions::DepositObligationCollateral as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*obligation, false),
+                AccountMeta::new_readonly(accounts.reserve, false),
+                AccountMeta::new(accounts.collateral_supply, false),
+                AccountMeta::new(*user_source_collateral, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_withdraw_obligation_collateral_instruction(
+        &self,
+        owner: &Pubkey,
+        obligation: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_destination_collateral: &Pubkey,
+        collateral_amount: u64,
+    ) -> Instruction {
+        let ix_data = WithdrawObligationCollateralIxData { collateral_amount };
+        let mut data = Vec::with_capacity(1 + WithdrawObligationCollateralIxData::LEN);
+        data.push(PlendInstructions::WithdrawObligationCollateral as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        let metas = vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(self.market_pubkey, false),
+            AccountMeta::new(*obligation, false),
+            AccountMeta::new_readonly(accounts.reserve, false),
+            AccountMeta::new(accounts.collateral_supply, false),
+            AccountMeta::new(*user_destination_collateral, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+        ];
+        Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data,
+        }
+    }
+
+    pub fn build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &self,
+        owner: &Pubkey,
+        obligation: &Pubkey,
+        accounts: &ReserveAccounts,
+        user_source_liquidity: &Pubkey,
+        liquidity_amount: u64,
+    ) -> Instruction {
+        let ix_data = DepositReserveLiquidityAndObligationCollateralIxData { liquidity_amount };
+        let mut data =
+            Vec::with_capacity(1 + DepositReserveLiquidityAndObligationCollateralIxData::LEN);
+        data.push(PlendInstructions::DepositReserveLiquidityAndObligationCollateral as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*obligation, false),
+                AccountMeta::new(accounts.reserve, false),
+                AccountMeta::new(accounts.liquidity_supply, false),
+                AccountMeta::new(accounts.collateral_mint, false),
+                AccountMeta::new(accounts.collateral_supply, false),
+                AccountMeta::new(*user_source_liquidity, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_liquidate_obligation_instruction(
+        &self,
+        liquidator: &Pubkey,
+        obligation: &Pubkey,
+        repay_accounts: &ReserveAccounts,
+        withdraw_accounts: &ReserveAccounts,
+        liquidator_source_liquidity: &Pubkey,
+        liquidator_destination_collateral: &Pubkey,
+        liquidity_amount: u64,
+    ) -> Instruction {
+        let ix_data = LiquidateObligationIxData { liquidity_amount };
+        let mut data = Vec::with_capacity(1 + LiquidateObligationIxData::LEN);
+        data.push(PlendInstructions::LiquidateObligation as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        let metas = vec![
+            AccountMeta::new_readonly(*liquidator, true),
+            AccountMeta::new_readonly(self.market_pubkey, false),
+            AccountMeta::new(*obligation, false),
+            AccountMeta::new(repay_accounts.reserve, false),
+            AccountMeta::new(repay_accounts.liquidity_supply, false),
+            AccountMeta::new(withdraw_accounts.reserve, false),
+            AccountMeta::new(withdraw_accounts.collateral_supply, false),
+            AccountMeta::new(withdraw_accounts.collateral_mint, false),
+            AccountMeta::new(*liquidator_source_liquidity, false),
+            AccountMeta::new(*liquidator_destination_collateral, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+        ];
+        Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data,
+        }
+    }
+
+    /// Same accounts and data as `LiquidateObligation`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_liquidate_obligation_autodeleverage_instruction(
+        &self,
+        liquidator: &Pubkey,
+        obligation: &Pubkey,
+        repay_accounts: &ReserveAccounts,
+        withdraw_accounts: &ReserveAccounts,
+        liquidator_source_liquidity: &Pubkey,
+        liquidator_destination_collateral: &Pubkey,
+        liquidity_amount: u64,
+    ) -> Instruction {
+        let mut instruction = self.build_liquidate_obligation_instruction(
+            liquidator,
+            obligation,
+            repay_accounts,
+            withdraw_accounts,
+            liquidator_source_liquidity,
+            liquidator_destination_collateral,
+            liquidity_amount,
+        );
+        instruction.data[0] = PlendInstructions::LiquidateObligationAutodeleverage as u8;
+        instruction
+    }
+
+    pub fn build_set_reserve_autodeleverage_instruction(
+        &self,
+        authority: &Pubkey,
+        reserve: &Pubkey,
+        enable: u8,
+        target_liquidation_threshold_pct: u8,
+    ) -> Instruction {
+        let ix_data = SetReserveAutodeleverageIxData {
+            enable,
+            target_liquidation_threshold_pct,
+        };
+        let mut data = Vec::with_capacity(1 + SetReserveAutodeleverageIxData::LEN);
+        data.push(PlendInstructions::SetReserveAutodeleverage as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*reserve, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn build_set_reserve_status_instruction(
+        &self,
+        authority: &Pubkey,
+        reserve: &Pubkey,
+        status: ReserveStatus,
+        paused_actions: u8,
+    ) -> Instruction {
+        let ix_data = SetReserveStatusIxData {
+            status: status as u8,
+            paused_actions,
+        };
+        let mut data = Vec::with_capacity(1 + SetReserveStatusIxData::LEN);
+        data.push(PlendInstructions::SetReserveStatus as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new_readonly(self.market_pubkey, false),
+                AccountMeta::new(*reserve, false),
+            ],
+            data,
+        }
+    }
+
     pub fn airdrop(&mut self, recipient: &Pubkey, lamports: u64) {
         self.svm
             .airdrop(recipient, lamports)
             .expect("airdrop failed unexpectedly");
     }
 
+    pub fn build_update_elevation_group_instruction(
+        &self,
+        elevation_group: ElevationGroup,
+    ) -> Instruction {
+        let ix_data = UpdateElevationGroupIxData { elevation_group };
+        let mut data = Vec::with_capacity(1 + UpdateElevationGroupIxData::LEN);
+        data.push(PlendInstructions::UpdateElevationGroup as u8);
+        data.extend_from_slice(serialize_struct(&ix_data));
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.fee_payer.pubkey(), true),
+                AccountMeta::new(self.market_pubkey, false),
+            ],
+            data,
+        }
+    }
+
+    pub fn update_elevation_group(&mut self, elevation_group: ElevationGroup) {
+        let instruction = self.build_update_elevation_group_instruction(elevation_group);
+        self.send_instruction(vec![self.fee_payer.insecure_clone()], instruction)
+            .expect("elevation group update failed");
+    }
+
     pub fn build_set_emergency_mode_instruction(&self, enable: u8) -> Instruction {
         let ix_data = SetEmergencyModeIxData { enable };
         let mut data = Vec::with_capacity(1 + SetEmergencyModeIxData::LEN);
@@ -138,10 +1221,10 @@ impl InitializedMarket {
         }
     }
 
-    pub fn build_update_owner_instruction(&self, new_owner: [u8; 32]) -> Instruction {
-        let ix_data = UpdateLendingMarketOwnerIxData { new_owner };
-        let mut data = Vec::with_capacity(1 + UpdateLendingMarketOwnerIxData::LEN);
-        data.push(PlendInstructions::UpdateLendingMarketOwner as u8);
+    pub fn build_propose_owner_instruction(&self, new_owner: [u8; 32]) -> Instruction {
+        let ix_data = ProposeLendingMarketOwnerIxData { new_owner };
+        let mut data = Vec::with_capacity(1 + ProposeLendingMarketOwnerIxData::LEN);
+        data.push(PlendInstructions::ProposeLendingMarketOwner as u8);
         data.extend_from_slice(serialize_struct(&ix_data));
         Instruction {
             program_id: self.program_id,
@@ -152,6 +1235,46 @@ impl InitializedMarket {
             data,
         }
     }
+
+    /// `AcceptLendingMarketOwner` or `AcceptRiskCouncil` signed by `signer`.
+    pub fn build_accept_instruction(
+        &self,
+        instruction: PlendInstructions,
+        signer: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(*signer, true),
+                AccountMeta::new(self.market_pubkey, false),
+            ],
+            data: vec![instruction as u8],
+        }
+    }
+
+    pub fn build_migrate_lending_market_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.fee_payer.pubkey(), true),
+                AccountMeta::new(self.market_pubkey, false),
+                AccountMeta::new_readonly(Pubkey::new_from_array(RENT_ID), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: vec![PlendInstructions::MigrateLendingMarket as u8],
+        }
+    }
+
+    pub fn build_cancel_owner_proposal_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.fee_payer.pubkey(), true),
+                AccountMeta::new(self.market_pubkey, false),
+            ],
+            data: vec![PlendInstructions::CancelOwnerProposal as u8],
+        }
+    }
 }
 
 pub fn initialize_lending_market() -> InitializedMarket {
@@ -0,0 +1,298 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use litesvm::types::{FailedTransactionMetadata, TransactionMetadata};
+use plend::{
+    instructions::update_reserve_config::UpdateReserveConfigMode, state::ElevationGroup, WAD,
+};
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// One whole token at 6 decimals.
+const TOKEN: u64 = 1_000_000;
+
+struct Setup {
+    collateral: ReserveAccounts,
+    debt: ReserveAccounts,
+    borrower: Keypair,
+    obligation: Pubkey,
+}
+
+fn elevation_group(debt_reserve: &Pubkey) -> ElevationGroup {
+    ElevationGroup {
+        id: 1,
+        loan_to_value_pct: 90,
+        liquidation_threshold_pct: 95,
+        liquidation_bonus_bps: 100,
+        debt_reserve: debt_reserve.to_bytes(),
+    }
+}
+
+/// A collateral reserve at 50% LTV that belongs to group 1, a debt reserve
+/// holding 1_000 tokens that group 1 borrows from, and a borrower who has
+/// deposited 1_000 collateral tokens. Both are priced at 1.
+fn setup(ctx: &mut InitializedMarket) -> Setup {
+    let collateral = ctx.init_reserve(6);
+    let debt = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::ElevationGroups,
+        &[1],
+    );
+    ctx.set_market_price(&collateral.reserve, WAD);
+    ctx.set_market_price(&debt.reserve, WAD);
+    ctx.update_elevation_group(elevation_group(&debt.reserve));
+
+    let (lender, liquidity, lender_collateral) = ctx.create_lender(&debt, 1_000 * TOKEN);
+    let supply = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &debt,
+        &liquidity,
+        &lender_collateral,
+        1_000 * TOKEN,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], supply)
+        .unwrap();
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let source = ctx.create_token_account(
+        &collateral.liquidity_mint,
+        &borrower.pubkey(),
+        1_000 * TOKEN,
+    );
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &collateral,
+        &source,
+        1_000 * TOKEN,
+    );
+    ctx.send_instruction(vec![borrower.insecure_clone()], deposit)
+        .expect("deposit should succeed");
+
+    Setup {
+        collateral,
+        debt,
+        borrower,
+        obligation,
+    }
+}
+
+fn expect_invalid_argument(result: Result<TransactionMetadata, FailedTransactionMetadata>) {
+    match result.expect_err("instruction should fail").err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_elevation_group_raises_borrow_limit() {
+    let mut ctx = initialize_lending_market();
+    let setup = setup(&mut ctx);
+    let destination =
+        ctx.create_token_account(&setup.debt.liquidity_mint, &setup.borrower.pubkey(), 0);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        &setup.debt,
+        &destination,
+        800 * TOKEN,
+    );
+    let refresh =
+        ctx.build_refresh_obligation_instruction(&setup.obligation, &[setup.collateral.reserve]);
+
+    // 800 is past the collateral reserve's own 50% LTV.
+    expect_invalid_argument(ctx.send_instructions(
+        vec![setup.borrower.insecure_clone()],
+        vec![refresh.clone(), borrow.clone()],
+    ));
+
+    let request = ctx.build_request_elevation_group_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        1,
+        &[setup.collateral.reserve],
+    );
+    ctx.send_instructions(
+        vec![setup.borrower.insecure_clone()],
+        vec![request, refresh, borrow],
+    )
+    .expect("group 1 allows borrowing up to 90%");
+
+    let state = ctx.obligation_state(&setup.obligation);
+    assert_eq!(state.elevation_group, 1);
+    assert_eq!(ctx.token_balance(&destination), 800 * TOKEN);
+}
+
+#[test]
+fn test_elevation_group_restricts_borrow_reserve() {
+    let mut ctx = initialize_lending_market();
+    let setup = setup(&mut ctx);
+    let other = ctx.init_reserve(6);
+    ctx.set_market_price(&other.reserve, WAD);
+
+    let request = ctx.build_request_elevation_group_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        1,
+        &[setup.collateral.reserve],
+    );
+    ctx.send_instruction(vec![setup.borrower.insecure_clone()], request)
+        .expect("request should succeed");
+
+    let destination = ctx.create_token_account(&other.liquidity_mint, &setup.borrower.pubkey(), 0);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        &other,
+        &destination,
+        TOKEN,
+    );
+    let refresh =
+        ctx.build_refresh_obligation_instruction(&setup.obligation, &[setup.collateral.reserve]);
+    expect_invalid_argument(
+        ctx.send_instructions(vec![setup.borrower.insecure_clone()], vec![refresh, borrow]),
+    );
+}
+
+#[test]
+fn test_request_elevation_group_rejects_incompatible_positions() {
+    let mut ctx = initialize_lending_market();
+    let setup = setup(&mut ctx);
+
+    // A deposit from a reserve outside the group.
+    let outsider = ctx.init_reserve(6);
+    ctx.set_market_price(&outsider.reserve, WAD);
+    let source =
+        ctx.create_token_account(&outsider.liquidity_mint, &setup.borrower.pubkey(), TOKEN);
+    let refresh =
+        ctx.build_refresh_obligation_instruction(&setup.obligation, &[setup.collateral.reserve]);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        &outsider,
+        &source,
+        TOKEN,
+    );
+    ctx.send_instructions(
+        vec![setup.borrower.insecure_clone()],
+        vec![refresh, deposit],
+    )
+    .expect("deposit should succeed");
+
+    let request = ctx.build_request_elevation_group_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        1,
+        &[setup.collateral.reserve, outsider.reserve],
+    );
+    expect_invalid_argument(ctx.send_instruction(vec![setup.borrower.insecure_clone()], request));
+
+    // Groups that were never configured cannot be requested either.
+    let request = ctx.build_request_elevation_group_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        2,
+        &[setup.collateral.reserve, outsider.reserve],
+    );
+    expect_invalid_argument(ctx.send_instruction(vec![setup.borrower.insecure_clone()], request));
+}
+
+#[test]
+fn test_request_elevation_group_rejects_foreign_debt() {
+    let mut ctx = initialize_lending_market();
+    let setup = setup(&mut ctx);
+
+    let destination =
+        ctx.create_token_account(&setup.debt.liquidity_mint, &setup.borrower.pubkey(), 0);
+    let refresh =
+        ctx.build_refresh_obligation_instruction(&setup.obligation, &[setup.collateral.reserve]);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        &setup.debt,
+        &destination,
+        100 * TOKEN,
+    );
+    ctx.send_instructions(vec![setup.borrower.insecure_clone()], vec![refresh, borrow])
+        .expect("borrow within LTV should succeed");
+
+    // Group 1 now borrows from a different reserve than the one the
+    // borrower owes.
+    let other = ctx.init_reserve(6);
+    ctx.update_elevation_group(elevation_group(&other.reserve));
+
+    let request = ctx.build_request_elevation_group_instruction(
+        &setup.borrower.pubkey(),
+        &setup.obligation,
+        1,
+        &[setup.collateral.reserve, setup.debt.reserve],
+    );
+    expect_invalid_argument(ctx.send_instruction(vec![setup.borrower.insecure_clone()], request));
+}
+
+#[test]
+fn test_update_elevation_group_rejects_invalid_values() {
+    let mut ctx = initialize_lending_market();
+    let debt_reserve = Pubkey::new_unique();
+
+    for group in [
+        ElevationGroup {
+            id: 0,
+            ..elevation_group(&debt_reserve)
+        },
+        ElevationGroup {
+            id: 9,
+            ..elevation_group(&debt_reserve)
+        },
+        ElevationGroup {
+            loan_to_value_pct: 96,
+            ..elevation_group(&debt_reserve)
+        },
+        ElevationGroup {
+            liquidation_threshold_pct: 101,
+            ..elevation_group(&debt_reserve)
+        },
+        ElevationGroup {
+            liquidation_bonus_bps: 5_001,
+            ..elevation_group(&debt_reserve)
+        },
+    ] {
+        let instruction = ctx.build_update_elevation_group_instruction(group);
+        expect_invalid_argument(
+            ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction),
+        );
+    }
+}
+
+#[test]
+fn test_update_elevation_group_requires_owner() {
+    let mut ctx = initialize_lending_market();
+    let unauthorized = Keypair::new();
+    ctx.airdrop(&unauthorized.pubkey(), 1_000_000_000);
+
+    let mut instruction =
+        ctx.build_update_elevation_group_instruction(elevation_group(&Pubkey::new_unique()));
+    instruction.accounts[0].pubkey = unauthorized.pubkey();
+    let err = ctx
+        .send_instruction(vec![unauthorized.insecure_clone()], instruction)
+        .expect_err("only the market owner may configure elevation groups");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(ctx.market_state().elevation_group(1).is_none());
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use plend::{
+    instructions::update_reserve_config::UpdateReserveConfigMode, state::ReserveState, WAD,
+};
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// One whole token at 6 decimals.
+const TOKEN: u64 = 1_000_000;
+
+/// A debt reserve with 1_000 tokens supplied and a 1% borrow fee, and a
+/// borrower who has taken 400 tokens against 1_000 tokens of collateral.
+fn borrow_with_fee(ctx: &mut InitializedMarket) -> (ReserveAccounts, Pubkey, Pubkey) {
+    let collateral = ctx.init_reserve(6);
+    let debt = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.update_reserve_config(
+        &debt.reserve,
+        UpdateReserveConfigMode::BorrowFeeBps,
+        &100u64.to_le_bytes(),
+    );
+    ctx.set_market_price(&collateral.reserve, WAD);
+    ctx.set_market_price(&debt.reserve, WAD);
+
+    let (lender, liquidity, lender_collateral) = ctx.create_lender(&debt, 1_000 * TOKEN);
+    let supply = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &debt,
+        &liquidity,
+        &lender_collateral,
+        1_000 * TOKEN,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], supply)
+        .unwrap();
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let source = ctx.create_token_account(
+        &collateral.liquidity_mint,
+        &borrower.pubkey(),
+        1_000 * TOKEN,
+    );
+    let destination = ctx.create_token_account(&debt.liquidity_mint, &borrower.pubkey(), 0);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &collateral,
+        &source,
+        1_000 * TOKEN,
+    );
+    let refresh = ctx.build_refresh_obligation_instruction(&obligation, &[collateral.reserve]);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &debt,
+        &destination,
+        400 * TOKEN,
+    );
+    ctx.send_instructions(
+        vec![borrower.insecure_clone()],
+        vec![deposit, refresh, borrow],
+    )
+    .expect("borrowing should succeed");
+
+    (debt, obligation, destination)
+}
+
+fn redeem_fees(
+    ctx: &mut InitializedMarket,
+    accounts: &ReserveAccounts,
+    fee_receiver: &Pubkey,
+) -> Result<(), TransactionError> {
+    let refresh = ctx.build_refresh_reserve_instruction(&accounts.reserve);
+    let redeem = ctx.build_redeem_fees_instruction(accounts, fee_receiver);
+    ctx.send_instructions(vec![ctx.fee_payer.insecure_clone()], vec![refresh, redeem])
+        .map(|_| ())
+        .map_err(|err| err.err)
+}
+
+#[test]
+fn test_borrow_fee_is_added_to_debt() {
+    let mut ctx = initialize_lending_market();
+    let (debt, obligation, destination) = borrow_with_fee(&mut ctx);
+
+    assert_eq!(ctx.token_balance(&destination), 400 * TOKEN);
+    let borrowed_amount_wads = ctx.obligation_state(&obligation).borrows[0].borrowed_amount_wads;
+    assert_eq!(borrowed_amount_wads, 404 * TOKEN as u128 * WAD);
+
+    let state = ctx.reserve_state(&debt.reserve);
+    let available_liquidity = state.available_liquidity;
+    let accumulated_protocol_fees_wads = state.accumulated_protocol_fees_wads;
+    assert_eq!(available_liquidity, 600 * TOKEN);
+    assert_eq!(accumulated_protocol_fees_wads, 4 * TOKEN as u128 * WAD);
+    // The fee belongs to the protocol, not suppliers.
+    assert_eq!(
+        state.collateral_to_liquidity(1_000 * TOKEN).unwrap(),
+        1_000 * TOKEN
+    );
+}
+
+#[test]
+fn test_redeem_fees_pays_market_owner() {
+    let mut ctx = initialize_lending_market();
+    let (debt, _, _) = borrow_with_fee(&mut ctx);
+
+    let owner = ctx.fee_payer.pubkey();
+    let fee_receiver = ctx.create_token_account(&debt.liquidity_mint, &owner, 0);
+    redeem_fees(&mut ctx, &debt, &fee_receiver).expect("redeeming fees should succeed");
+
+    assert_eq!(ctx.token_balance(&fee_receiver), 4 * TOKEN);
+    assert_eq!(ctx.token_balance(&debt.liquidity_supply), 596 * TOKEN);
+    let state = ctx.reserve_state(&debt.reserve);
+    let available_liquidity = state.available_liquidity;
+    let accumulated_protocol_fees_wads = state.accumulated_protocol_fees_wads;
+    assert_eq!(available_liquidity, 596 * TOKEN);
+    assert_eq!(accumulated_protocol_fees_wads, 0);
+
+    // Nothing is left to redeem.
+    ctx.warp_to_slot(10);
+    ctx.set_market_price(&debt.reserve, WAD);
+    match redeem_fees(&mut ctx, &debt, &fee_receiver) {
+        Err(TransactionError::InstructionError(_, InstructionError::InsufficientFunds)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_redeem_fees_requires_owner_receiver() {
+    let mut ctx = initialize_lending_market();
+    let (debt, _, _) = borrow_with_fee(&mut ctx);
+
+    let stranger = Keypair::new();
+    let fee_receiver = ctx.create_token_account(&debt.liquidity_mint, &stranger.pubkey(), 0);
+    match redeem_fees(&mut ctx, &debt, &fee_receiver) {
+        Err(TransactionError::InstructionError(_, InstructionError::InvalidAccountData)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_borrow_fee_is_kept_in_wads() {
+    let mut reserve = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    assert!(reserve.borrow_fee(1_000_000).unwrap().is_zero());
+
+    reserve.config.borrow_fee_bps = 30;
+    let fee = reserve.borrow_fee(1_000_000).unwrap().to_wads().unwrap();
+    assert_eq!(fee, 3_000 * WAD);
+    // Fractions of a token are charged as debt rather than rounded away.
+    let fee = reserve.borrow_fee(1).unwrap().to_wads().unwrap();
+    assert_eq!(fee, WAD * 3 / 1_000);
+}
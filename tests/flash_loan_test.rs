@@ -0,0 +1,252 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use plend::{instructions::update_reserve_config::UpdateReserveConfigMode, state::ReserveState};
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// A reserve holding 1_000_000 supplied tokens at the default 0.3% flash
+/// loan fee, and a borrower with `balance` tokens to cover fees.
+fn setup(ctx: &mut InitializedMarket, balance: u64) -> (ReserveAccounts, Keypair, Pubkey) {
+    let accounts = ctx.init_reserve(6);
+    let (lender, liquidity, collateral) = ctx.create_lender(&accounts, 1_000_000);
+    let deposit = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &liquidity,
+        &collateral,
+        1_000_000,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], deposit)
+        .expect("deposit should succeed");
+
+    let borrower = Keypair::new();
+    ctx.airdrop(&borrower.pubkey(), 1_000_000_000);
+    let wallet = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), balance);
+    (accounts, borrower, wallet)
+}
+
+fn expect_invalid_instruction_data(err: TransactionError) {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidInstructionData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_flash_loan_charges_fee() {
+    let mut ctx = initialize_lending_market();
+    let (accounts, borrower, wallet) = setup(&mut ctx, 3_000);
+
+    let borrow = ctx.build_flash_borrow_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &accounts,
+        &wallet,
+        1_000_000,
+    );
+    let repay = ctx.build_flash_repay_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &accounts,
+        &wallet,
+        1_000_000,
+        0,
+    );
+    ctx.send_instructions(vec![borrower.insecure_clone()], vec![borrow, repay])
+        .expect("flash loan should succeed");
+
+    assert_eq!(ctx.token_balance(&wallet), 0);
+    assert_eq!(ctx.token_balance(&accounts.liquidity_supply), 1_003_000);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let available_liquidity = state.available_liquidity;
+    let accumulated_protocol_fees_wads = state.accumulated_protocol_fees_wads;
+    assert_eq!(available_liquidity, 1_003_000);
+    assert_eq!(accumulated_protocol_fees_wads, 3_000 * plend::WAD);
+    // The fee belongs to the protocol, not suppliers.
+    assert_eq!(state.collateral_to_liquidity(1_000_000).unwrap(), 1_000_000);
+}
+
+#[test]
+fn test_deposit_during_flash_loan_keeps_exchange_rate() {
+    let mut ctx = initialize_lending_market();
+    let (accounts, borrower, wallet) = setup(&mut ctx, 3_000);
+    let (lender, liquidity, collateral) = ctx.create_lender(&accounts, 100_000);
+
+    let instructions = vec![
+        ctx.build_flash_borrow_reserve_liquidity_instruction(
+            &borrower.pubkey(),
+            &accounts,
+            &wallet,
+            900_000,
+        ),
+        ctx.build_refresh_reserve_instruction(&accounts.reserve),
+        ctx.build_deposit_reserve_liquidity_instruction(
+            &lender.pubkey(),
+            &accounts,
+            &liquidity,
+            &collateral,
+            100_000,
+        ),
+        ctx.build_flash_repay_reserve_liquidity_instruction(
+            &borrower.pubkey(),
+            &accounts,
+            &wallet,
+            900_000,
+            0,
+        ),
+    ];
+    ctx.send_instructions(
+        vec![borrower.insecure_clone(), lender.insecure_clone()],
+        instructions,
+    )
+    .expect("deposit during a flash loan should succeed");
+
+    // The open loan still counted towards total liquidity, so the deposit
+    // was minted at the same 1:1 rate as the first one.
+    assert_eq!(ctx.token_balance(&collateral), 100_000);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let borrowed_amount_wads = state.borrowed_amount_wads;
+    assert_eq!(borrowed_amount_wads, 0);
+    assert_eq!(state.collateral_to_liquidity(1_000_000).unwrap(), 1_000_000);
+}
+
+#[test]
+fn test_flash_borrow_requires_repay() {
+    let mut ctx = initialize_lending_market();
+    let (accounts, borrower, wallet) = setup(&mut ctx, 3_000);
+
+    let borrow = ctx.build_flash_borrow_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &accounts,
+        &wallet,
+        1_000_000,
+    );
+    let err = ctx
+        .send_instruction(vec![borrower.insecure_clone()], borrow.clone())
+        .expect_err("a flash borrow without a repay should fail");
+    expect_invalid_instruction_data(err.err);
+
+    // Repaying less than was borrowed does not count.
+    let repay = ctx.build_flash_repay_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &accounts,
+        &wallet,
+        999_999,
+        0,
+    );
+    let err = ctx
+        .send_instructions(vec![borrower.insecure_clone()], vec![borrow, repay])
+        .expect_err("the repay must match the borrowed amount");
+    expect_invalid_instruction_data(err.err);
+}
+
+#[test]
+fn test_flash_repay_requires_borrow() {
+    let mut ctx = initialize_lending_market();
+    let (accounts, borrower, wallet) = setup(&mut ctx, 3_000);
+    let other = ctx.init_reserve(6);
+
+    // Index 0 is not a flash borrow.
+    let refresh = ctx.build_refresh_reserve_instruction(&accounts.reserve);
+    let repay = ctx.build_flash_repay_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &accounts,
+        &wallet,
+        1_000,
+        0,
+    );
+    let err = ctx
+        .send_instructions(vec![borrower.insecure_clone()], vec![refresh, repay])
+        .expect_err("a repay needs a matching borrow");
+    expect_invalid_instruction_data(err.err);
+
+    // A borrow from another reserve does not match either.
+    let other_wallet = ctx.create_token_account(&other.liquidity_mint, &borrower.pubkey(), 0);
+    let borrow = ctx.build_flash_borrow_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &other,
+        &other_wallet,
+        1_000,
+    );
+    let repay = ctx.build_flash_repay_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &accounts,
+        &wallet,
+        1_000,
+        0,
+    );
+    let err = ctx
+        .send_instructions(vec![borrower.insecure_clone()], vec![borrow, repay])
+        .expect_err("the repay must be for the borrowed reserve");
+    expect_invalid_instruction_data(err.err);
+}
+
+#[test]
+fn test_flash_loans_cannot_nest() {
+    let mut ctx = initialize_lending_market();
+    let (accounts, borrower, wallet) = setup(&mut ctx, 10_000);
+
+    let instructions = vec![
+        ctx.build_flash_borrow_reserve_liquidity_instruction(
+            &borrower.pubkey(),
+            &accounts,
+            &wallet,
+            1_000,
+        ),
+        ctx.build_flash_borrow_reserve_liquidity_instruction(
+            &borrower.pubkey(),
+            &accounts,
+            &wallet,
+            2_000,
+        ),
+        ctx.build_flash_repay_reserve_liquidity_instruction(
+            &borrower.pubkey(),
+            &accounts,
+            &wallet,
+            2_000,
+            1,
+        ),
+        ctx.build_flash_repay_reserve_liquidity_instruction(
+            &borrower.pubkey(),
+            &accounts,
+            &wallet,
+            1_000,
+            0,
+        ),
+    ];
+    let err = ctx
+        .send_instructions(vec![borrower.insecure_clone()], instructions)
+        .expect_err("nested flash loans should be rejected");
+    expect_invalid_instruction_data(err.err);
+}
+
+#[test]
+fn test_update_flash_loan_fee() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::FlashLoanFeeBps,
+        &0u64.to_le_bytes(),
+    );
+
+    let flash_loan_fee_bps = ctx
+        .reserve_state(&accounts.reserve)
+        .config
+        .flash_loan_fee_bps;
+    assert_eq!(flash_loan_fee_bps, 0);
+}
+
+#[test]
+fn test_flash_loan_fee_rounds_up() {
+    let mut reserve = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    assert_eq!(reserve.flash_loan_fee(1_000_000).unwrap(), 3_000);
+    assert_eq!(reserve.flash_loan_fee(1).unwrap(), 1);
+
+    reserve.config.flash_loan_fee_bps = 0;
+    assert_eq!(reserve.flash_loan_fee(1_000_000).unwrap(), 0);
+}
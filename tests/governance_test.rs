@@ -0,0 +1,506 @@
+mod common;
+
+use common::{initialize_lending_market, serialize_struct, InitializedMarket};
+use plend::{
+    error::PlendError,
+    instructions::{
+        update_lending_market::UpdateLendingMarketMode,
+        update_reserve_config::UpdateReserveConfigMode, PlendInstructions,
+    },
+    state::{ElevationGroup, ReserveAction, ReserveStatus},
+    WAD,
+};
+use solana_instruction::error::InstructionError;
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+const DAY: u64 = 86_400;
+
+fn expect_error(result: Result<(), TransactionError>, expected: InstructionError) {
+    match result {
+        Err(TransactionError::InstructionError(_, err)) if err == expected => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+fn plend_error(error: PlendError) -> InstructionError {
+    InstructionError::Custom(error as u32)
+}
+
+fn queue(
+    ctx: &mut InitializedMarket,
+    target: &Pubkey,
+    mode: u64,
+    value: &[u8],
+) -> Result<Pubkey, TransactionError> {
+    let (instruction, queued_change) =
+        ctx.build_queue_config_change_instruction(target, mode, value);
+    ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .map(|_| queued_change)
+        .map_err(|err| err.err)
+}
+
+fn execute(
+    ctx: &mut InitializedMarket,
+    target: &Pubkey,
+    queued_change: &Pubkey,
+) -> Result<(), TransactionError> {
+    let instruction = ctx.build_execute_config_change_instruction(target, queued_change);
+    ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .map(|_| ())
+        .map_err(|err| err.err)
+}
+
+fn send(
+    ctx: &mut InitializedMarket,
+    signer: &Keypair,
+    instruction: Instruction,
+) -> Result<(), TransactionError> {
+    ctx.send_instruction(vec![signer.insecure_clone()], instruction)
+        .map(|_| ())
+        .map_err(|err| err.err)
+}
+
+/// Without a delay a queued change is executable right away, which is how a
+/// market first opts into the timelock.
+fn set_governance_delay(ctx: &mut InitializedMarket, seconds: u64) {
+    let market = ctx.market_pubkey;
+    let queued_change = queue(
+        ctx,
+        &market,
+        UpdateLendingMarketMode::GovernanceDelaySeconds as u64,
+        &seconds.to_le_bytes(),
+    )
+    .expect("queueing the delay should succeed");
+    execute(ctx, &market, &queued_change).expect("an undelayed change should execute");
+}
+
+#[test]
+fn test_reserve_change_waits_for_delay() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[95],
+    );
+    set_governance_delay(&mut ctx, DAY);
+    let governance_delay_seconds = ctx.market_state().governance_delay_seconds;
+    assert_eq!(governance_delay_seconds, DAY);
+
+    let queued_change = queue(
+        &mut ctx,
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct as u64,
+        &[60],
+    )
+    .expect("queueing should succeed");
+    let change = ctx.queued_config_change(&queued_change);
+    let (queued_at, executable_at) = (change.queued_at, change.executable_at);
+    assert_eq!(executable_at - queued_at, DAY as i64);
+
+    expect_error(
+        execute(&mut ctx, &accounts.reserve, &queued_change),
+        plend_error(PlendError::TimelockNotElapsed),
+    );
+
+    ctx.advance_clock(DAY as i64);
+    execute(&mut ctx, &accounts.reserve, &queued_change)
+        .expect("the change should execute after the delay");
+    assert_eq!(
+        ctx.reserve_state(&accounts.reserve)
+            .config
+            .loan_to_value_pct,
+        60
+    );
+    assert!(!ctx.account_exists(&queued_change));
+}
+
+#[test]
+fn test_delay_requires_queued_changes() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    set_governance_delay(&mut ctx, DAY);
+
+    let instruction = ctx.build_update_reserve_config_instruction(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[60],
+    );
+    expect_error(
+        ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+            .map(|_| ())
+            .map_err(|err| err.err),
+        plend_error(PlendError::TimelockRequired),
+    );
+
+    let instruction = ctx.build_update_risk_council_instruction([15u8; 32]);
+    expect_error(
+        ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+            .map(|_| ())
+            .map_err(|err| err.err),
+        plend_error(PlendError::TimelockRequired),
+    );
+}
+
+#[test]
+fn test_queued_risk_council_change() {
+    let mut ctx = initialize_lending_market();
+    set_governance_delay(&mut ctx, DAY);
+
+    let new_risk = Keypair::new();
+    ctx.airdrop(&new_risk.pubkey(), 1_000_000_000);
+    let market = ctx.market_pubkey;
+    let queued_change = queue(
+        &mut ctx,
+        &market,
+        UpdateLendingMarketMode::RiskCouncil as u64,
+        new_risk.pubkey().as_ref(),
+    )
+    .expect("queueing should succeed");
+    ctx.advance_clock(DAY as i64);
+    execute(&mut ctx, &market, &queued_change).expect("the change should execute");
+
+    // The new council still has to accept.
+    let state = ctx.market_state();
+    assert_eq!(state.risk_council, ctx.risk_council_pubkey());
+    assert_eq!(state.pending_risk_council, new_risk.pubkey().to_bytes());
+    let instruction =
+        ctx.build_accept_instruction(PlendInstructions::AcceptRiskCouncil, &new_risk.pubkey());
+    ctx.send_instruction(vec![new_risk.insecure_clone()], instruction)
+        .expect("the proposed risk council should accept");
+    assert_eq!(
+        ctx.market_state().risk_council,
+        new_risk.pubkey().to_bytes()
+    );
+}
+
+#[test]
+fn test_risk_council_vetoes_change() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[95],
+    );
+    set_governance_delay(&mut ctx, DAY);
+    let queued_change = queue(
+        &mut ctx,
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct as u64,
+        &[90],
+    )
+    .expect("queueing should succeed");
+
+    let stranger = Keypair::new();
+    ctx.airdrop(&stranger.pubkey(), 1_000_000_000);
+    let instruction = ctx.build_veto_config_change_instruction(&stranger.pubkey(), &queued_change);
+    expect_error(
+        ctx.send_instruction(vec![stranger.insecure_clone()], instruction)
+            .map(|_| ())
+            .map_err(|err| err.err),
+        InstructionError::IllegalOwner,
+    );
+
+    let owner_lamports = ctx.lamports(&ctx.fee_payer.pubkey());
+    let rent = ctx.lamports(&queued_change);
+    let instruction =
+        ctx.build_veto_config_change_instruction(&ctx.risk_council.pubkey(), &queued_change);
+    ctx.send_instruction(vec![ctx.risk_council.insecure_clone()], instruction)
+        .expect("the risk council should veto");
+    assert!(!ctx.account_exists(&queued_change));
+    assert_eq!(ctx.lamports(&ctx.fee_payer.pubkey()), owner_lamports + rent);
+
+    ctx.advance_clock(DAY as i64);
+    assert!(execute(&mut ctx, &accounts.reserve, &queued_change).is_err());
+    assert_eq!(
+        ctx.reserve_state(&accounts.reserve)
+            .config
+            .loan_to_value_pct,
+        0
+    );
+}
+
+#[test]
+fn test_queue_rejects_invalid_values() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let market = ctx.market_pubkey;
+
+    expect_error(
+        queue(
+            &mut ctx,
+            &accounts.reserve,
+            UpdateReserveConfigMode::LoanToValuePct as u64,
+            &[101],
+        )
+        .map(|_| ()),
+        InstructionError::InvalidArgument,
+    );
+    expect_error(
+        queue(
+            &mut ctx,
+            &market,
+            UpdateLendingMarketMode::GovernanceDelaySeconds as u64,
+            &(31 * DAY).to_le_bytes(),
+        )
+        .map(|_| ()),
+        InstructionError::InvalidArgument,
+    );
+    expect_error(
+        queue(
+            &mut ctx,
+            &market,
+            UpdateLendingMarketMode::RiskCouncil as u64,
+            &[0; 32],
+        )
+        .map(|_| ()),
+        InstructionError::InvalidArgument,
+    );
+}
+
+#[test]
+fn test_elevation_group_and_manual_price_wait_for_delay() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.set_market_price(&accounts.reserve, WAD);
+    set_governance_delay(&mut ctx, DAY);
+    let owner = ctx.fee_payer.insecure_clone();
+
+    let elevation_group = ElevationGroup {
+        id: 1,
+        loan_to_value_pct: 90,
+        liquidation_threshold_pct: 95,
+        liquidation_bonus_bps: 100,
+        debt_reserve: accounts.reserve.to_bytes(),
+    };
+    let instruction = ctx.build_update_elevation_group_instruction(elevation_group);
+    expect_error(
+        send(&mut ctx, &owner, instruction),
+        plend_error(PlendError::TimelockRequired),
+    );
+
+    let market = ctx.market_pubkey;
+    let queued_change = queue(
+        &mut ctx,
+        &market,
+        UpdateLendingMarketMode::ElevationGroup as u64,
+        serialize_struct(&elevation_group),
+    )
+    .expect("queueing the elevation group should succeed");
+    ctx.advance_clock(DAY as i64);
+    execute(&mut ctx, &market, &queued_change).expect("the change should execute");
+    assert_eq!(ctx.market_state().elevation_groups[0], elevation_group);
+
+    let mint = Pubkey::new_from_array(ctx.reserve_state(&accounts.reserve).liquidity_mint);
+    let manual_price = ctx.manual_price_pubkey(&mint);
+    let new_price_wads = 2 * WAD;
+    let instruction = ctx.build_update_manual_price_instruction(&manual_price, new_price_wads);
+    expect_error(
+        send(&mut ctx, &owner, instruction),
+        plend_error(PlendError::TimelockRequired),
+    );
+    expect_error(
+        queue(&mut ctx, &manual_price, 0, &0u128.to_le_bytes()).map(|_| ()),
+        InstructionError::InvalidArgument,
+    );
+
+    let queued_change = queue(&mut ctx, &manual_price, 0, &new_price_wads.to_le_bytes())
+        .expect("queueing the price should succeed");
+    expect_error(
+        execute(&mut ctx, &manual_price, &queued_change),
+        plend_error(PlendError::TimelockNotElapsed),
+    );
+    ctx.advance_clock(DAY as i64);
+    execute(&mut ctx, &manual_price, &queued_change).expect("the change should execute");
+    let price_wads = ctx.manual_price_state(&manual_price).price_wads;
+    assert_eq!(price_wads, new_price_wads);
+
+    // Republishing the current price keeps it from going stale.
+    ctx.advance_clock(120);
+    let instruction = ctx.build_update_manual_price_instruction(&manual_price, new_price_wads);
+    send(&mut ctx, &owner, instruction).expect("republishing the price should succeed");
+    let publish_timestamp = ctx.manual_price_state(&manual_price).publish_timestamp;
+    assert_eq!(publish_timestamp, ctx.unix_timestamp());
+}
+
+#[test]
+fn test_queued_threshold_decrease_checks_ramp_on_execution() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::AutodeleverageRampBpsPerDay,
+        &1_000u64.to_le_bytes(),
+    );
+    set_governance_delay(&mut ctx, DAY);
+
+    let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+        &ctx.risk_council.pubkey(),
+        &accounts.reserve,
+        1,
+        50,
+    );
+    let risk_council = ctx.risk_council.insecure_clone();
+    send(&mut ctx, &risk_council, instruction).expect("starting the ramp should succeed");
+
+    // The ramp has only just started, but it will have finished by the time
+    // the change can execute.
+    let queued_change = queue(
+        &mut ctx,
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct as u64,
+        &[50],
+    )
+    .expect("queueing the decrease should succeed");
+    ctx.advance_clock(DAY as i64);
+    execute(&mut ctx, &accounts.reserve, &queued_change).expect("the change should execute");
+    let state = ctx.reserve_state(&accounts.reserve);
+    assert_eq!(state.config.liquidation_threshold_pct, 50);
+    assert!(!state.is_autodeleveraging());
+}
+
+#[test]
+fn test_delay_only_lets_owner_tighten_reserve() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[80],
+    );
+    set_governance_delay(&mut ctx, DAY);
+    let owner = ctx.fee_payer.insecure_clone();
+    let risk_council = ctx.risk_council.insecure_clone();
+    let owner_key = owner.pubkey();
+
+    // Freezing and pausing take effect at once.
+    let instruction = ctx.build_set_reserve_status_instruction(
+        &owner_key,
+        &accounts.reserve,
+        ReserveStatus::Frozen,
+        ReserveAction::Borrow as u8,
+    );
+    send(&mut ctx, &owner, instruction).expect("the owner should freeze the reserve");
+
+    for (status, paused_actions) in [
+        (ReserveStatus::Active, ReserveAction::Borrow as u8),
+        (ReserveStatus::Frozen, 0),
+    ] {
+        let instruction = ctx.build_set_reserve_status_instruction(
+            &owner_key,
+            &accounts.reserve,
+            status,
+            paused_actions,
+        );
+        expect_error(
+            send(&mut ctx, &owner, instruction),
+            plend_error(PlendError::TimelockRequired),
+        );
+    }
+
+    let queued_change = queue(
+        &mut ctx,
+        &accounts.reserve,
+        UpdateReserveConfigMode::Status as u64,
+        &[ReserveStatus::Active as u8, 0],
+    )
+    .expect("queueing the status should succeed");
+    ctx.advance_clock(DAY as i64);
+    execute(&mut ctx, &accounts.reserve, &queued_change).expect("the change should execute");
+    let reserve_state = ctx.reserve_state(&accounts.reserve);
+    assert_eq!(reserve_state.status, ReserveStatus::Active as u8);
+    assert_eq!(reserve_state.paused_actions, 0);
+
+    // Starting a ramp or lowering its target takes effect at once.
+    for target_liquidation_threshold_pct in [50, 40] {
+        let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+            &owner_key,
+            &accounts.reserve,
+            1,
+            target_liquidation_threshold_pct,
+        );
+        send(&mut ctx, &owner, instruction).expect("the owner should tighten the ramp");
+    }
+
+    for (enable, target_liquidation_threshold_pct) in [(1, 60), (0, 0)] {
+        let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+            &owner_key,
+            &accounts.reserve,
+            enable,
+            target_liquidation_threshold_pct,
+        );
+        expect_error(
+            send(&mut ctx, &owner, instruction),
+            plend_error(PlendError::TimelockRequired),
+        );
+    }
+
+    // The risk council is not held to the delay.
+    let instruction = ctx.build_set_reserve_autodeleverage_instruction(
+        &risk_council.pubkey(),
+        &accounts.reserve,
+        0,
+        0,
+    );
+    send(&mut ctx, &risk_council, instruction).expect("the risk council should stop the ramp");
+    assert!(!ctx.reserve_state(&accounts.reserve).is_autodeleveraging());
+
+    let instruction =
+        ctx.build_set_reserve_autodeleverage_instruction(&owner_key, &accounts.reserve, 1, 50);
+    send(&mut ctx, &owner, instruction).expect("the owner should start the ramp");
+    let queued_change = queue(
+        &mut ctx,
+        &accounts.reserve,
+        UpdateReserveConfigMode::Autodeleverage as u64,
+        &[0, 0],
+    )
+    .expect("queueing the ramp stop should succeed");
+    ctx.advance_clock(DAY as i64);
+    execute(&mut ctx, &accounts.reserve, &queued_change).expect("the change should execute");
+    assert!(!ctx.reserve_state(&accounts.reserve).is_autodeleveraging());
+}
+
+#[test]
+fn test_queued_interest_change_requires_refreshed_reserve() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    set_governance_delay(&mut ctx, DAY);
+
+    let queued_change = queue(
+        &mut ctx,
+        &accounts.reserve,
+        UpdateReserveConfigMode::ProtocolTakeRatePct as u64,
+        &[10],
+    )
+    .expect("queueing should succeed");
+    ctx.advance_clock(DAY as i64);
+    ctx.warp_to_slot(100);
+    expect_error(
+        execute(&mut ctx, &accounts.reserve, &queued_change),
+        InstructionError::InvalidAccountData,
+    );
+
+    let refresh = ctx.build_refresh_reserve_instruction(&accounts.reserve);
+    let instruction =
+        ctx.build_execute_config_change_instruction(&accounts.reserve, &queued_change);
+    ctx.send_instructions(
+        vec![ctx.fee_payer.insecure_clone()],
+        vec![refresh, instruction],
+    )
+    .expect("the change should execute after a refresh");
+    let protocol_take_rate_pct = ctx
+        .reserve_state(&accounts.reserve)
+        .config
+        .protocol_take_rate_pct;
+    assert_eq!(protocol_take_rate_pct, 10);
+}
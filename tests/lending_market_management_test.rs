@@ -1,11 +1,17 @@
 mod common;
 
-use common::initialize_lending_market;
+use common::{initialize_lending_market, InitializedMarket};
 use plend::instructions::{
-    set_emergency_mode::SetEmergencyModeIxData,
-    update_lending_market_owner::UpdateLendingMarketOwnerIxData,
+    propose_lending_market_owner::ProposeLendingMarketOwnerIxData,
+    set_emergency_mode::SetEmergencyModeIxData, update_lending_market::UpdateLendingMarketMode,
     update_risk_council::UpdateRiskCouncilIxData, PlendInstructions,
 };
+use plend::{
+    helper::{account_init::StateDefinition, utils::DataLen},
+    state::{LendingMarketState, ReserveState},
+    DEFAULT_LIQUIDATION_CLOSE_FACTOR_PCT, DEFAULT_MIN_FULL_LIQUIDATION_VALUE_THRESHOLD_WADS,
+    LENDING_MARKET_VERSION,
+};
 use solana_instruction::{account_meta::AccountMeta, error::InstructionError, Instruction};
 use solana_keypair::Keypair;
 use solana_signer::Signer;
@@ -96,14 +102,25 @@ fn test_set_emergency_mode_requires_authority() {
 #[test]
 fn test_update_risk_council() {
     let mut ctx = initialize_lending_market();
-    let new_risk = [15u8; 32];
-    let instruction = ctx.build_update_risk_council_instruction(new_risk);
+    let new_risk = Keypair::new();
+    ctx.airdrop(&new_risk.pubkey(), 1_000_000_000);
+    let instruction = ctx.build_update_risk_council_instruction(new_risk.pubkey().to_bytes());
 
     ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
         .unwrap();
 
     let state = ctx.market_state();
-    assert_eq!(state.risk_council, new_risk);
+    assert_eq!(state.risk_council, ctx.risk_council_pubkey());
+    assert_eq!(state.pending_risk_council, new_risk.pubkey().to_bytes());
+
+    let instruction =
+        ctx.build_accept_instruction(PlendInstructions::AcceptRiskCouncil, &new_risk.pubkey());
+    ctx.send_instruction(vec![new_risk.insecure_clone()], instruction)
+        .expect("the proposed risk council should accept");
+
+    let state = ctx.market_state();
+    assert_eq!(state.risk_council, new_risk.pubkey().to_bytes());
+    assert_eq!(state.pending_risk_council, [0u8; 32]);
 }
 
 #[test]
@@ -149,20 +166,89 @@ fn test_update_risk_council_requires_owner() {
 }
 
 #[test]
-fn test_update_lending_market_owner() {
+fn test_transfer_lending_market_owner() {
     let mut ctx = initialize_lending_market();
-    let new_owner = [33u8; 32];
-    let instruction = ctx.build_update_owner_instruction(new_owner);
+    let new_owner = Keypair::new();
+    ctx.airdrop(&new_owner.pubkey(), 1_000_000_000);
+    let instruction = ctx.build_propose_owner_instruction(new_owner.pubkey().to_bytes());
+
+    ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .unwrap();
+
+    // Proposing alone does not move ownership.
+    let state = ctx.market_state();
+    assert_eq!(state.lending_market_owner, ctx.owner_pubkey());
+    assert_eq!(state.pending_owner, new_owner.pubkey().to_bytes());
+
+    let instruction = ctx.build_accept_instruction(
+        PlendInstructions::AcceptLendingMarketOwner,
+        &new_owner.pubkey(),
+    );
+    ctx.send_instruction(vec![new_owner.insecure_clone()], instruction)
+        .expect("the pending owner should accept");
+
+    let state = ctx.market_state();
+    assert_eq!(state.lending_market_owner, new_owner.pubkey().to_bytes());
+    assert_eq!(state.pending_owner, [0u8; 32]);
+}
 
+#[test]
+fn test_accept_requires_pending_owner() {
+    let mut ctx = initialize_lending_market();
+    let new_owner = Keypair::new();
+    let stranger = Keypair::new();
+    ctx.airdrop(&stranger.pubkey(), 1_000_000_000);
+    let instruction = ctx.build_propose_owner_instruction(new_owner.pubkey().to_bytes());
     ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
         .unwrap();
 
+    let instruction = ctx.build_accept_instruction(
+        PlendInstructions::AcceptLendingMarketOwner,
+        &stranger.pubkey(),
+    );
+    let err = ctx
+        .send_instruction(vec![stranger.insecure_clone()], instruction)
+        .expect_err("only the pending owner may accept");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_cancel_owner_proposal() {
+    let mut ctx = initialize_lending_market();
+    let new_owner = Keypair::new();
+    ctx.airdrop(&new_owner.pubkey(), 1_000_000_000);
+    let propose_owner = ctx.build_propose_owner_instruction(new_owner.pubkey().to_bytes());
+    let propose_council = ctx.build_update_risk_council_instruction([15u8; 32]);
+    let cancel = ctx.build_cancel_owner_proposal_instruction();
+    ctx.send_instructions(
+        vec![ctx.fee_payer.insecure_clone()],
+        vec![propose_owner, propose_council, cancel],
+    )
+    .unwrap();
+
     let state = ctx.market_state();
-    assert_eq!(state.lending_market_owner, new_owner);
+    assert_eq!(state.pending_owner, [0u8; 32]);
+    assert_eq!(state.pending_risk_council, [0u8; 32]);
+
+    let instruction = ctx.build_accept_instruction(
+        PlendInstructions::AcceptLendingMarketOwner,
+        &new_owner.pubkey(),
+    );
+    let err = ctx
+        .send_instruction(vec![new_owner.insecure_clone()], instruction)
+        .expect_err("a cancelled proposal cannot be accepted");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(ctx.market_state().lending_market_owner, ctx.owner_pubkey());
 }
 
 #[test]
-fn test_update_lending_market_owner_requires_owner() {
+fn test_propose_lending_market_owner_requires_owner() {
     let mut ctx = initialize_lending_market();
     let new_owner = [44u8; 32];
     let unauthorized = Keypair::new();
@@ -170,8 +256,8 @@ fn test_update_lending_market_owner_requires_owner() {
     ctx.airdrop(&unauthorized_pubkey, 1_000_000_000);
 
     let data = encode_instruction(
-        PlendInstructions::UpdateLendingMarketOwner,
-        &UpdateLendingMarketOwnerIxData { new_owner },
+        PlendInstructions::ProposeLendingMarketOwner,
+        &ProposeLendingMarketOwnerIxData { new_owner },
     );
     let instruction = Instruction {
         program_id: ctx.program_id,
@@ -200,3 +286,230 @@ fn test_update_lending_market_owner_requires_owner() {
         other => panic!("unexpected error: {:?}", other),
     }
 }
+
+#[test]
+fn test_retired_owner_update_discriminant_is_rejected() {
+    let mut ctx = initialize_lending_market();
+    let mut data = vec![1u8];
+    data.extend_from_slice(&[44u8; 32]);
+    let instruction = Instruction {
+        program_id: ctx.program_id,
+        accounts: vec![
+            AccountMeta::new(ctx.fee_payer.pubkey(), true),
+            AccountMeta::new(ctx.market_pubkey, false),
+        ],
+        data,
+    };
+
+    let err = ctx
+        .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .expect_err("discriminant 1 is no longer an instruction");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidInstructionData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(ctx.market_state().pending_owner, [0u8; 32]);
+}
+
+#[test]
+fn test_update_lending_market() {
+    let mut ctx = initialize_lending_market();
+    let mut name = [0u8; 32];
+    name[..4].copy_from_slice(b"main");
+    ctx.update_lending_market(UpdateLendingMarketMode::QuoteCurrency, &[7u8; 32]);
+    ctx.update_lending_market(UpdateLendingMarketMode::Name, &name);
+    ctx.update_lending_market(UpdateLendingMarketMode::LiquidationCloseFactorPct, &[50]);
+    ctx.update_lending_market(
+        UpdateLendingMarketMode::MinFullLiquidationValueThreshold,
+        &(10 * plend::WAD).to_le_bytes(),
+    );
+    ctx.update_lending_market(
+        UpdateLendingMarketMode::ReferralFeeBps,
+        &2_500u64.to_le_bytes(),
+    );
+    ctx.update_lending_market(
+        UpdateLendingMarketMode::PriceRefreshTriggerToMaxAgePct,
+        &[80],
+    );
+
+    let state = ctx.market_state();
+    let min_full_liquidation_value_threshold_wads = state.min_full_liquidation_value_threshold_wads;
+    let referral_fee_bps = state.referral_fee_bps;
+    assert_eq!(state.quote_currency, [7u8; 32]);
+    assert_eq!(state.name, name);
+    assert_eq!(state.liquidation_max_debt_close_factor_pct, 50);
+    assert_eq!(min_full_liquidation_value_threshold_wads, 10 * plend::WAD);
+    assert_eq!(referral_fee_bps, 2_500);
+    assert_eq!(state.price_refresh_trigger_to_max_age_pct, 80);
+}
+
+#[test]
+fn test_update_lending_market_rejects_invalid_values() {
+    let mut ctx = initialize_lending_market();
+    let invalid: [(UpdateLendingMarketMode, Vec<u8>); 5] = [
+        (UpdateLendingMarketMode::LiquidationCloseFactorPct, vec![0]),
+        (
+            UpdateLendingMarketMode::LiquidationCloseFactorPct,
+            vec![101],
+        ),
+        (
+            UpdateLendingMarketMode::ReferralFeeBps,
+            10_001u64.to_le_bytes().to_vec(),
+        ),
+        (
+            UpdateLendingMarketMode::PriceRefreshTriggerToMaxAgePct,
+            vec![101],
+        ),
+        (UpdateLendingMarketMode::RiskCouncil, vec![0; 32]),
+    ];
+
+    for (mode, value) in invalid {
+        let instruction = ctx.build_update_lending_market_instruction(mode, &value);
+        let err = ctx
+            .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+            .expect_err("invalid market setting should be rejected");
+        match err.err {
+            TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+            other => panic!("unexpected error for {:?}: {:?}", mode, other),
+        }
+    }
+}
+
+#[test]
+fn test_update_lending_market_requires_owner() {
+    let mut ctx = initialize_lending_market();
+    let unauthorized = Keypair::new();
+    ctx.airdrop(&unauthorized.pubkey(), 1_000_000_000);
+
+    let mut instruction =
+        ctx.build_update_lending_market_instruction(UpdateLendingMarketMode::Name, &[1u8; 32]);
+    instruction.accounts[0] = AccountMeta::new_readonly(unauthorized.pubkey(), true);
+    let err = ctx
+        .send_instruction(vec![unauthorized.insecure_clone()], instruction)
+        .expect_err("only the owner may update the market");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+/// Replaces the market account with `data`, written at an older layout,
+/// and migrates it.
+fn migrate_market(ctx: &mut InitializedMarket, data: Vec<u8>) -> LendingMarketState {
+    let market = ctx.market_pubkey;
+    let program_id = ctx.program_id;
+    ctx.set_foreign_account(&market, &program_id, data);
+
+    let instruction = ctx.build_migrate_lending_market_instruction();
+    ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .expect("migration should succeed");
+
+    assert_eq!(
+        ctx.account_data_len(&market),
+        <LendingMarketState as DataLen>::LEN
+    );
+    assert_eq!(
+        ctx.lamports(&market),
+        ctx.minimum_balance(<LendingMarketState as DataLen>::LEN)
+    );
+    ctx.market_state()
+}
+
+#[test]
+fn test_migrate_lending_market_from_v0() {
+    let mut ctx = initialize_lending_market();
+    let owner = ctx.owner_pubkey();
+    let risk_council = ctx.risk_council_pubkey();
+
+    // The original market: version, owner, quote currency, risk council and
+    // emergency mode.
+    let mut data = 0u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&owner);
+    data.extend_from_slice(&[7; 32]);
+    data.extend_from_slice(&risk_council);
+    data.push(1);
+    assert_eq!(data.len(), 105);
+    assert_eq!(LendingMarketState::V0_LEN, data.len());
+
+    let migrated = migrate_market(&mut ctx, data);
+    let version = migrated.version;
+    let min_full_liquidation_value_threshold_wads =
+        migrated.min_full_liquidation_value_threshold_wads;
+    let governance_delay_seconds = migrated.governance_delay_seconds;
+    let global_allowed_borrow_value_wads = migrated.global_allowed_borrow_value_wads;
+    assert_eq!(migrated.discriminator, LendingMarketState::DISCRIMINATOR);
+    assert_eq!(version, LENDING_MARKET_VERSION);
+    assert_eq!(migrated.lending_market_owner, owner);
+    assert_eq!(migrated.quote_currency, [7; 32]);
+    assert_eq!(migrated.risk_council, risk_council);
+    assert_eq!(migrated.emergency_mode, 1);
+    assert_eq!(
+        migrated.liquidation_max_debt_close_factor_pct,
+        DEFAULT_LIQUIDATION_CLOSE_FACTOR_PCT
+    );
+    assert_eq!(
+        min_full_liquidation_value_threshold_wads,
+        DEFAULT_MIN_FULL_LIQUIDATION_VALUE_THRESHOLD_WADS
+    );
+    assert!(migrated.elevation_group(1).is_none());
+    assert_eq!(migrated.pending_owner, [0u8; 32]);
+    assert_eq!(governance_delay_seconds, 0);
+    assert_eq!(global_allowed_borrow_value_wads, u128::MAX);
+}
+
+#[test]
+fn test_from_v0_fills_defaults() {
+    let mut data = 0u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&[1; 32]);
+    data.extend_from_slice(&[2; 32]);
+    data.extend_from_slice(&[3; 32]);
+    data.push(0);
+    let state = LendingMarketState::from_v0(&data).unwrap();
+    assert_eq!(state, LendingMarketState::new([1; 32], [2; 32], [3; 32]));
+
+    data[0] = 1;
+    assert!(LendingMarketState::from_v0(&data).is_err());
+    assert!(LendingMarketState::from_v0(&data[..LendingMarketState::V0_LEN - 1]).is_err());
+}
+
+#[test]
+fn test_migrate_current_lending_market_fails() {
+    let mut ctx = initialize_lending_market();
+    let version = ctx.market_state().version;
+    assert_eq!(version, LENDING_MARKET_VERSION);
+
+    let instruction = ctx.build_migrate_lending_market_instruction();
+    let err = ctx
+        .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .expect_err("a current market has nothing to migrate");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_market_requires_discriminator() {
+    let mut ctx = initialize_lending_market();
+
+    // A program account of the market's size tagged as something else.
+    let mut state = ctx.market_state();
+    state.discriminator = ReserveState::DISCRIMINATOR;
+    let market = ctx.market_pubkey;
+    let program_id = ctx.program_id;
+    ctx.set_foreign_account(
+        &market,
+        &program_id,
+        common::serialize_struct(&state).to_vec(),
+    );
+
+    let instruction =
+        ctx.build_update_lending_market_instruction(UpdateLendingMarketMode::Name, &[1u8; 32]);
+    let err = ctx
+        .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .expect_err("an account of another type is not a market");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::UninitializedAccount) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
@@ -0,0 +1,279 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use plend::{
+    instructions::{
+        liquidate_obligation::{
+            calculate_liquidation, protocol_liquidation_fee, LiquidationResult,
+        },
+        update_reserve_config::UpdateReserveConfigMode,
+    },
+    state::ReserveState,
+    WAD,
+};
+use solana_instruction::{error::InstructionError, Instruction};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// One whole token at 6 decimals.
+const TOKEN: u64 = 1_000_000;
+
+struct Position {
+    collateral: ReserveAccounts,
+    debt: ReserveAccounts,
+    obligation: Pubkey,
+}
+
+/// A borrower with 1_000 tokens of collateral (LTV 50%, liquidation
+/// threshold 60%, bonus 5%) and a 400 token borrow, both priced at 1.
+fn open_position(ctx: &mut InitializedMarket) -> Position {
+    let collateral = ctx.init_reserve(6);
+    let debt = ctx.init_reserve(6);
+    for (mode, value) in [
+        (UpdateReserveConfigMode::LiquidationThresholdPct, vec![60u8]),
+        (UpdateReserveConfigMode::LoanToValuePct, vec![50u8]),
+        (
+            UpdateReserveConfigMode::LiquidationBonusBps,
+            500u64.to_le_bytes().to_vec(),
+        ),
+    ] {
+        ctx.update_reserve_config(&collateral.reserve, mode, &value);
+    }
+    ctx.set_market_price(&collateral.reserve, WAD);
+    ctx.set_market_price(&debt.reserve, WAD);
+
+    let (lender, liquidity, lender_collateral) = ctx.create_lender(&debt, 1_000 * TOKEN);
+    let supply = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &debt,
+        &liquidity,
+        &lender_collateral,
+        1_000 * TOKEN,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], supply)
+        .unwrap();
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let source = ctx.create_token_account(
+        &collateral.liquidity_mint,
+        &borrower.pubkey(),
+        1_000 * TOKEN,
+    );
+    let destination = ctx.create_token_account(&debt.liquidity_mint, &borrower.pubkey(), 0);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &collateral,
+        &source,
+        1_000 * TOKEN,
+    );
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &debt,
+        &destination,
+        400 * TOKEN,
+    );
+    let refresh = ctx.build_refresh_obligation_instruction(&obligation, &[collateral.reserve]);
+    ctx.send_instructions(
+        vec![borrower.insecure_clone()],
+        vec![deposit, refresh, borrow],
+    )
+    .expect("opening the position should succeed");
+
+    Position {
+        collateral,
+        debt,
+        obligation,
+    }
+}
+
+fn refresh_position(ctx: &InitializedMarket, position: &Position) -> Instruction {
+    ctx.build_refresh_obligation_instruction(
+        &position.obligation,
+        &[position.collateral.reserve, position.debt.reserve],
+    )
+}
+
+fn create_liquidator(
+    ctx: &mut InitializedMarket,
+    position: &Position,
+    amount: u64,
+) -> (Keypair, Pubkey, Pubkey) {
+    let liquidator = Keypair::new();
+    ctx.airdrop(&liquidator.pubkey(), 1_000_000_000);
+    let source =
+        ctx.create_token_account(&position.debt.liquidity_mint, &liquidator.pubkey(), amount);
+    let destination = ctx.create_token_account(
+        &position.collateral.collateral_mint,
+        &liquidator.pubkey(),
+        0,
+    );
+    (liquidator, source, destination)
+}
+
+#[test]
+fn test_liquidate_healthy_obligation_fails() {
+    let mut ctx = initialize_lending_market();
+    let position = open_position(&mut ctx);
+    let (liquidator, source, destination) = create_liquidator(&mut ctx, &position, 400 * TOKEN);
+
+    let liquidate = ctx.build_liquidate_obligation_instruction(
+        &liquidator.pubkey(),
+        &position.obligation,
+        &position.debt,
+        &position.collateral,
+        &source,
+        &destination,
+        400 * TOKEN,
+    );
+    let err = ctx
+        .send_instructions(
+            vec![liquidator.insecure_clone()],
+            vec![refresh_position(&ctx, &position), liquidate],
+        )
+        .expect_err("a healthy obligation cannot be liquidated");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_liquidation_is_capped_by_close_factor() {
+    let mut ctx = initialize_lending_market();
+    let position = open_position(&mut ctx);
+    let (liquidator, source, destination) = create_liquidator(&mut ctx, &position, 400 * TOKEN);
+
+    // At 0.6 the collateral is worth 600, so 400 of debt is past the 360
+    // liquidation threshold.
+    ctx.set_market_price(&position.collateral.reserve, WAD * 6 / 10);
+
+    let liquidate = ctx.build_liquidate_obligation_instruction(
+        &liquidator.pubkey(),
+        &position.obligation,
+        &position.debt,
+        &position.collateral,
+        &source,
+        &destination,
+        400 * TOKEN,
+    );
+    ctx.send_instructions(
+        vec![liquidator.insecure_clone()],
+        vec![refresh_position(&ctx, &position), liquidate],
+    )
+    .expect("liquidation should succeed");
+
+    // The default 20% close factor allows 80 to be repaid, paid back as
+    // 84 worth of collateral: 140 collateral tokens at 0.6.
+    assert_eq!(ctx.token_balance(&source), 320 * TOKEN);
+    assert_eq!(ctx.token_balance(&destination), 140 * TOKEN);
+    let state = ctx.obligation_state(&position.obligation);
+    let borrowed_amount_wads = state.borrows[0].borrowed_amount_wads;
+    let deposited_amount = state.deposits[0].deposited_amount;
+    assert_eq!(borrowed_amount_wads, 320 * TOKEN as u128 * WAD);
+    assert_eq!(deposited_amount, 860 * TOKEN);
+}
+
+#[test]
+fn test_protocol_liquidation_fee_is_burned() {
+    let mut ctx = initialize_lending_market();
+    let position = open_position(&mut ctx);
+    let (liquidator, source, destination) = create_liquidator(&mut ctx, &position, 400 * TOKEN);
+    ctx.update_reserve_config(
+        &position.collateral.reserve,
+        UpdateReserveConfigMode::ProtocolLiquidationFeePct,
+        &[50],
+    );
+    ctx.set_market_price(&position.collateral.reserve, WAD * 6 / 10);
+
+    let liquidate = ctx.build_liquidate_obligation_instruction(
+        &liquidator.pubkey(),
+        &position.obligation,
+        &position.debt,
+        &position.collateral,
+        &source,
+        &destination,
+        400 * TOKEN,
+    );
+    ctx.send_instructions(
+        vec![liquidator.insecure_clone()],
+        vec![refresh_position(&ctx, &position), liquidate],
+    )
+    .expect("liquidation should succeed");
+
+    // 140 collateral tokens are seized, 6.67 of them bonus. Half of the
+    // bonus is redeemed for the protocol instead of going to the liquidator.
+    let protocol_fee = 3_333_333;
+    assert_eq!(ctx.token_balance(&destination), 140 * TOKEN - protocol_fee);
+    assert_eq!(
+        ctx.token_balance(&position.collateral.collateral_supply),
+        860 * TOKEN
+    );
+    let state = ctx.reserve_state(&position.collateral.reserve);
+    let collateral_mint_total_supply = state.collateral_mint_total_supply;
+    let accumulated_protocol_fees_wads = state.accumulated_protocol_fees_wads;
+    assert_eq!(collateral_mint_total_supply, 1_000 * TOKEN - protocol_fee);
+    assert_eq!(accumulated_protocol_fees_wads, protocol_fee as u128 * WAD);
+    // Suppliers keep their exchange rate.
+    assert_eq!(state.collateral_to_liquidity(TOKEN).unwrap(), TOKEN);
+}
+
+#[test]
+fn test_protocol_liquidation_fee_takes_share_of_bonus() {
+    // 105 seized at a 5% bonus is 100 plus 5 of bonus.
+    assert_eq!(protocol_liquidation_fee(105, 500, 0).unwrap(), 0);
+    assert_eq!(protocol_liquidation_fee(105, 500, 40).unwrap(), 2);
+    assert_eq!(protocol_liquidation_fee(105, 500, 100).unwrap(), 5);
+    assert_eq!(protocol_liquidation_fee(105, 0, 100).unwrap(), 0);
+}
+
+#[test]
+fn test_calculate_liquidation_seizes_whole_deposit_when_short() {
+    let mut repay_reserve = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    repay_reserve.market_price_wads = WAD;
+    let mut withdraw_reserve = repay_reserve;
+    withdraw_reserve.market_price_wads = WAD / 10;
+
+    // 1_000 collateral worth 100 cannot pay a 5% bonus on 100 of debt, so
+    // the whole deposit goes and the repayment shrinks to 95.
+    let result = calculate_liquidation(
+        &repay_reserve,
+        &withdraw_reserve,
+        100 * WAD,
+        1_000,
+        100,
+        100,
+        500,
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        LiquidationResult {
+            repay_amount: 95,
+            settle_amount_wads: 95 * WAD,
+            withdraw_amount: 1_000,
+        }
+    );
+
+    // Below the full-liquidation threshold the close factor is bypassed and
+    // the rounded-up debt is settled exactly.
+    withdraw_reserve.market_price_wads = WAD;
+    let result = calculate_liquidation(
+        &repay_reserve,
+        &withdraw_reserve,
+        10 * WAD + 1,
+        1_000,
+        u64::MAX / 2,
+        100,
+        0,
+    )
+    .unwrap();
+    assert_eq!(result.repay_amount, 11);
+    assert_eq!(result.settle_amount_wads, 10 * WAD + 1);
+    assert_eq!(result.withdraw_amount, 11);
+}
@@ -0,0 +1,75 @@
+use plend::{
+    helper::math::{Decimal, Rounding},
+    WAD,
+};
+
+#[test]
+fn test_mul_and_div_respect_rounding() {
+    let third = Decimal::one().try_div_u64(3, Rounding::Down).unwrap();
+    assert_eq!(third.to_wads().unwrap(), WAD / 3);
+    let third_up = Decimal::one().try_div_u64(3, Rounding::Up).unwrap();
+    assert_eq!(third_up.to_wads().unwrap(), WAD / 3 + 1);
+
+    let two_thirds = Decimal::from_u64(2)
+        .try_div(Decimal::from_u64(3), Rounding::Up)
+        .unwrap();
+    assert_eq!(two_thirds.to_wads().unwrap(), 2 * WAD / 3 + 1);
+
+    let tiny = Decimal::from_wads(1);
+    let half = Decimal::from_pct(50);
+    assert_eq!(tiny.try_mul(half, Rounding::Down).unwrap(), Decimal::zero());
+    assert_eq!(
+        tiny.try_mul(half, Rounding::Up).unwrap(),
+        Decimal::from_wads(1)
+    );
+}
+
+#[test]
+fn test_to_u64_rounding() {
+    let value = Decimal::from_wads(10 * WAD + 1);
+    assert_eq!(value.to_u64(Rounding::Down).unwrap(), 10);
+    assert_eq!(value.to_u64(Rounding::Up).unwrap(), 11);
+    assert_eq!(Decimal::from_u64(7).to_u64(Rounding::Up).unwrap(), 7);
+}
+
+#[test]
+fn test_intermediate_products_do_not_overflow() {
+    // Both operands are far past what a u128 product could hold.
+    let large = Decimal::from_u64(u64::MAX);
+    let product = large.try_mul(large, Rounding::Down).unwrap();
+    let back = product.try_div(large, Rounding::Down).unwrap();
+    assert_eq!(back, large);
+    assert!(product.to_wads().is_err());
+}
+
+#[test]
+fn test_checked_errors() {
+    assert!(Decimal::zero().try_sub(Decimal::one()).is_err());
+    assert!(Decimal::one()
+        .try_div(Decimal::zero(), Rounding::Down)
+        .is_err());
+    assert!(Decimal::from_u64(u64::MAX).to_u64(Rounding::Down).is_ok());
+    assert!(Decimal::from_u64(u64::MAX)
+        .try_add(Decimal::from_wads(1))
+        .unwrap()
+        .to_u64(Rounding::Up)
+        .is_err());
+}
+
+#[test]
+fn test_pow_compounds() {
+    assert_eq!(Decimal::from_u64(2).try_pow(0).unwrap(), Decimal::one());
+    assert_eq!(
+        Decimal::from_u64(2).try_pow(10).unwrap(),
+        Decimal::from_u64(1_024)
+    );
+    assert_eq!(
+        Decimal::from_pct(50).try_pow(3).unwrap(),
+        Decimal::from_bps(1_250)
+    );
+
+    // 1% per period over 12 periods, against the well-known 1.1268250301...
+    let rate = Decimal::one().try_add(Decimal::from_pct(1)).unwrap();
+    let compounded = rate.try_pow(12).unwrap().to_wads().unwrap();
+    assert_eq!(compounded / 1_000_000_000, 1_126_825_030);
+}
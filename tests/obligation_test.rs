@@ -0,0 +1,400 @@
+mod common;
+
+use common::initialize_lending_market;
+use plend::{instructions::update_reserve_config::UpdateReserveConfigMode, WAD};
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+#[test]
+fn test_init_obligation() {
+    let mut ctx = initialize_lending_market();
+    let (borrower, obligation) = ctx.create_borrower(0);
+
+    let state = ctx.obligation_state(&obligation);
+    let id = state.id;
+    assert_eq!(state.lending_market, ctx.market_pubkey.to_bytes());
+    assert_eq!(state.owner, borrower.pubkey().to_bytes());
+    assert_eq!(id, 0);
+    assert!(state.is_empty());
+}
+
+#[test]
+fn test_one_owner_can_hold_several_obligations() {
+    let mut ctx = initialize_lending_market();
+    let (borrower, first) = ctx.create_borrower(0);
+
+    let instruction = ctx.build_init_obligation_instruction(&borrower.pubkey(), 7);
+    ctx.send_instruction(vec![borrower.insecure_clone()], instruction)
+        .expect("second obligation should be created");
+
+    let second = ctx.obligation_pubkey(&borrower.pubkey(), 7);
+    assert_ne!(first, second);
+    let id = ctx.obligation_state(&second).id;
+    assert_eq!(id, 7);
+}
+
+#[test]
+fn test_close_obligation_returns_rent() {
+    let mut ctx = initialize_lending_market();
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let rent = ctx.lamports(&obligation);
+    let balance_before = ctx.lamports(&borrower.pubkey());
+
+    let instruction =
+        ctx.build_close_obligation_instruction(&borrower.pubkey(), &obligation, &borrower.pubkey());
+    ctx.send_instruction(vec![borrower.insecure_clone()], instruction)
+        .expect("empty obligation should close");
+
+    assert!(!ctx.account_exists(&obligation));
+    assert!(ctx.lamports(&borrower.pubkey()) > balance_before + rent - 10_000);
+}
+
+#[test]
+fn test_close_obligation_requires_owner() {
+    let mut ctx = initialize_lending_market();
+    let (_borrower, obligation) = ctx.create_borrower(0);
+    let unauthorized = Keypair::new();
+    ctx.airdrop(&unauthorized.pubkey(), 1_000_000_000);
+
+    let instruction = ctx.build_close_obligation_instruction(
+        &unauthorized.pubkey(),
+        &obligation,
+        &unauthorized.pubkey(),
+    );
+    let err = ctx
+        .send_instruction(vec![unauthorized.insecure_clone()], instruction)
+        .expect_err("only the owner may close an obligation");
+
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(ctx.account_exists(&obligation));
+}
+
+#[test]
+fn test_borrow_without_collateral_fails() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.set_market_price(&accounts.reserve, WAD);
+    let (lender, liquidity, collateral) = ctx.create_lender(&accounts, 1_000_000);
+    let deposit = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &liquidity,
+        &collateral,
+        1_000_000,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], deposit)
+        .unwrap();
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let destination = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 0);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &destination,
+        1,
+    );
+    let err = ctx
+        .send_instruction(vec![borrower.insecure_clone()], borrow)
+        .expect_err("an obligation without collateral cannot borrow");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(ctx.token_balance(&destination), 0);
+}
+
+#[test]
+fn test_repay_without_borrow_fails() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let source = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 100);
+
+    let repay = ctx.build_repay_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &source,
+        u64::MAX,
+    );
+    ctx.send_instruction(vec![borrower.insecure_clone()], repay)
+        .expect_err("nothing to repay");
+    assert_eq!(ctx.token_balance(&source), 100);
+}
+
+#[test]
+fn test_deposit_and_withdraw_obligation_collateral() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let liquidity = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 1_000);
+    let collateral = ctx.create_token_account(&accounts.collateral_mint, &borrower.pubkey(), 0);
+    let deposit = ctx.build_deposit_reserve_liquidity_instruction(
+        &borrower.pubkey(),
+        &accounts,
+        &liquidity,
+        &collateral,
+        1_000,
+    );
+    let lock = ctx.build_deposit_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &collateral,
+        600,
+    );
+    ctx.send_instructions(vec![borrower.insecure_clone()], vec![deposit, lock])
+        .expect("collateral deposit should succeed");
+
+    assert_eq!(ctx.token_balance(&collateral), 400);
+    assert_eq!(ctx.token_balance(&accounts.collateral_supply), 600);
+    let state = ctx.obligation_state(&obligation);
+    let deposited_amount = state.deposits[0].deposited_amount;
+    assert_eq!(
+        state.deposits[0].deposit_reserve,
+        accounts.reserve.to_bytes()
+    );
+    assert_eq!(deposited_amount, 600);
+
+    let withdraw = ctx.build_withdraw_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &collateral,
+        u64::MAX,
+    );
+    let refresh = ctx.build_refresh_obligation_instruction(&obligation, &[accounts.reserve]);
+    ctx.send_instructions(vec![borrower.insecure_clone()], vec![refresh, withdraw])
+        .expect("an obligation without borrows can withdraw everything");
+
+    assert_eq!(ctx.token_balance(&collateral), 1_000);
+    assert_eq!(ctx.token_balance(&accounts.collateral_supply), 0);
+    assert!(ctx.obligation_state(&obligation).is_empty());
+}
+
+#[test]
+fn test_withdraw_is_limited_by_loan_to_value() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.set_market_price(&accounts.reserve, WAD);
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let liquidity = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 1_000);
+    let collateral = ctx.create_token_account(&accounts.collateral_mint, &borrower.pubkey(), 0);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        1_000,
+    );
+    ctx.send_instruction(vec![borrower.insecure_clone()], deposit)
+        .expect("combined deposit should succeed");
+
+    assert_eq!(ctx.token_balance(&liquidity), 0);
+    assert_eq!(ctx.token_balance(&collateral), 0);
+    assert_eq!(ctx.token_balance(&accounts.collateral_supply), 1_000);
+    let deposited_amount = ctx.obligation_state(&obligation).deposits[0].deposited_amount;
+    assert_eq!(deposited_amount, 1_000);
+
+    // 1_000 collateral at 50% LTV supports a 400 borrow, which in turn pins
+    // at least 800 collateral in place.
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        400,
+    );
+    let refresh = ctx.build_refresh_obligation_instruction(&obligation, &[accounts.reserve]);
+    ctx.send_instructions(vec![borrower.insecure_clone()], vec![refresh, borrow])
+        .expect("borrow within LTV should succeed");
+
+    let withdraw = ctx.build_withdraw_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &collateral,
+        201,
+    );
+    let refresh = ctx
+        .build_refresh_obligation_instruction(&obligation, &[accounts.reserve, accounts.reserve]);
+    let err = ctx
+        .send_instructions(
+            vec![borrower.insecure_clone()],
+            vec![refresh.clone(), withdraw],
+        )
+        .expect_err("withdraw would push the obligation over its LTV");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let withdraw = ctx.build_withdraw_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &collateral,
+        200,
+    );
+    ctx.send_instructions(vec![borrower.insecure_clone()], vec![refresh, withdraw])
+        .expect("withdraw down to the LTV limit should succeed");
+    assert_eq!(ctx.token_balance(&collateral), 200);
+}
+
+#[test]
+fn test_borrow_requires_refreshed_reserve() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.set_market_price(&accounts.reserve, WAD);
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let liquidity = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 1_000);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        1_000,
+    );
+    ctx.send_instruction(vec![borrower.insecure_clone()], deposit)
+        .unwrap();
+
+    ctx.warp_to_slot(1_000);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        100,
+    );
+    let refresh_obligation =
+        ctx.build_refresh_obligation_instruction(&obligation, &[accounts.reserve]);
+    let err = ctx
+        .send_instructions(
+            vec![borrower.insecure_clone()],
+            vec![refresh_obligation.clone(), borrow.clone()],
+        )
+        .expect_err("a reserve not refreshed this slot cannot be borrowed from");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let refresh_reserve = ctx.build_refresh_reserve_instruction(&accounts.reserve);
+    ctx.send_instructions(
+        vec![borrower.insecure_clone()],
+        vec![refresh_reserve, refresh_obligation, borrow],
+    )
+    .expect("borrow after refresh should succeed");
+    assert_eq!(ctx.token_balance(&liquidity), 100);
+    let last_update_slot = ctx.reserve_state(&accounts.reserve).last_update_slot;
+    assert_eq!(last_update_slot, 1_000);
+}
+
+#[test]
+fn test_refresh_obligation_stores_values() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    for (mode, value) in [
+        (UpdateReserveConfigMode::LiquidationThresholdPct, vec![60u8]),
+        (UpdateReserveConfigMode::LoanToValuePct, vec![50u8]),
+    ] {
+        ctx.update_reserve_config(&accounts.reserve, mode, &value);
+    }
+    ctx.set_market_price(&accounts.reserve, 2 * WAD);
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let liquidity =
+        ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 1_000_000);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        1_000_000,
+    );
+    let refresh = ctx.build_refresh_obligation_instruction(&obligation, &[accounts.reserve]);
+    ctx.send_instructions(vec![borrower.insecure_clone()], vec![deposit, refresh])
+        .expect("refresh should succeed");
+
+    // One whole token at a price of 2.
+    let state = ctx.obligation_state(&obligation);
+    let deposited_value_wads = state.deposited_value_wads;
+    let allowed_borrow_value_wads = state.allowed_borrow_value_wads;
+    let unhealthy_borrow_value_wads = state.unhealthy_borrow_value_wads;
+    let borrowed_value_wads = state.borrowed_value_wads;
+    assert_eq!(deposited_value_wads, 2 * WAD);
+    assert_eq!(allowed_borrow_value_wads, WAD);
+    assert_eq!(unhealthy_borrow_value_wads, WAD * 12 / 10);
+    assert_eq!(borrowed_value_wads, 0);
+    assert!(!state.is_unhealthy());
+}
+
+#[test]
+fn test_borrow_from_stale_obligation_fails() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.set_market_price(&accounts.reserve, WAD);
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let liquidity = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 1_000);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        1_000,
+    );
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        100,
+    );
+    // The deposit leaves the obligation's stored values out of date.
+    let err = ctx
+        .send_instructions(vec![borrower.insecure_clone()], vec![deposit, borrow])
+        .expect_err("a stale obligation cannot borrow");
+    match err.err {
+        TransactionError::InstructionError(1, InstructionError::InvalidAccountData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
@@ -0,0 +1,460 @@
+mod common;
+
+use common::initialize_lending_market;
+use plend::{
+    helper::math::Decimal,
+    instructions::update_reserve_config::UpdateReserveConfigMode,
+    oracle::{
+        aggregate_prices, ManualOracle, OracleAdapter, OracleType, Price, PriceAggregation,
+        PriceGuards, PythEmaOracle, PythPullOracle, SwitchboardOnDemand,
+        PYTH_PRICE_UPDATE_DISCRIMINATOR, PYTH_PRICE_UPDATE_LEN, PYTH_RECEIVER_PROGRAM_ID,
+        SWITCHBOARD_PULL_FEED_DISCRIMINATOR, SWITCHBOARD_PULL_FEED_MIN_LEN,
+    },
+    state::ManualPriceState,
+    WAD,
+};
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+fn pyth_price_update(
+    price: i64,
+    confidence: u64,
+    exponent: i32,
+    publish_time: i64,
+    ema_price: i64,
+    posted_slot: u64,
+) -> Vec<u8> {
+    let mut data = vec![0u8; PYTH_PRICE_UPDATE_LEN];
+    data[..8].copy_from_slice(&PYTH_PRICE_UPDATE_DISCRIMINATOR);
+    // Fully verified, then the 32-byte feed id.
+    data[40] = 1;
+    data[73..81].copy_from_slice(&price.to_le_bytes());
+    data[81..89].copy_from_slice(&confidence.to_le_bytes());
+    data[89..93].copy_from_slice(&exponent.to_le_bytes());
+    data[93..101].copy_from_slice(&publish_time.to_le_bytes());
+    data[109..117].copy_from_slice(&ema_price.to_le_bytes());
+    data[125..133].copy_from_slice(&posted_slot.to_le_bytes());
+    data
+}
+
+fn switchboard_pull_feed(value: i128, std_dev: i128, timestamp: i64, slot: u64) -> Vec<u8> {
+    let mut data = vec![0u8; SWITCHBOARD_PULL_FEED_MIN_LEN];
+    data[..8].copy_from_slice(&SWITCHBOARD_PULL_FEED_DISCRIMINATOR);
+    data[2216..2224].copy_from_slice(&timestamp.to_le_bytes());
+    data[2264..2280].copy_from_slice(&value.to_le_bytes());
+    data[2280..2296].copy_from_slice(&std_dev.to_le_bytes());
+    data[2368..2376].copy_from_slice(&slot.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_pyth_price_update_is_normalized() {
+    let data = pyth_price_update(15_000_000_000, 2_500_000, -8, 1_700, 14_900_000_000, 42);
+    let price = PythPullOracle::parse_price(&data).unwrap();
+    assert_eq!(
+        price,
+        Price {
+            value: 15_000_000_000,
+            exponent: -8,
+            confidence: 2_500_000,
+            publish_slot: 42,
+            publish_time: 1_700,
+            twap: Some(14_900_000_000),
+        }
+    );
+    assert_eq!(price.to_decimal().unwrap().to_wads().unwrap(), 150 * WAD);
+    assert_eq!(
+        price.confidence_to_decimal().unwrap().to_wads().unwrap(),
+        WAD / 40
+    );
+
+    // Partially verified updates and non-positive prices are rejected.
+    let mut partial = data.clone();
+    partial[40] = 0;
+    assert!(PythPullOracle::parse_price(&partial).is_err());
+    assert!(PythPullOracle::parse_price(&pyth_price_update(0, 0, -8, 0, 0, 42)).is_err());
+    assert!(PythPullOracle::parse_price(&pyth_price_update(-1, 0, -8, 0, 0, 42)).is_err());
+    assert!(PythPullOracle::parse_price(&data[..PYTH_PRICE_UPDATE_LEN - 1]).is_err());
+}
+
+#[test]
+fn test_switchboard_pull_feed_is_normalized() {
+    let data = switchboard_pull_feed(3 * WAD as i128 / 2, WAD as i128 / 100, 1_700, 7);
+    let price = SwitchboardOnDemand::parse_price(&data).unwrap();
+    assert_eq!(price.exponent, -18);
+    assert_eq!(price.publish_slot, 7);
+    assert_eq!(price.publish_time, 1_700);
+    assert_eq!(price.twap, None);
+    assert_eq!(price.to_decimal().unwrap().to_wads().unwrap(), 3 * WAD / 2);
+    assert_eq!(
+        price.confidence_to_decimal().unwrap().to_wads().unwrap(),
+        WAD / 100
+    );
+
+    assert!(SwitchboardOnDemand::parse_price(&switchboard_pull_feed(-1, 0, 0, 7)).is_err());
+    let mut wrong_discriminator = data;
+    wrong_discriminator[0] ^= 1;
+    assert!(SwitchboardOnDemand::parse_price(&wrong_discriminator).is_err());
+}
+
+#[test]
+fn test_manual_price_is_normalized() {
+    let state = ManualPriceState::new([1; 32], [2; 32], 5 * WAD, 9, 1_700, 255);
+    let price = ManualOracle::parse_price(bytemuck::bytes_of(&state)).unwrap();
+    assert_eq!(price.to_decimal().unwrap().to_wads().unwrap(), 5 * WAD);
+    assert_eq!(price.confidence, 0);
+    assert_eq!(price.publish_slot, 9);
+    assert_eq!(price.publish_time, 1_700);
+
+    let mut untagged = state;
+    untagged.discriminator = [0; 8];
+    assert!(ManualOracle::parse_price(bytemuck::bytes_of(&untagged)).is_err());
+}
+
+#[test]
+fn test_positive_exponents_scale_up() {
+    let price = Price {
+        value: 12,
+        exponent: 3,
+        confidence: 0,
+        publish_slot: 0,
+        publish_time: 0,
+        twap: None,
+    };
+    assert_eq!(price.to_decimal().unwrap().to_wads().unwrap(), 12_000 * WAD);
+}
+
+#[test]
+fn test_refresh_reserve_reads_manual_price() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    ctx.set_market_price(&accounts.reserve, 3 * WAD);
+    let manual_price = ctx.manual_price_pubkey(&accounts.liquidity_mint);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let market_price_wads = state.market_price_wads;
+    assert_eq!(market_price_wads, 3 * WAD);
+    let sources = state.config.oracle.sources;
+    assert_eq!(sources[0].oracle_type, OracleType::Manual as u8);
+    assert_eq!(sources[0].feed, manual_price.to_bytes());
+
+    ctx.set_market_price(&accounts.reserve, WAD / 2);
+    let price_wads = ctx.manual_price_state(&manual_price).price_wads;
+    let market_price_wads = ctx.reserve_state(&accounts.reserve).market_price_wads;
+    assert_eq!(price_wads, WAD / 2);
+    assert_eq!(market_price_wads, WAD / 2);
+}
+
+#[test]
+fn test_refresh_reserve_rejects_other_feeds() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let other = ctx.init_reserve(6);
+    ctx.set_market_price(&accounts.reserve, WAD);
+    ctx.set_market_price(&other.reserve, 2 * WAD);
+
+    let mut refresh = ctx.build_refresh_reserve_instruction(&accounts.reserve);
+    refresh.accounts[1].pubkey = ctx.manual_price_pubkey(&other.liquidity_mint);
+    let err = ctx
+        .send_instruction(vec![ctx.fee_payer.insecure_clone()], refresh)
+        .expect_err("only the configured feed may price a reserve");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_refresh_reserve_without_oracle_has_no_valid_price() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    ctx.refresh_reserve(&accounts.reserve);
+    assert!(!ctx.reserve_state(&accounts.reserve).is_price_valid());
+}
+
+#[test]
+fn test_update_manual_price_requires_market_owner() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.set_market_price(&accounts.reserve, WAD);
+    let manual_price = ctx.manual_price_pubkey(&accounts.liquidity_mint);
+
+    let unauthorized = Keypair::new();
+    ctx.airdrop(&unauthorized.pubkey(), 1_000_000_000);
+    let mut instruction = ctx.build_update_manual_price_instruction(&manual_price, 2 * WAD);
+    instruction.accounts[0].pubkey = unauthorized.pubkey();
+    let err = ctx
+        .send_instruction(vec![unauthorized.insecure_clone()], instruction)
+        .expect_err("only the market owner may set a manual price");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let price_wads = ctx.manual_price_state(&manual_price).price_wads;
+    assert_eq!(price_wads, WAD);
+}
+
+#[test]
+fn test_price_guards() {
+    let guards = PriceGuards {
+        max_age_seconds: 60,
+        max_confidence_bps: 100,
+        max_twap_divergence_bps: 0,
+    };
+    let price = Price {
+        value: 10_000,
+        exponent: -2,
+        confidence: 100,
+        publish_slot: 0,
+        publish_time: 1_000,
+        twap: None,
+    };
+    assert!(guards.accepts(&price, 1_060).unwrap());
+    assert!(!guards.accepts(&price, 1_061).unwrap());
+    // Prices from the future are treated as brand new.
+    assert!(guards.accepts(&price, 900).unwrap());
+
+    let wide = Price {
+        confidence: 101,
+        ..price
+    };
+    assert!(!guards.accepts(&wide, 1_000).unwrap());
+
+    // Once a TWAP tolerance is set, feeds must provide a TWAP within it.
+    let guards = PriceGuards {
+        max_twap_divergence_bps: 500,
+        ..guards
+    };
+    assert!(!guards.accepts(&price, 1_000).unwrap());
+    let near_twap = Price {
+        twap: Some(9_524),
+        ..price
+    };
+    assert!(guards.accepts(&near_twap, 1_000).unwrap());
+    let far_from_twap = Price {
+        twap: Some(9_500),
+        ..price
+    };
+    assert!(!guards.accepts(&far_from_twap, 1_000).unwrap());
+}
+
+#[test]
+fn test_stale_price_blocks_borrow_but_not_repay_or_deposit() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.set_market_price(&accounts.reserve, WAD);
+
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let liquidity = ctx.create_token_account(&accounts.liquidity_mint, &borrower.pubkey(), 1_000);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        800,
+    );
+    let refresh = ctx.build_refresh_obligation_instruction(&obligation, &[accounts.reserve]);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        100,
+    );
+    ctx.send_instructions(
+        vec![borrower.insecure_clone()],
+        vec![deposit, refresh, borrow],
+    )
+    .expect("borrow at a valid price should succeed");
+
+    // The manual price is now older than the default 60 second limit.
+    ctx.advance_clock(61);
+    ctx.refresh_reserve(&accounts.reserve);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let market_price_wads = state.market_price_wads;
+    assert!(!state.is_price_valid());
+    assert_eq!(market_price_wads, WAD);
+
+    let refresh = ctx
+        .build_refresh_obligation_instruction(&obligation, &[accounts.reserve, accounts.reserve]);
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        1,
+    );
+    let err = ctx
+        .send_instructions(
+            vec![borrower.insecure_clone()],
+            vec![refresh.clone(), borrow],
+        )
+        .expect_err("borrowing needs a valid price");
+    match err.err {
+        TransactionError::InstructionError(1, InstructionError::InvalidAccountData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let repay = ctx.build_repay_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        50,
+    );
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &accounts,
+        &liquidity,
+        100,
+    );
+    ctx.send_instructions(
+        vec![borrower.insecure_clone()],
+        vec![refresh, repay, deposit],
+    )
+    .expect("repaying and depositing work without a valid price");
+    assert!(!ctx.obligation_state(&obligation).has_valid_prices());
+}
+
+#[test]
+fn test_pyth_ema_source_reads_moving_average() {
+    let mut data = pyth_price_update(10_000, 10, -2, 1_700, 9_800, 42);
+    data[117..125].copy_from_slice(&20u64.to_le_bytes());
+    let price = PythEmaOracle::parse_price(&data).unwrap();
+    assert_eq!(price.value, 9_800);
+    assert_eq!(price.confidence, 20);
+    assert_eq!(price.publish_time, 1_700);
+}
+
+#[test]
+fn test_aggregate_prices() {
+    let price = |value: u64| Decimal::from_u64(value);
+
+    let mut prices = [price(103), price(99), price(100)];
+    assert_eq!(
+        aggregate_prices(&mut prices, PriceAggregation::Median, 0).unwrap(),
+        Some(price(100))
+    );
+    let mut prices = [price(100), price(102)];
+    assert_eq!(
+        aggregate_prices(&mut prices, PriceAggregation::Median, 0).unwrap(),
+        Some(price(101))
+    );
+    let mut prices = [price(103), price(99), price(100)];
+    assert_eq!(
+        aggregate_prices(&mut prices, PriceAggregation::PrimaryWithFallback, 0).unwrap(),
+        Some(price(103))
+    );
+    assert_eq!(
+        aggregate_prices(&mut [], PriceAggregation::Median, 0).unwrap(),
+        None
+    );
+
+    // 103 sits 3% from the median: within 300 bps but not 299.
+    let mut prices = [price(103), price(99), price(100)];
+    assert_eq!(
+        aggregate_prices(&mut prices, PriceAggregation::Median, 300).unwrap(),
+        Some(price(100))
+    );
+    let mut prices = [price(103), price(99), price(100)];
+    assert_eq!(
+        aggregate_prices(&mut prices, PriceAggregation::Median, 299).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_refresh_reserve_takes_median_of_sources() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.set_market_price(&accounts.reserve, 99 * WAD);
+
+    let pyth_feed = Pubkey::new_unique();
+    let now = ctx.unix_timestamp();
+    ctx.set_foreign_account(
+        &pyth_feed,
+        &Pubkey::new_from_array(PYTH_RECEIVER_PROGRAM_ID),
+        pyth_price_update(10_000, 0, -2, now, 9_800, 0),
+    );
+    ctx.set_oracle_source(&accounts.reserve, 1, OracleType::Pyth, &pyth_feed);
+    ctx.set_oracle_source(&accounts.reserve, 2, OracleType::PythEma, &pyth_feed);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::MaxOracleSourceDivergenceBps,
+        &150u64.to_le_bytes(),
+    );
+
+    ctx.refresh_reserve(&accounts.reserve);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let market_price_wads = state.market_price_wads;
+    assert!(state.is_price_valid());
+    assert_eq!(market_price_wads, 99 * WAD);
+
+    // A manual price of 110 moves the median to 100 but sits 10% away
+    // from it, so the sources no longer agree.
+    ctx.set_market_price(&accounts.reserve, 110 * WAD);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let market_price_wads = state.market_price_wads;
+    assert!(!state.is_price_valid());
+    assert_eq!(market_price_wads, 99 * WAD);
+
+    // With fallback aggregation and no divergence limit the primary source,
+    // the manual price, wins.
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::OraclePriceAggregation,
+        &[PriceAggregation::PrimaryWithFallback as u8],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::MaxOracleSourceDivergenceBps,
+        &0u64.to_le_bytes(),
+    );
+    ctx.refresh_reserve(&accounts.reserve);
+    let market_price_wads = ctx.reserve_state(&accounts.reserve).market_price_wads;
+    assert_eq!(market_price_wads, 110 * WAD);
+}
+
+#[test]
+fn test_unscalable_source_falls_back_to_others() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.set_market_price(&accounts.reserve, 99 * WAD);
+
+    // 10^100 does not fit a Decimal.
+    let pyth_feed = Pubkey::new_unique();
+    let now = ctx.unix_timestamp();
+    ctx.set_foreign_account(
+        &pyth_feed,
+        &Pubkey::new_from_array(PYTH_RECEIVER_PROGRAM_ID),
+        pyth_price_update(1, 0, 100, now, 1, 0),
+    );
+    ctx.set_oracle_source(&accounts.reserve, 1, OracleType::Pyth, &pyth_feed);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::OraclePriceAggregation,
+        &[PriceAggregation::Median as u8],
+    );
+
+    ctx.refresh_reserve(&accounts.reserve);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let market_price_wads = state.market_price_wads;
+    assert!(state.is_price_valid());
+    assert_eq!(market_price_wads, 99 * WAD);
+}
@@ -0,0 +1,283 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use plend::{
+    helper::math::Decimal,
+    instructions::{
+        update_lending_market::UpdateLendingMarketMode,
+        update_reserve_config::UpdateReserveConfigMode,
+    },
+    state::ReserveState,
+    REFERRER_FEE_PCT_INHERIT, WAD,
+};
+use solana_instruction::{account_meta::AccountMeta, error::InstructionError};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// One whole token at 6 decimals.
+const TOKEN: u64 = 1_000_000;
+
+struct Market {
+    collateral: ReserveAccounts,
+    debt: ReserveAccounts,
+    referrer: Keypair,
+    referrer_state: Pubkey,
+}
+
+/// A debt reserve with 1_000 tokens supplied, a 1% borrow fee of which
+/// referrers get 40%, and a referrer registered on it.
+fn setup(ctx: &mut InitializedMarket) -> Market {
+    let collateral = ctx.init_reserve(6);
+    let debt = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.update_reserve_config(
+        &debt.reserve,
+        UpdateReserveConfigMode::BorrowFeeBps,
+        &100u64.to_le_bytes(),
+    );
+    ctx.update_reserve_config(
+        &debt.reserve,
+        UpdateReserveConfigMode::ReferrerFeePct,
+        &[40],
+    );
+    ctx.set_market_price(&collateral.reserve, WAD);
+    ctx.set_market_price(&debt.reserve, WAD);
+
+    let (lender, liquidity, lender_collateral) = ctx.create_lender(&debt, 1_000 * TOKEN);
+    let supply = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &debt,
+        &liquidity,
+        &lender_collateral,
+        1_000 * TOKEN,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], supply)
+        .unwrap();
+
+    let referrer = Keypair::new();
+    ctx.airdrop(&referrer.pubkey(), 1_000_000_000);
+    let init = ctx.build_init_referrer_state_instruction(&referrer.pubkey(), &debt.reserve);
+    ctx.send_instruction(vec![referrer.insecure_clone()], init)
+        .expect("referrer state creation should succeed");
+    let referrer_state = ctx.referrer_state_pubkey(&referrer.pubkey(), &debt.reserve);
+
+    Market {
+        collateral,
+        debt,
+        referrer,
+        referrer_state,
+    }
+}
+
+/// Borrows 400 tokens against 1_000 tokens of collateral, crediting
+/// `referrer_state` if given.
+fn borrow(
+    ctx: &mut InitializedMarket,
+    market: &Market,
+    referrer_state: Option<Pubkey>,
+) -> Result<(), TransactionError> {
+    let (borrower, obligation) = ctx.create_borrower(0);
+    let source = ctx.create_token_account(
+        &market.collateral.liquidity_mint,
+        &borrower.pubkey(),
+        1_000 * TOKEN,
+    );
+    let destination = ctx.create_token_account(&market.debt.liquidity_mint, &borrower.pubkey(), 0);
+    let deposit = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &market.collateral,
+        &source,
+        1_000 * TOKEN,
+    );
+    let refresh =
+        ctx.build_refresh_obligation_instruction(&obligation, &[market.collateral.reserve]);
+    let mut borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.pubkey(),
+        &obligation,
+        &market.debt,
+        &destination,
+        400 * TOKEN,
+    );
+    if let Some(referrer_state) = referrer_state {
+        borrow
+            .accounts
+            .push(AccountMeta::new(referrer_state, false));
+    }
+    ctx.send_instructions(
+        vec![borrower.insecure_clone()],
+        vec![deposit, refresh, borrow],
+    )
+    .map(|_| ())
+    .map_err(|err| err.err)
+}
+
+#[test]
+fn test_borrow_credits_referrer() {
+    let mut ctx = initialize_lending_market();
+    let market = setup(&mut ctx);
+    borrow(&mut ctx, &market, Some(market.referrer_state)).expect("borrow should succeed");
+
+    // The 4 token fee is split 1.6 to the referrer and 2.4 to the protocol.
+    let referrer_state = ctx.referrer_state(&market.referrer_state);
+    let unclaimed_fees_wads = referrer_state.unclaimed_fees_wads;
+    assert_eq!(unclaimed_fees_wads, 1_600_000 * WAD);
+    let state = ctx.reserve_state(&market.debt.reserve);
+    let accumulated_protocol_fees_wads = state.accumulated_protocol_fees_wads;
+    let accumulated_referrer_fees_wads = state.accumulated_referrer_fees_wads;
+    assert_eq!(accumulated_protocol_fees_wads, 2_400_000 * WAD);
+    assert_eq!(accumulated_referrer_fees_wads, 1_600_000 * WAD);
+    assert_eq!(
+        state.collateral_to_liquidity(1_000 * TOKEN).unwrap(),
+        1_000 * TOKEN
+    );
+
+    let referrer = market.referrer.pubkey();
+    let destination = ctx.create_token_account(&market.debt.liquidity_mint, &referrer, 0);
+    let withdraw =
+        ctx.build_withdraw_referrer_fees_instruction(&referrer, &market.debt, &destination);
+    ctx.send_instruction(vec![market.referrer.insecure_clone()], withdraw)
+        .expect("referrer should withdraw its fees");
+
+    assert_eq!(ctx.token_balance(&destination), 1_600_000);
+    let unclaimed_fees_wads = ctx
+        .referrer_state(&market.referrer_state)
+        .unclaimed_fees_wads;
+    assert_eq!(unclaimed_fees_wads, 0);
+    let state = ctx.reserve_state(&market.debt.reserve);
+    let available_liquidity = state.available_liquidity;
+    let accumulated_referrer_fees_wads = state.accumulated_referrer_fees_wads;
+    assert_eq!(available_liquidity, 600 * TOKEN - 1_600_000);
+    assert_eq!(accumulated_referrer_fees_wads, 0);
+}
+
+#[test]
+fn test_borrow_without_referrer_pays_protocol() {
+    let mut ctx = initialize_lending_market();
+    let market = setup(&mut ctx);
+    borrow(&mut ctx, &market, None).expect("borrow should succeed");
+
+    let state = ctx.reserve_state(&market.debt.reserve);
+    let accumulated_protocol_fees_wads = state.accumulated_protocol_fees_wads;
+    let accumulated_referrer_fees_wads = state.accumulated_referrer_fees_wads;
+    assert_eq!(accumulated_protocol_fees_wads, 4 * TOKEN as u128 * WAD);
+    assert_eq!(accumulated_referrer_fees_wads, 0);
+}
+
+#[test]
+fn test_market_referral_fee_is_the_default() {
+    let mut ctx = initialize_lending_market();
+    let market = setup(&mut ctx);
+    ctx.update_reserve_config(
+        &market.debt.reserve,
+        UpdateReserveConfigMode::ReferrerFeePct,
+        &[REFERRER_FEE_PCT_INHERIT],
+    );
+    ctx.update_lending_market(
+        UpdateLendingMarketMode::ReferralFeeBps,
+        &2_500u64.to_le_bytes(),
+    );
+    borrow(&mut ctx, &market, Some(market.referrer_state)).expect("borrow should succeed");
+
+    // A quarter of the 4 token fee.
+    let unclaimed_fees_wads = ctx
+        .referrer_state(&market.referrer_state)
+        .unclaimed_fees_wads;
+    assert_eq!(unclaimed_fees_wads, TOKEN as u128 * WAD);
+}
+
+#[test]
+fn test_zero_referrer_fee_pays_referrer_nothing() {
+    let mut ctx = initialize_lending_market();
+    let market = setup(&mut ctx);
+    ctx.update_reserve_config(
+        &market.debt.reserve,
+        UpdateReserveConfigMode::ReferrerFeePct,
+        &[0],
+    );
+    ctx.update_lending_market(
+        UpdateLendingMarketMode::ReferralFeeBps,
+        &2_500u64.to_le_bytes(),
+    );
+    borrow(&mut ctx, &market, Some(market.referrer_state)).expect("borrow should succeed");
+
+    let unclaimed_fees_wads = ctx
+        .referrer_state(&market.referrer_state)
+        .unclaimed_fees_wads;
+    assert_eq!(unclaimed_fees_wads, 0);
+    let accumulated_protocol_fees_wads = ctx
+        .reserve_state(&market.debt.reserve)
+        .accumulated_protocol_fees_wads;
+    assert_eq!(accumulated_protocol_fees_wads, 4 * TOKEN as u128 * WAD);
+}
+
+#[test]
+fn test_referrer_share() {
+    let mut reserve = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    assert_eq!(reserve.config.referrer_fee_pct, REFERRER_FEE_PCT_INHERIT);
+    assert_eq!(
+        reserve.config.referrer_share(2_500),
+        Decimal::from_bps(2_500)
+    );
+
+    reserve.config.referrer_fee_pct = 0;
+    assert!(reserve.config.referrer_share(2_500).is_zero());
+    reserve.config.referrer_fee_pct = 40;
+    assert_eq!(reserve.config.referrer_share(2_500), Decimal::from_pct(40));
+}
+
+#[test]
+fn test_referrer_state_must_match_reserve() {
+    let mut ctx = initialize_lending_market();
+    let market = setup(&mut ctx);
+
+    // Registered on the collateral reserve instead of the borrowed one.
+    let init = ctx.build_init_referrer_state_instruction(
+        &market.referrer.pubkey(),
+        &market.collateral.reserve,
+    );
+    ctx.send_instruction(vec![market.referrer.insecure_clone()], init)
+        .unwrap();
+    let other = ctx.referrer_state_pubkey(&market.referrer.pubkey(), &market.collateral.reserve);
+    match borrow(&mut ctx, &market, Some(other)) {
+        Err(TransactionError::InstructionError(_, InstructionError::InvalidAccountData)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_only_referrer_withdraws_fees() {
+    let mut ctx = initialize_lending_market();
+    let market = setup(&mut ctx);
+    borrow(&mut ctx, &market, Some(market.referrer_state)).expect("borrow should succeed");
+
+    let stranger = Keypair::new();
+    ctx.airdrop(&stranger.pubkey(), 1_000_000_000);
+    let destination = ctx.create_token_account(&market.debt.liquidity_mint, &stranger.pubkey(), 0);
+    let mut withdraw = ctx.build_withdraw_referrer_fees_instruction(
+        &stranger.pubkey(),
+        &market.debt,
+        &destination,
+    );
+    withdraw.accounts[3] = AccountMeta::new(market.referrer_state, false);
+    let err = ctx
+        .send_instruction(vec![stranger.insecure_clone()], withdraw)
+        .expect_err("only the referrer may withdraw its fees");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
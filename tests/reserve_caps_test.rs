@@ -0,0 +1,264 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use plend::{
+    error::PlendError,
+    instructions::{
+        update_lending_market::UpdateLendingMarketMode,
+        update_reserve_config::UpdateReserveConfigMode,
+    },
+    state::RollingCap,
+    WAD,
+};
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+/// One whole token at 6 decimals.
+const TOKEN: u64 = 1_000_000;
+const HOUR: u64 = 3_600;
+
+fn expect_error(result: Result<(), TransactionError>, expected: PlendError) {
+    match result {
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code)))
+            if code == expected as u32 => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+fn rolling_cap(max_amount: u64, interval_seconds: u64) -> Vec<u8> {
+    let mut value = max_amount.to_le_bytes().to_vec();
+    value.extend_from_slice(&interval_seconds.to_le_bytes());
+    value
+}
+
+fn deposit(
+    ctx: &mut InitializedMarket,
+    accounts: &ReserveAccounts,
+    amount: u64,
+) -> Result<(Keypair, Pubkey, Pubkey), TransactionError> {
+    let (lender, liquidity, collateral) = ctx.create_lender(accounts, amount);
+    let instruction = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        accounts,
+        &liquidity,
+        &collateral,
+        amount,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], instruction)
+        .map(|_| (lender, liquidity, collateral))
+        .map_err(|err| err.err)
+}
+
+/// `lender` is the keypair, liquidity and collateral accounts `deposit`
+/// returned.
+fn redeem(
+    ctx: &mut InitializedMarket,
+    accounts: &ReserveAccounts,
+    lender: &(Keypair, Pubkey, Pubkey),
+    amount: u64,
+) -> Result<(), TransactionError> {
+    let (lender, liquidity, collateral) = lender;
+    let instruction = ctx.build_redeem_reserve_collateral_instruction(
+        &lender.pubkey(),
+        accounts,
+        collateral,
+        liquidity,
+        amount,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], instruction)
+        .map(|_| ())
+        .map_err(|err| err.err)
+}
+
+struct Borrower {
+    collateral: ReserveAccounts,
+    debt: ReserveAccounts,
+    keypair: Keypair,
+    obligation: Pubkey,
+    destination: Pubkey,
+}
+
+/// A borrower with 1_000 tokens of collateral at 50% LTV against a debt
+/// reserve holding 1_000 supplied tokens, both priced at 1.
+fn open_borrower(ctx: &mut InitializedMarket) -> Borrower {
+    let collateral = ctx.init_reserve(6);
+    let debt = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &collateral.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+    ctx.set_market_price(&collateral.reserve, WAD);
+    ctx.set_market_price(&debt.reserve, WAD);
+    deposit(ctx, &debt, 1_000 * TOKEN).expect("supplying should succeed");
+
+    let (keypair, obligation) = ctx.create_borrower(0);
+    let source =
+        ctx.create_token_account(&collateral.liquidity_mint, &keypair.pubkey(), 1_000 * TOKEN);
+    let destination = ctx.create_token_account(&debt.liquidity_mint, &keypair.pubkey(), 0);
+    let instruction = ctx.build_deposit_reserve_liquidity_and_obligation_collateral_instruction(
+        &keypair.pubkey(),
+        &obligation,
+        &collateral,
+        &source,
+        1_000 * TOKEN,
+    );
+    ctx.send_instruction(vec![keypair.insecure_clone()], instruction)
+        .expect("depositing collateral should succeed");
+
+    Borrower {
+        collateral,
+        debt,
+        keypair,
+        obligation,
+        destination,
+    }
+}
+
+fn borrow(
+    ctx: &mut InitializedMarket,
+    borrower: &Borrower,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    let refresh = ctx.build_refresh_obligation_instruction(
+        &borrower.obligation,
+        &[borrower.collateral.reserve, borrower.debt.reserve],
+    );
+    let borrow = ctx.build_borrow_obligation_liquidity_instruction(
+        &borrower.keypair.pubkey(),
+        &borrower.obligation,
+        &borrower.debt,
+        &borrower.destination,
+        amount,
+    );
+    ctx.send_instructions(
+        vec![borrower.keypair.insecure_clone()],
+        vec![refresh, borrow],
+    )
+    .map(|_| ())
+    .map_err(|err| err.err)
+}
+
+#[test]
+fn test_deposit_limit() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::DepositLimit,
+        &(1_000 * TOKEN).to_le_bytes(),
+    );
+
+    deposit(&mut ctx, &accounts, 1_000 * TOKEN).expect("deposit up to the limit should succeed");
+    expect_error(
+        deposit(&mut ctx, &accounts, 1).map(|_| ()),
+        PlendError::DepositLimitExceeded,
+    );
+}
+
+#[test]
+fn test_withdrawal_cap_resets_each_interval() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::WithdrawalCap,
+        &rolling_cap(100 * TOKEN, HOUR),
+    );
+    let lender = deposit(&mut ctx, &accounts, 1_000 * TOKEN).expect("deposit should succeed");
+
+    // Start at the beginning of a window so the test does not straddle two.
+    let into_window = ctx.unix_timestamp().rem_euclid(HOUR as i64);
+    ctx.advance_clock(HOUR as i64 - into_window);
+
+    redeem(&mut ctx, &accounts, &lender, 60 * TOKEN).expect("redeem under the cap should succeed");
+    expect_error(
+        redeem(&mut ctx, &accounts, &lender, 41 * TOKEN),
+        PlendError::WithdrawalCapReached,
+    );
+    redeem(&mut ctx, &accounts, &lender, 40 * TOKEN).expect("redeem up to the cap should succeed");
+
+    ctx.advance_clock(HOUR as i64);
+    redeem(&mut ctx, &accounts, &lender, 100 * TOKEN).expect("a new interval should reset the cap");
+    assert_eq!(ctx.token_balance(&lender.1), 200 * TOKEN);
+}
+
+#[test]
+fn test_borrow_limit() {
+    let mut ctx = initialize_lending_market();
+    let borrower = open_borrower(&mut ctx);
+    ctx.update_reserve_config(
+        &borrower.debt.reserve,
+        UpdateReserveConfigMode::BorrowLimit,
+        &(300 * TOKEN).to_le_bytes(),
+    );
+
+    expect_error(
+        borrow(&mut ctx, &borrower, 301 * TOKEN),
+        PlendError::BorrowLimitExceeded,
+    );
+    borrow(&mut ctx, &borrower, 300 * TOKEN).expect("borrow up to the limit should succeed");
+}
+
+#[test]
+fn test_borrow_cap() {
+    let mut ctx = initialize_lending_market();
+    let borrower = open_borrower(&mut ctx);
+    ctx.update_reserve_config(
+        &borrower.debt.reserve,
+        UpdateReserveConfigMode::BorrowCap,
+        &rolling_cap(100 * TOKEN, 24 * HOUR),
+    );
+
+    borrow(&mut ctx, &borrower, 100 * TOKEN).expect("borrow up to the cap should succeed");
+    expect_error(
+        borrow(&mut ctx, &borrower, TOKEN),
+        PlendError::BorrowCapReached,
+    );
+}
+
+#[test]
+fn test_global_borrow_limit() {
+    let mut ctx = initialize_lending_market();
+    let borrower = open_borrower(&mut ctx);
+    ctx.update_lending_market(
+        UpdateLendingMarketMode::GlobalAllowedBorrowValue,
+        &(250 * WAD).to_le_bytes(),
+    );
+
+    borrow(&mut ctx, &borrower, 200 * TOKEN).expect("borrow under the limit should succeed");
+    // Counts what the obligation already owes.
+    expect_error(
+        borrow(&mut ctx, &borrower, 51 * TOKEN),
+        PlendError::GlobalBorrowLimitExceeded,
+    );
+}
+
+#[test]
+fn test_rolling_cap_windows() {
+    let mut cap = RollingCap {
+        max_amount: 100,
+        interval_seconds: 60,
+        window_start: 0,
+        amount_in_window: 0,
+    };
+    assert!(cap.try_consume(70, 130));
+    let window_start = cap.window_start;
+    assert_eq!(window_start, 120);
+    assert!(!cap.try_consume(31, 179));
+    assert!(cap.try_consume(30, 179));
+    // The next window starts empty.
+    assert!(cap.try_consume(100, 180));
+    assert!(!cap.try_consume(1, 239));
+
+    cap.interval_seconds = 0;
+    assert!(cap.try_consume(u64::MAX, 240));
+}
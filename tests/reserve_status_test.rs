@@ -0,0 +1,235 @@
+mod common;
+
+use common::{initialize_lending_market, InitializedMarket, ReserveAccounts};
+use plend::{
+    error::PlendError,
+    state::{ReserveAction, ReserveState, ReserveStatus},
+};
+use solana_instruction::error::InstructionError;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+fn set_status(
+    ctx: &mut InitializedMarket,
+    authority: &Keypair,
+    reserve: &Pubkey,
+    status: ReserveStatus,
+    paused_actions: u8,
+) -> Result<(), TransactionError> {
+    let instruction = ctx.build_set_reserve_status_instruction(
+        &authority.pubkey(),
+        reserve,
+        status,
+        paused_actions,
+    );
+    ctx.send_instruction(vec![authority.insecure_clone()], instruction)
+        .map(|_| ())
+        .map_err(|err| err.err)
+}
+
+/// Deposits `amount` from a new lender and returns the lender's keypair,
+/// liquidity and collateral accounts.
+fn deposit(
+    ctx: &mut InitializedMarket,
+    accounts: &ReserveAccounts,
+    amount: u64,
+) -> Result<(Keypair, Pubkey, Pubkey), TransactionError> {
+    let (lender, liquidity, collateral) = ctx.create_lender(accounts, amount);
+    let instruction = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        accounts,
+        &liquidity,
+        &collateral,
+        amount,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], instruction)
+        .map(|_| (lender, liquidity, collateral))
+        .map_err(|err| err.err)
+}
+
+fn redeem(
+    ctx: &mut InitializedMarket,
+    accounts: &ReserveAccounts,
+    lender: &(Keypair, Pubkey, Pubkey),
+    amount: u64,
+) -> Result<(), TransactionError> {
+    let (lender, liquidity, collateral) = lender;
+    let instruction = ctx.build_redeem_reserve_collateral_instruction(
+        &lender.pubkey(),
+        accounts,
+        collateral,
+        liquidity,
+        amount,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], instruction)
+        .map(|_| ())
+        .map_err(|err| err.err)
+}
+
+fn expect_error(result: Result<(), TransactionError>, expected: InstructionError) {
+    match result {
+        Err(TransactionError::InstructionError(_, err)) if err == expected => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_paused_action_is_rejected() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let lender = deposit(&mut ctx, &accounts, 1_000).expect("deposit should succeed");
+
+    let risk_council = ctx.risk_council.insecure_clone();
+    set_status(
+        &mut ctx,
+        &risk_council,
+        &accounts.reserve,
+        ReserveStatus::Active,
+        ReserveAction::Deposit as u8,
+    )
+    .expect("risk council should pause deposits");
+
+    expect_error(
+        deposit(&mut ctx, &accounts, 1_000).map(|_| ()),
+        InstructionError::Custom(PlendError::ReserveActionPaused as u32),
+    );
+    redeem(&mut ctx, &accounts, &lender, 500).expect("withdrawals are not paused");
+}
+
+#[test]
+fn test_frozen_reserve_only_unwinds() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let lender = deposit(&mut ctx, &accounts, 1_000).expect("deposit should succeed");
+
+    let owner = ctx.fee_payer.insecure_clone();
+    set_status(
+        &mut ctx,
+        &owner,
+        &accounts.reserve,
+        ReserveStatus::Frozen,
+        0,
+    )
+    .expect("market owner should freeze the reserve");
+    expect_error(
+        deposit(&mut ctx, &accounts, 1_000).map(|_| ()),
+        InstructionError::Custom(PlendError::ReserveNotActive as u32),
+    );
+    redeem(&mut ctx, &accounts, &lender, 500).expect("a frozen reserve still pays out");
+
+    set_status(
+        &mut ctx,
+        &owner,
+        &accounts.reserve,
+        ReserveStatus::Active,
+        0,
+    )
+    .expect("market owner should unfreeze the reserve");
+    deposit(&mut ctx, &accounts, 1_000).expect("deposit should succeed again");
+}
+
+#[test]
+fn test_obsolete_reserve_cannot_be_reactivated() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    let risk_council = ctx.risk_council.insecure_clone();
+    set_status(
+        &mut ctx,
+        &risk_council,
+        &accounts.reserve,
+        ReserveStatus::Obsolete,
+        0,
+    )
+    .expect("risk council should retire the reserve");
+    expect_error(
+        set_status(
+            &mut ctx,
+            &risk_council,
+            &accounts.reserve,
+            ReserveStatus::Active,
+            0,
+        ),
+        InstructionError::InvalidArgument,
+    );
+
+    // Pausing actions on it is still possible.
+    set_status(
+        &mut ctx,
+        &risk_council,
+        &accounts.reserve,
+        ReserveStatus::Obsolete,
+        ReserveAction::ALL,
+    )
+    .expect("risk council should pause the obsolete reserve");
+    let state = ctx.reserve_state(&accounts.reserve);
+    let paused_actions = state.paused_actions;
+    assert_eq!(paused_actions, ReserveAction::ALL);
+}
+
+#[test]
+fn test_set_reserve_status_requires_authority() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    let stranger = Keypair::new();
+    ctx.airdrop(&stranger.pubkey(), 1_000_000_000);
+    expect_error(
+        set_status(
+            &mut ctx,
+            &stranger,
+            &accounts.reserve,
+            ReserveStatus::Frozen,
+            0,
+        ),
+        InstructionError::IllegalOwner,
+    );
+    let risk_council = ctx.risk_council.insecure_clone();
+    expect_error(
+        set_status(
+            &mut ctx,
+            &risk_council,
+            &accounts.reserve,
+            ReserveStatus::Active,
+            1 << 6,
+        ),
+        InstructionError::InvalidArgument,
+    );
+}
+
+#[test]
+fn test_check_action() {
+    let mut reserve = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    assert!(reserve.check_action(ReserveAction::Borrow).is_ok());
+
+    reserve.paused_actions = ReserveAction::Borrow as u8 | ReserveAction::Repay as u8;
+    assert_eq!(
+        reserve.check_action(ReserveAction::Repay),
+        Err(PlendError::ReserveActionPaused.into())
+    );
+    assert!(reserve.check_action(ReserveAction::Liquidate).is_ok());
+
+    reserve.paused_actions = 0;
+    reserve.status = ReserveStatus::Obsolete as u8;
+    for action in [
+        ReserveAction::Deposit,
+        ReserveAction::Borrow,
+        ReserveAction::FlashLoan,
+    ] {
+        assert_eq!(
+            reserve.check_action(action),
+            Err(PlendError::ReserveNotActive.into())
+        );
+    }
+    for action in [
+        ReserveAction::Withdraw,
+        ReserveAction::Repay,
+        ReserveAction::Liquidate,
+    ] {
+        assert!(reserve.check_action(action).is_ok());
+    }
+}
@@ -0,0 +1,457 @@
+mod common;
+
+use common::{initialize_lending_market, TOKEN_PROGRAM};
+use pinocchio::program_error::ProgramError;
+use plend::{
+    helper::math::Decimal, instructions::update_reserve_config::UpdateReserveConfigMode,
+    oracle::OracleType, state::ReserveState, SLOTS_PER_YEAR, WAD,
+};
+use solana_instruction::{account_meta::AccountMeta, error::InstructionError};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction_error::TransactionError;
+
+#[test]
+fn test_init_reserve() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    let state = ctx.reserve_state(&accounts.reserve);
+    let cumulative_borrow_rate_wads = state.cumulative_borrow_rate_wads;
+    let available_liquidity = state.available_liquidity;
+    let borrowed_amount_wads = state.borrowed_amount_wads;
+
+    assert_eq!(state.lending_market, ctx.market_pubkey.to_bytes());
+    assert_eq!(state.liquidity_mint, accounts.liquidity_mint.to_bytes());
+    assert_eq!(
+        state.liquidity_supply_vault,
+        accounts.liquidity_supply.to_bytes()
+    );
+    assert_eq!(state.collateral_mint, accounts.collateral_mint.to_bytes());
+    assert_eq!(state.token_program, TOKEN_PROGRAM.to_bytes());
+    assert_eq!(state.liquidity_mint_decimals, 6);
+    assert_eq!(available_liquidity, 0);
+    assert_eq!(borrowed_amount_wads, 0);
+    assert_eq!(cumulative_borrow_rate_wads, WAD);
+
+    assert!(ctx.account_exists(&accounts.liquidity_supply));
+    assert!(ctx.account_exists(&accounts.collateral_mint));
+    assert_eq!(ctx.token_balance(&accounts.liquidity_supply), 0);
+}
+
+#[test]
+fn test_init_reserve_requires_market_owner() {
+    let mut ctx = initialize_lending_market();
+    let unauthorized = Keypair::new();
+    ctx.airdrop(&unauthorized.pubkey(), 1_000_000_000);
+
+    let liquidity_mint = ctx.create_mint(9);
+    let accounts = ctx.reserve_accounts(liquidity_mint);
+    let mut instruction = ctx.build_init_reserve_instruction(&accounts);
+    instruction.accounts[0] = AccountMeta::new(unauthorized.pubkey(), true);
+
+    let err = ctx
+        .send_instruction(vec![unauthorized.insecure_clone()], instruction)
+        .expect_err("only the market owner may list a reserve");
+
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(!ctx.account_exists(&accounts.reserve));
+}
+
+#[test]
+fn test_init_reserve_rejects_mint_with_extensions() {
+    let mut ctx = initialize_lending_market();
+
+    // A Token-2022 mint with extensions is padded to a token account's size
+    // and followed by its account type and TLV entries.
+    let liquidity_mint = Pubkey::new_unique();
+    let mut data = vec![0u8; 170];
+    data[44] = 6;
+    data[45] = 1;
+    data[165] = 1;
+    ctx.set_foreign_account(&liquidity_mint, &TOKEN_PROGRAM, data);
+
+    let accounts = ctx.reserve_accounts(liquidity_mint);
+    let instruction = ctx.build_init_reserve_instruction(&accounts);
+    let err = ctx
+        .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .expect_err("mints with extensions cannot be listed");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(!ctx.account_exists(&accounts.reserve));
+}
+
+#[test]
+fn test_init_reserve_twice_fails() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    let instruction = ctx.build_init_reserve_instruction(&accounts);
+    ctx.send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+        .expect_err("a mint can only be listed once per market");
+}
+
+#[test]
+fn test_deposit_and_redeem_reserve_liquidity() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let (lender, liquidity, collateral) = ctx.create_lender(&accounts, 1_000_000);
+
+    let deposit = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &liquidity,
+        &collateral,
+        400_000,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], deposit)
+        .expect("deposit should succeed");
+
+    assert_eq!(ctx.token_balance(&liquidity), 600_000);
+    assert_eq!(ctx.token_balance(&collateral), 400_000);
+    assert_eq!(ctx.token_balance(&accounts.liquidity_supply), 400_000);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let available_liquidity = state.available_liquidity;
+    let collateral_supply = state.collateral_mint_total_supply;
+    assert_eq!(available_liquidity, 400_000);
+    assert_eq!(collateral_supply, 400_000);
+
+    let redeem = ctx.build_redeem_reserve_collateral_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &collateral,
+        &liquidity,
+        150_000,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], redeem)
+        .expect("redeem should succeed");
+
+    assert_eq!(ctx.token_balance(&liquidity), 750_000);
+    assert_eq!(ctx.token_balance(&collateral), 250_000);
+    let state = ctx.reserve_state(&accounts.reserve);
+    let available_liquidity = state.available_liquidity;
+    assert_eq!(available_liquidity, 250_000);
+}
+
+#[test]
+fn test_redeem_more_than_available_fails() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let (lender, liquidity, collateral) = ctx.create_lender(&accounts, 1_000);
+
+    let deposit = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &liquidity,
+        &collateral,
+        1_000,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], deposit)
+        .unwrap();
+
+    let redeem = ctx.build_redeem_reserve_collateral_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &collateral,
+        &liquidity,
+        1_001,
+    );
+    let err = ctx
+        .send_instruction(vec![lender.insecure_clone()], redeem)
+        .expect_err("cannot redeem more than the reserve holds");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::InsufficientFunds) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_deposit_and_redeem_require_refreshed_reserve() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let (lender, liquidity, collateral) = ctx.create_lender(&accounts, 1_000);
+
+    let deposit = ctx.build_deposit_reserve_liquidity_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &liquidity,
+        &collateral,
+        500,
+    );
+    ctx.send_instruction(vec![lender.insecure_clone()], deposit.clone())
+        .expect("deposit should succeed");
+
+    ctx.warp_to_slot(100);
+    let redeem = ctx.build_redeem_reserve_collateral_instruction(
+        &lender.pubkey(),
+        &accounts,
+        &collateral,
+        &liquidity,
+        100,
+    );
+    for instruction in [deposit.clone(), redeem.clone()] {
+        let err = ctx
+            .send_instruction(vec![lender.insecure_clone()], instruction)
+            .expect_err("a reserve not refreshed this slot cannot be used");
+        match err.err {
+            TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    let refresh = ctx.build_refresh_reserve_instruction(&accounts.reserve);
+    ctx.send_instructions(
+        vec![lender.insecure_clone()],
+        vec![refresh, deposit, redeem],
+    )
+    .expect("deposit and redeem after refresh should succeed");
+    assert_eq!(ctx.token_balance(&liquidity), 100);
+}
+
+#[test]
+fn test_loan_to_value_cannot_exceed_liquidation_threshold() {
+    let mut state = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    assert!(state.config.validate_collateral_params().is_ok());
+
+    state.config.loan_to_value_pct = 50;
+    assert!(state.config.validate_collateral_params().is_err());
+    state.config.liquidation_threshold_pct = 50;
+    assert!(state.config.validate_collateral_params().is_ok());
+    state.config.liquidation_threshold_pct = 101;
+    assert!(state.config.validate_collateral_params().is_err());
+}
+
+#[test]
+fn test_borrowable_liquidity_excludes_fees() {
+    let mut state = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    state.available_liquidity = 1_000;
+    assert_eq!(state.borrowable_liquidity().unwrap(), 1_000);
+
+    state.accumulated_protocol_fees_wads = 100 * WAD;
+    state.accumulated_referrer_fees_wads = WAD / 2;
+    assert_eq!(state.borrowable_liquidity().unwrap(), 899);
+
+    state.accumulated_protocol_fees_wads = 2_000 * WAD;
+    assert_eq!(state.borrowable_liquidity().unwrap(), 0);
+}
+
+#[test]
+fn test_market_value_rejects_oversized_decimals() {
+    let mut state = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 19, 0, 255,
+    );
+    state.market_price_wads = 2 * WAD;
+    assert_eq!(
+        state.market_value(Decimal::from_u64(10_000_000_000_000_000_000)),
+        Ok(Decimal::from_u64(2))
+    );
+
+    state.liquidity_mint_decimals = 20;
+    assert_eq!(
+        state.market_value(Decimal::from_u64(1)),
+        Err(ProgramError::ArithmeticOverflow)
+    );
+}
+
+#[test]
+fn test_collateral_exchange_rate_includes_borrows() {
+    let mut state = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 0, 255,
+    );
+    assert_eq!(state.liquidity_to_collateral(500).unwrap(), 500);
+
+    state.available_liquidity = 800;
+    state.borrowed_amount_wads = 400 * WAD;
+    state.collateral_mint_total_supply = 1_000;
+
+    // 1_200 liquidity backs 1_000 collateral tokens.
+    assert_eq!(state.total_liquidity().unwrap(), Decimal::from_u64(1_200));
+    assert_eq!(state.liquidity_to_collateral(600).unwrap(), 500);
+    assert_eq!(state.collateral_to_liquidity(500).unwrap(), 600);
+    // Rounding always favours the reserve.
+    assert_eq!(state.liquidity_to_collateral(5).unwrap(), 4);
+    assert_eq!(state.collateral_to_liquidity(3).unwrap(), 3);
+}
+
+#[test]
+fn test_update_reserve_config() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[80],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[75],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::BorrowFactorPct,
+        &120u64.to_le_bytes(),
+    );
+    let feed = Pubkey::new_unique();
+    ctx.set_oracle_source(&accounts.reserve, 1, OracleType::Pyth, &feed);
+
+    let state = ctx.reserve_state(&accounts.reserve);
+    let borrow_factor_pct = state.config.borrow_factor_pct;
+    assert_eq!(state.config.loan_to_value_pct, 75);
+    assert_eq!(borrow_factor_pct, 120);
+    let sources = state.config.oracle.sources;
+    assert!(sources[0].is_empty());
+    assert_eq!(sources[1].oracle_type, OracleType::Pyth as u8);
+    assert_eq!(sources[1].feed, feed.to_bytes());
+}
+
+#[test]
+fn test_update_reserve_config_rejects_invalid_values() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LiquidationThresholdPct,
+        &[60],
+    );
+    ctx.update_reserve_config(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[50],
+    );
+
+    for (mode, value) in [
+        (UpdateReserveConfigMode::LoanToValuePct, vec![101u8]),
+        (UpdateReserveConfigMode::LoanToValuePct, vec![61u8]),
+        (UpdateReserveConfigMode::LiquidationThresholdPct, vec![49u8]),
+        (
+            UpdateReserveConfigMode::BorrowFactorPct,
+            99u64.to_le_bytes().to_vec(),
+        ),
+        (UpdateReserveConfigMode::OracleSource, vec![0u8, 5]),
+        (UpdateReserveConfigMode::OracleSource, vec![3u8, 1]),
+        (UpdateReserveConfigMode::OraclePriceAggregation, vec![2u8]),
+        (UpdateReserveConfigMode::ElevationGroups, vec![1u8, 9]),
+        (
+            UpdateReserveConfigMode::AutodeleverageRampBpsPerDay,
+            0u64.to_le_bytes().to_vec(),
+        ),
+        (
+            UpdateReserveConfigMode::FlashLoanFeeBps,
+            10_001u64.to_le_bytes().to_vec(),
+        ),
+        (
+            UpdateReserveConfigMode::BorrowFeeBps,
+            10_001u64.to_le_bytes().to_vec(),
+        ),
+        (
+            UpdateReserveConfigMode::ProtocolLiquidationFeePct,
+            vec![101u8],
+        ),
+        (UpdateReserveConfigMode::ReferrerFeePct, vec![101u8]),
+        (UpdateReserveConfigMode::WithdrawalCap, {
+            let mut value = 1u64.to_le_bytes().to_vec();
+            value.extend_from_slice(&u64::MAX.to_le_bytes());
+            value
+        }),
+        (
+            UpdateReserveConfigMode::MaxPriceConfidenceBps,
+            10_001u64.to_le_bytes().to_vec(),
+        ),
+        (
+            UpdateReserveConfigMode::LiquidationThresholdPct,
+            vec![101u8],
+        ),
+        (
+            UpdateReserveConfigMode::LiquidationBonusBps,
+            5_001u64.to_le_bytes().to_vec(),
+        ),
+    ] {
+        let instruction =
+            ctx.build_update_reserve_config_instruction(&accounts.reserve, mode, &value);
+        let err = ctx
+            .send_instruction(vec![ctx.fee_payer.insecure_clone()], instruction)
+            .expect_err("invalid reserve config should be rejected");
+        match err.err {
+            TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_update_reserve_config_requires_market_owner() {
+    let mut ctx = initialize_lending_market();
+    let accounts = ctx.init_reserve(6);
+    let unauthorized = Keypair::new();
+    ctx.airdrop(&unauthorized.pubkey(), 1_000_000_000);
+
+    let mut instruction = ctx.build_update_reserve_config_instruction(
+        &accounts.reserve,
+        UpdateReserveConfigMode::LoanToValuePct,
+        &[90],
+    );
+    instruction.accounts[0] = AccountMeta::new_readonly(unauthorized.pubkey(), true);
+
+    let err = ctx
+        .send_instruction(vec![unauthorized.insecure_clone()], instruction)
+        .expect_err("only the market owner may configure a reserve");
+    match err.err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_accrue_interest_compounds_and_takes_protocol_fee() {
+    let mut state = ReserveState::new(
+        [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], [0; 32], 6, 100, 255,
+    );
+    // A flat 10% APR regardless of utilization.
+    for point in state.config.borrow_rate_curve.points.iter_mut() {
+        point.borrow_rate_bps = 1_000;
+    }
+    state.config.protocol_take_rate_pct = 20;
+    state.available_liquidity = 1_000_000;
+    state.borrowed_amount_wads = 1_000_000 * WAD;
+
+    state.accrue_interest(100).unwrap();
+    let borrowed_amount_wads = state.borrowed_amount_wads;
+    assert_eq!(borrowed_amount_wads, 1_000_000 * WAD);
+
+    state.accrue_interest(100 + SLOTS_PER_YEAR).unwrap();
+    let cumulative_borrow_rate_wads = state.cumulative_borrow_rate_wads;
+    let borrowed_amount_wads = state.borrowed_amount_wads;
+    let accumulated_protocol_fees_wads = state.accumulated_protocol_fees_wads;
+    let last_update_slot = state.last_update_slot;
+
+    // Per-slot compounding of 10% over a year lands on e^0.1 = 1.10517...
+    assert_eq!(cumulative_borrow_rate_wads / 100_000_000_000_000, 11_051);
+    assert_eq!(borrowed_amount_wads / WAD, 1_105_170);
+    let interest = borrowed_amount_wads - 1_000_000 * WAD;
+    assert_eq!(accumulated_protocol_fees_wads, interest / 5);
+    assert_eq!(last_update_slot, 100 + SLOTS_PER_YEAR);
+    assert!(state.is_fresh(100 + SLOTS_PER_YEAR));
+
+    // Suppliers keep the other 80% of the interest.
+    let supplier_liquidity_wads =
+        1_000_000 * WAD + borrowed_amount_wads - accumulated_protocol_fees_wads;
+    assert_eq!(
+        state.total_liquidity().unwrap().to_wads().unwrap(),
+        supplier_liquidity_wads
+    );
+
+    assert!(state.accrue_interest(99).is_err());
+}